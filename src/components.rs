@@ -1,8 +1,60 @@
-use printpdf::Op;
+use crate::drawing::DrawOp;
 
+pub mod code128;
+pub mod ean;
 pub mod label;
+pub mod layout;
+pub mod link;
+pub mod picture;
+pub mod qr_code;
 pub mod table;
 
+/// Extent of a component in mm, `ascent` is the distance from its top edge down to the y it is rendered at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Size {
+    pub width: f32,
+    pub height: f32,
+    pub ascent: f32
+}
+
 pub trait Component {
-    fn render_at(&self, x: f32, y: f32) -> Vec<Op>;
-}
\ No newline at end of file
+    fn render_at(&self, x: f32, y: f32) -> Vec<DrawOp>;
+
+    /// Size of the component as rendered by `render_at`, or by `render_within` when a width is given
+    fn measure(&self, width: Option<f32>) -> Size;
+
+    /// Renders the component wrapped to the given width, components with a fixed size ignore the width
+    fn render_within(&self, x: f32, y: f32, _width: f32) -> Vec<DrawOp> {
+        self.render_at(x, y)
+    }
+
+    /// Distance from the first to the last baseline when wrapped to the given width
+    fn wrapped_height(&self, _width: f32) -> f32 {
+        0.0
+    }
+
+    /// Width of the text before the separator, or of all of it without one, for lining up numbers
+    /// at the decimal separator. `None` when the component is not text.
+    fn width_before(&self, _separator: char) -> Option<f32> {
+        None
+    }
+}
+
+/// Renders the component with its top edge at `top`, wrapped to the width when one is given
+pub fn render_top(component: &dyn Component, x: f32, top: f32, width: Option<f32>) -> Vec<DrawOp> {
+    let y = top - component.measure(width).ascent;
+    match width {
+        Some(width) => component.render_within(x, y, width),
+        None => component.render_at(x, y)
+    }
+}
+
+/// Fills the given rectangles (x, y of the lower left corner, width, height in mm) with the current fill color
+pub(crate) fn fill_rectangles(rectangles: &[(f32, f32, f32, f32)]) -> DrawOp {
+    DrawOp::FillRectangles(rectangles.to_vec())
+}
+
+/// Strokes a line through the given points (x, y in mm) with the current outline color and thickness
+pub(crate) fn stroke_line(points: &[(f32, f32)]) -> DrawOp {
+    DrawOp::Line(points.to_vec())
+}
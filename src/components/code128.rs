@@ -0,0 +1,163 @@
+use anyhow::Error;
+use crate::drawing::DrawOp;
+use super::{fill_rectangles, Component, Size};
+
+/// Bar and space widths (in modules) of the Code 128 symbols, indexed by symbol value
+const PATTERNS: [&str; 107] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212", "221213",
+    "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221", "223211", "221132",
+    "221231", "213212", "223112", "312131", "311222", "321122", "321221", "312212", "322112", "322211",
+    "212123", "212321", "232121", "111323", "131123", "131321", "112313", "132113", "132311", "211313",
+    "231113", "231311", "112133", "112331", "132131", "113123", "113321", "133121", "313121", "211331",
+    "231131", "213113", "213311", "213131", "311123", "311321", "331121", "312113", "312311", "332111",
+    "314111", "221411", "431111", "111224", "111422", "121124", "121421", "141122", "141221", "112214",
+    "112412", "122114", "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111",
+    "111242", "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311", "113141",
+    "114131", "311141", "411131", "211412", "211214", "211232", "2331112"
+];
+const CODE_B: usize = 100;
+const CODE_C: usize = 99;
+const START_B: usize = 104;
+const START_C: usize = 105;
+const STOP: usize = 106;
+
+#[derive(Clone, Copy, PartialEq)]
+enum CodeSet {
+    B,
+    C
+}
+
+/// Code 128 barcode drawn as vector bars, (x, y) is the top left corner of the barcode
+pub struct Code128 {
+    pub module_width: f32,
+    pub height: f32,
+    symbols: Vec<usize>
+}
+
+impl Code128 {
+    /// Encodes printable ASCII text, runs of at least four digits use the denser code set C
+    pub fn new(data: &str, module_width: f32, height: f32) -> Result<Code128, Error> {
+        if data.is_empty() || !data.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+            return Err(Error::msg(format!("Code 128 supports only printable ASCII characters: {}", data)));
+        }
+        let bytes = data.as_bytes();
+        let mut symbols = Vec::new();
+        let mut code_set: Option<CodeSet> = None;
+        let mut index = 0;
+        while index < bytes.len() {
+            let digit_run = bytes[index..].iter().take_while(|b| b.is_ascii_digit()).count();
+            if digit_run >= 4 && (digit_run.is_multiple_of(2) || code_set == Some(CodeSet::C)) {
+                Code128::switch_to(&mut symbols, &mut code_set, CodeSet::C);
+                for pair in bytes[index..index + digit_run - digit_run % 2].chunks(2) {
+                    symbols.push(((pair[0] - b'0') * 10 + (pair[1] - b'0')) as usize);
+                }
+                index += digit_run - digit_run % 2;
+            } else {
+                Code128::switch_to(&mut symbols, &mut code_set, CodeSet::B);
+                symbols.push((bytes[index] - b' ') as usize);
+                index += 1;
+            }
+        }
+        let checksum = symbols.iter().enumerate()
+            .map(|(position, value)| position.max(1) * value)
+            .sum::<usize>() % 103;
+        symbols.push(checksum);
+        symbols.push(STOP);
+        Ok(Code128 {
+            module_width,
+            height,
+            symbols
+        })
+    }
+
+    fn switch_to(symbols: &mut Vec<usize>, code_set: &mut Option<CodeSet>, target: CodeSet) {
+        match (*code_set, target) {
+            (None, CodeSet::B) => symbols.push(START_B),
+            (None, CodeSet::C) => symbols.push(START_C),
+            (Some(CodeSet::C), CodeSet::B) => symbols.push(CODE_B),
+            (Some(CodeSet::B), CodeSet::C) => symbols.push(CODE_C),
+            _ => {}
+        }
+        *code_set = Some(target);
+    }
+
+    /// Widths of alternating bars and spaces in modules, starting with a bar
+    fn bar_widths(&self) -> Vec<u32> {
+        self.symbols.iter()
+            .flat_map(|&symbol| PATTERNS[symbol].chars().map(|c| c.to_digit(10).unwrap()))
+            .collect()
+    }
+
+    pub fn width(&self) -> f32 {
+        self.bar_widths().iter().sum::<u32>() as f32 * self.module_width
+    }
+}
+
+impl Component for Code128 {
+    fn render_at(&self, x: f32, y: f32) -> Vec<DrawOp> {
+        let mut rectangles = Vec::new();
+        let mut current_x_offset = x;
+        for (index, width) in self.bar_widths().into_iter().enumerate() {
+            let width = width as f32 * self.module_width;
+            if index % 2 == 0 {
+                rectangles.push((current_x_offset, y - self.height, width, self.height));
+            }
+            current_x_offset += width;
+        }
+        vec![fill_rectangles(&rectangles)]
+    }
+
+    fn measure(&self, _width: Option<f32>) -> Size {
+        Size { width: self.width(), height: self.height, ascent: 0.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns_have_eleven_modules() {
+        for (value, pattern) in PATTERNS.iter().enumerate() {
+            let modules: u32 = pattern.chars().map(|c| c.to_digit(10).unwrap()).sum();
+            assert_eq!(modules, if value == STOP { 13 } else { 11 }, "pattern {}", value);
+        }
+    }
+
+    #[test]
+    fn test_code128_set_c_symbols() {
+        let barcode = Code128::new("123456", 0.3, 10.0).unwrap();
+        // Start C, 12, 34, 56, checksum (105 + 12 + 2 * 34 + 3 * 56) % 103 = 44, stop
+        assert_eq!(barcode.symbols, vec![105, 12, 34, 56, 44, 106]);
+    }
+
+    #[test]
+    fn test_code128_width() {
+        let barcode = Code128::new("479440520200360820048831500000000868516259619897100612", 0.3, 10.0).unwrap();
+        assert_eq!(barcode.symbols.len(), 30);
+        assert!((barcode.width() - 332.0 * 0.3).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_code128_set_b_symbols() {
+        let barcode = Code128::new("Inv-1", 0.3, 10.0).unwrap();
+        // Start B, I, n, v, -, 1, checksum (104 + 41 + 2 * 78 + 3 * 86 + 4 * 13 + 5 * 17) % 103 = 78, stop
+        assert_eq!(barcode.symbols, vec![104, 41, 78, 86, 13, 17, 78, 106]);
+    }
+
+    #[test]
+    fn test_code128_switches_to_set_c_for_digit_runs() {
+        let barcode = Code128::new("AB123456", 0.3, 10.0).unwrap();
+        assert_eq!(&barcode.symbols[..6], &[104, 33, 34, 99, 12, 34]);
+        let odd_digits = Code128::new("12345", 0.3, 10.0).unwrap();
+        assert_eq!(&odd_digits.symbols[..4], &[104, 17, 99, 23]);
+    }
+
+    #[test]
+    fn test_code128_rejects_invalid_data() {
+        assert!(Code128::new("Hyvä", 0.3, 10.0).is_err());
+        assert!(Code128::new("12\n34", 0.3, 10.0).is_err());
+        assert!(Code128::new("", 0.3, 10.0).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use anyhow::Error;
+use crate::drawing::DrawOp;
+use super::{fill_rectangles, Component, Size};
+
+/// Left hand odd parity ("L") patterns, the even parity ("G") and right hand ("R") patterns are derived from them
+const L_PATTERNS: [&str; 10] = [
+    "0001101", "0011001", "0010011", "0111101", "0100011", "0110001", "0101111", "0111011", "0110111", "0001011"
+];
+/// Parity of the left hand digits of EAN-13 encoding the first digit, 'L' for odd and 'G' for even
+const EAN_13_PARITIES: [&str; 10] = [
+    "LLLLLL", "LLGLGG", "LLGGLG", "LLGGGL", "LGLLGG", "LGGLLG", "LGGGLL", "LGLGLG", "LGLGGL", "LGGLGL"
+];
+const GUARD_EXTENSION_MODULES: f32 = 5.0;
+
+/// EAN-13 or EAN-8 barcode drawn as vector bars, (x, y) is the top left corner of the barcode.
+/// Guard bars extend below the other bars.
+pub struct Ean {
+    pub module_width: f32,
+    pub height: f32,
+    pub digits: String,
+    /// Dark modules and whether they belong to a guard pattern
+    modules: Vec<(bool, bool)>
+}
+
+impl Ean {
+    /// Accepts 12 or 7 digits (the check digit is computed) or 13 or 8 digits (the check digit is verified)
+    pub fn new(data: &str, module_width: f32, height: f32) -> Result<Ean, Error> {
+        if !data.chars().all(|c| c.is_ascii_digit()) {
+            return Err(Error::msg(format!("EAN barcode should consist of digits: {}", data)));
+        }
+        let digits = match data.len() {
+            7 | 12 => format!("{}{}", data, check_digit(data)),
+            8 | 13 => {
+                let (body, check) = data.split_at(data.len() - 1);
+                if check_digit(body).to_string() != check {
+                    return Err(Error::msg(format!("Invalid EAN check digit: {}", data)));
+                }
+                data.to_owned()
+            },
+            _ => return Err(Error::msg(format!("EAN barcode should have 8 or 13 digits: {}", data)))
+        };
+        let values: Vec<usize> = digits.chars().map(|c| c.to_digit(10).unwrap() as usize).collect();
+        let (parities, left, right) = if values.len() == 13 {
+            (EAN_13_PARITIES[values[0]], &values[1..7], &values[7..])
+        } else {
+            ("LLLL", &values[..4], &values[4..])
+        };
+        let mut modules = Vec::new();
+        push_pattern(&mut modules, "101", true);
+        for (value, parity) in left.iter().zip(parities.chars()) {
+            let pattern = if parity == 'L' { L_PATTERNS[*value].to_owned() } else { even_parity_pattern(*value) };
+            push_pattern(&mut modules, &pattern, false);
+        }
+        push_pattern(&mut modules, "01010", true);
+        for value in right.iter() {
+            push_pattern(&mut modules, &right_hand_pattern(*value), false);
+        }
+        push_pattern(&mut modules, "101", true);
+        Ok(Ean {
+            module_width,
+            height,
+            digits,
+            modules
+        })
+    }
+
+    pub fn width(&self) -> f32 {
+        self.modules.len() as f32 * self.module_width
+    }
+}
+
+fn check_digit(body: &str) -> u32 {
+    let weighted_sum: u32 = body.chars().rev()
+        .zip([3, 1].iter().cycle())
+        .map(|(c, weight)| c.to_digit(10).unwrap() * weight)
+        .sum();
+    (10 - weighted_sum % 10) % 10
+}
+
+fn right_hand_pattern(value: usize) -> String {
+    L_PATTERNS[value].chars().map(|c| if c == '0' { '1' } else { '0' }).collect()
+}
+
+fn even_parity_pattern(value: usize) -> String {
+    right_hand_pattern(value).chars().rev().collect()
+}
+
+fn push_pattern(modules: &mut Vec<(bool, bool)>, pattern: &str, guard: bool) {
+    modules.extend(pattern.chars().map(|c| (c == '1', guard)));
+}
+
+impl Component for Ean {
+    fn render_at(&self, x: f32, y: f32) -> Vec<DrawOp> {
+        let guard_height = self.height + GUARD_EXTENSION_MODULES * self.module_width;
+        let mut rectangles: Vec<(f32, f32, f32, f32)> = Vec::new();
+        let mut previous: Option<(bool, bool)> = None;
+        for (index, &(dark, guard)) in self.modules.iter().enumerate() {
+            if !dark {
+                previous = None;
+                continue;
+            }
+            let height = if guard { guard_height } else { self.height };
+            match (previous, rectangles.last_mut()) {
+                (Some((true, previous_guard)), Some(last)) if previous_guard == guard => last.2 += self.module_width,
+                _ => rectangles.push((x + index as f32 * self.module_width, y - height, self.module_width, height))
+            }
+            previous = Some((dark, guard));
+        }
+        vec![fill_rectangles(&rectangles)]
+    }
+
+    fn measure(&self, _width: Option<f32>) -> Size {
+        Size { width: self.width(), height: self.height + GUARD_EXTENSION_MODULES * self.module_width, ascent: 0.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module_string(ean: &Ean) -> String {
+        ean.modules.iter().map(|&(dark, _)| if dark { '1' } else { '0' }).collect()
+    }
+
+    #[test]
+    fn test_ean_13_check_digit() {
+        assert_eq!(Ean::new("400638133393", 0.33, 20.0).unwrap().digits, "4006381333931");
+        assert!(Ean::new("4006381333931", 0.33, 20.0).is_ok());
+        assert!(Ean::new("4006381333932", 0.33, 20.0).is_err());
+    }
+
+    #[test]
+    fn test_ean_13_modules() {
+        let ean = Ean::new("5901234123457", 0.33, 20.0).unwrap();
+        assert_eq!(ean.modules.len(), 95);
+        assert_eq!(
+            module_string(&ean),
+            concat!(
+                "101",
+                "0001011", "0100111", "0110011", "0010011", "0111101", "0011101",
+                "01010",
+                "1100110", "1101100", "1000010", "1011100", "1001110", "1000100",
+                "101"
+            )
+        );
+    }
+
+    #[test]
+    fn test_ean_8_modules() {
+        let ean = Ean::new("9638507", 0.33, 20.0).unwrap();
+        assert_eq!(ean.digits, "96385074");
+        assert_eq!(ean.modules.len(), 67);
+        assert!((ean.width() - 67.0 * 0.33).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_ean_rejects_invalid_data() {
+        assert!(Ean::new("12345", 0.33, 20.0).is_err());
+        assert!(Ean::new("40063813339a", 0.33, 20.0).is_err());
+    }
+}
@@ -1,68 +1,184 @@
-use printpdf::*;
-use super::Component;
+use crate::drawing::{DrawOp, Font};
+use crate::theme::Color;
+use crate::fonts::text_width;
+use crate::locale::hyphenation::Hyphenation;
+use super::{Component, Size};
+
+const MM_PER_PT: f32 = 25.4 / 72.0;
+/// Height of the glyphs above the baseline as a share of the font size, the rest of the line
+/// height is split evenly above and below the glyphs
+const ASCENDER: f32 = 0.8;
 
 pub struct Label {
     pub value: String,
     pub font_size: f32,
-    pub font_id: FontId
+    pub font: Font,
+    pub hyphenation: Hyphenation,
+    /// Text color, `None` keeps the current fill color
+    pub color: Option<Color>
 }
 
 impl Label {
-    pub fn new(value: &str, font_size: f32, font_id: &FontId) -> Label {
+    pub fn new(value: &str, font_size: f32, font: Font) -> Label {
         Label {
             value: value.to_owned(),
             font_size,
-            font_id: font_id.clone()
+            font,
+            hyphenation: Hyphenation::None,
+            color: None
+        }
+    }
+
+    pub fn with_hyphenation(mut self, hyphenation: Hyphenation) -> Label {
+        self.hyphenation = hyphenation;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Label {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn line_height(&self) -> f32 {
+        self.font_size / 2.0 // Approximate line height
+    }
+
+    /// Lines of the label wrapped to the given width in mm, `None` only breaks at "\n"
+    pub fn lines(&self, width: Option<f32>) -> Vec<String> {
+        match width {
+            Some(width) => wrap_lines(&self.value, width, self.hyphenation, |text| text_width(text, self.font_size, self.font)),
+            None => self.value.split('\n').map(str::to_owned).collect()
         }
     }
 
-    pub fn new_row(row: Vec<&str>, font_size: f32, font_id: &FontId) -> Vec<Box<dyn Component>> {
+    fn render_lines(&self, x: f32, y: f32, lines: Vec<String>) -> Vec<DrawOp> {
+        let line_height = self.line_height();
+        let mut ops: Vec<DrawOp> = self.color.map(DrawOp::SetFillColor).into_iter().collect();
+        ops.extend(lines.into_iter().enumerate().map(|(index, line)| DrawOp::Text {
+            x,
+            y: y - (index as f32 * line_height),
+            text: line,
+            font_size: self.font_size,
+            font: self.font
+        }));
+        if self.color.is_some() {
+            ops.push(DrawOp::SetFillColor(Color::BLACK));
+        }
+        ops
+    }
+
+    pub fn new_row(row: Vec<&str>, font_size: f32, font: Font) -> Vec<Box<dyn Component>> {
         let mut label_row: Vec<Box<dyn Component>> = Vec::new();
         for value in row.into_iter() {
-            label_row.push(Box::new(Label::new(value, font_size, font_id)));
+            label_row.push(Box::new(Label::new(value, font_size, font)));
         }
         label_row
     }
 
-    pub fn new_rows(rows: Vec<Vec<&str>>, font_size: f32, font_id: &FontId) -> Vec<Vec<Box<dyn Component>>> {
+    pub fn new_rows(rows: Vec<Vec<&str>>, font_size: f32, font: Font) -> Vec<Vec<Box<dyn Component>>> {
         let mut labels: Vec<Vec<Box<dyn Component>>> = Vec::new();
         for row in rows.into_iter() {
-            labels.push(Label::new_row(row, font_size, font_id));
+            labels.push(Label::new_row(row, font_size, font));
         }
         labels
     }
 }
 
 impl Component for Label {
-    fn render_at(&self, x: f32, y: f32) -> Vec<Op> {
-        let mut ops: Vec<Op> = Vec::new();
-        let lines: Vec<&str> = self.value.split('\n').collect();
-        let line_height = self.font_size / 2.0; // Approximate line height
-        for (index, line) in lines.iter().enumerate() {
-            let current_y = y - (index as f32 * line_height);
-            ops.push(Op::SetFontSize { size: Pt(self.font_size), font: self.font_id.clone() });
-            ops.push(Op::StartTextSection);
-            ops.push(Op::SetTextCursor {
-                pos: Point { x: Mm(x).into(), y: Mm(current_y).into() }
-            });
-            ops.push(Op::WriteText {
-                items: vec![TextItem::Text(line.to_string())],
-                font: self.font_id.clone(),
-            });
-            ops.push(Op::EndTextSection);
+    fn render_at(&self, x: f32, y: f32) -> Vec<DrawOp> {
+        self.render_lines(x, y, self.lines(None))
+    }
+
+    fn measure(&self, width: Option<f32>) -> Size {
+        let lines = self.lines(width);
+        let font_size = self.font_size * MM_PER_PT;
+        Size {
+            width: lines.iter().map(|line| text_width(line, self.font_size, self.font)).fold(0.0, f32::max),
+            height: lines.len() as f32 * self.line_height(),
+            ascent: (self.line_height() - font_size) / 2.0 + font_size * ASCENDER
         }
-        ops
     }
+
+    fn render_within(&self, x: f32, y: f32, width: f32) -> Vec<DrawOp> {
+        self.render_lines(x, y, self.lines(Some(width)))
+    }
+
+    fn wrapped_height(&self, width: f32) -> f32 {
+        (self.lines(Some(width)).len() - 1) as f32 * self.line_height()
+    }
+
+    fn width_before(&self, separator: char) -> Option<f32> {
+        let integer_part = self.value.split(separator).next().unwrap_or("");
+        Some(text_width(integer_part, self.font_size, self.font))
+    }
+}
+
+/// Breaks the text into lines no wider than `width` at spaces, at the hyphenation points of words
+/// that do not fit, and between characters when a single character is all that fits
+pub(crate) fn wrap_lines(text: &str, width: f32, hyphenation: Hyphenation, measure: impl Fn(&str) -> f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let mut word = word.to_owned();
+            loop {
+                let candidate = if line.is_empty() { word.clone() } else { format!("{} {}", line, word) };
+                if measure(&candidate) <= width {
+                    line = candidate;
+                    break;
+                }
+                let prefix = if line.is_empty() { String::new() } else { format!("{} ", line) };
+                if let Some((head, tail)) = break_word(&prefix, &word, width, hyphenation, &measure) {
+                    lines.push(format!("{}{}", prefix, head));
+                    line = String::new();
+                    word = tail;
+                } else if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                } else {
+                    // Not even a single character fits, keep it on its own line
+                    let split_at = word.char_indices().nth(1).map(|(index, _)| index).unwrap_or(word.len());
+                    lines.push(word[..split_at].to_owned());
+                    word = word[split_at..].to_owned();
+                    if word.is_empty() {
+                        break;
+                    }
+                }
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Longest start of the word fitting after the prefix, broken at a hyphenation point (adding a
+/// hyphen), after a hyphen of the word or, on an empty line, between any characters
+fn break_word(prefix: &str, word: &str, width: f32, hyphenation: Hyphenation, measure: &impl Fn(&str) -> f32) -> Option<(String, String)> {
+    let chars: Vec<char> = word.chars().collect();
+    let longest_fitting = |mut candidates: Vec<(usize, bool)>| {
+        candidates.sort_by_key(|&(point, _)| std::cmp::Reverse(point));
+        candidates.into_iter()
+            .map(|(point, hyphen)| {
+                let head: String = chars[..point].iter().collect();
+                (if hyphen { format!("{}-", head) } else { head }, chars[point..].iter().collect::<String>())
+            })
+            .find(|(head, _)| measure(&format!("{}{}", prefix, head)) <= width)
+    };
+    let mut candidates: Vec<(usize, bool)> = hyphenation.break_points(word).into_iter().map(|point| (point, true)).collect();
+    candidates.extend((1..chars.len()).filter(|&point| chars[point - 1] == '-').map(|point| (point, false)));
+    longest_fitting(candidates).or_else(|| if prefix.is_empty() {
+        longest_fitting((1..chars.len()).map(|point| (point, false)).collect())
+    } else {
+        None
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use printpdf::FontId;
 
     #[test]
     fn test_label_new() {
-        let label = Label::new("Test Label", 12.0, &FontId::new());
+        let label = Label::new("Test Label", 12.0, Font::REGULAR);
         assert_eq!(label.value, "Test Label");
         assert_eq!(label.font_size, 12.0);
     }
@@ -70,7 +186,7 @@ mod tests {
     #[test]
     fn test_label_new_row() {
         let row_data = vec!["Header1", "Header2", "Header3"];
-        let labels = Label::new_row(row_data, 12.0, &FontId::new());
+        let labels = Label::new_row(row_data, 12.0, Font::REGULAR);
         assert_eq!(labels.len(), 3);
     }
 
@@ -81,22 +197,49 @@ mod tests {
             vec!["Row2Col1", "Row2Col2"],
             vec!["Row3Col1", "Row3Col2"]
         ];
-        let labels = Label::new_rows(rows_data, 12.0, &FontId::new());
+        let labels = Label::new_rows(rows_data, 12.0, Font::REGULAR);
         assert_eq!(labels.len(), 3);
         assert_eq!(labels[0].len(), 2);
         assert_eq!(labels[1].len(), 2);
         assert_eq!(labels[2].len(), 2);
     }
 
+    #[test]
+    fn test_wrap_lines() {
+        // Every character is 1 mm wide
+        let measure = |text: &str| text.chars().count() as f32;
+        assert_eq!(wrap_lines("Computer services and support", 18.0, Hyphenation::None, measure), vec!["Computer services", "and support"]);
+        assert_eq!(wrap_lines("Line 1\nLine 2", 18.0, Hyphenation::None, measure), vec!["Line 1", "Line 2"]);
+        assert_eq!(wrap_lines("Ohjelmistokehitys", 8.0, Hyphenation::None, measure), vec!["Ohjelmis", "tokehity", "s"]);
+        assert_eq!(wrap_lines("Ohjelmistokehitys", 8.0, Hyphenation::Finnish, measure), vec!["Ohjel-", "mistoke-", "hitys"]);
+        assert_eq!(wrap_lines("Uusi ohjelmistokehitys", 12.0, Hyphenation::Finnish, measure), vec!["Uusi ohjel-", "mistokehitys"]);
+        assert_eq!(wrap_lines("CD-levyt", 4.0, Hyphenation::None, measure), vec!["CD-", "levy", "t"]);
+    }
+
+    #[test]
+    fn test_label_color() {
+        let blue = Color { r: 0.0, g: 0.0, b: 1.0 };
+        let ops = Label::new("Invoice", 22.0, Font::BOLD).with_color(blue).render_at(10.0, 100.0);
+        assert_eq!(ops.len(), 3);
+        assert!(matches!(ops[0], DrawOp::SetFillColor(color) if color == blue));
+        assert!(matches!(ops[2], DrawOp::SetFillColor(Color::BLACK)));
+    }
+
     #[test]
     fn test_multi_line_label() {
         let multi_line_text = "Line 1\nLine 2\nLine 3";
-        let label = Label::new(multi_line_text, 12.0, &FontId::new());
+        let label = Label::new(multi_line_text, 12.0, Font::REGULAR);
         assert_eq!(label.value, multi_line_text);
         
         let ops = label.render_at(10.0, 100.0);
-        // Should have 5 operations per line: SetFontSize, StartTextSection, SetTextCursor, WriteText, EndTextSection
-        // For 3 lines, that's 15 operations total
-        assert_eq!(ops.len(), 15);
+        // One text operation per line, moving down by half of the font size
+        assert_eq!(ops.len(), 3);
+        match &ops[2] {
+            DrawOp::Text { y, text, .. } => {
+                assert_eq!(*y, 88.0);
+                assert_eq!(text, "Line 3");
+            },
+            _ => panic!("Expected a text operation")
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,260 @@
+//! Containers place their children by their top edges. A container is rendered at the y of its
+//! first child, so containers can be nested and put into table cells like any other component.
+
+use serde::Deserialize;
+use crate::drawing::DrawOp;
+use super::{render_top, Component, Size};
+
+/// Children below each other with the given space between them
+pub struct Stack {
+    pub spacing: f32,
+    pub children: Vec<Box<dyn Component>>
+}
+
+impl Stack {
+    fn render(&self, x: f32, y: f32, width: Option<f32>) -> Vec<DrawOp> {
+        let mut ops = Vec::new();
+        let mut top = y + self.measure(width).ascent;
+        for child in self.children.iter() {
+            ops.extend(render_top(child.as_ref(), x, top, width));
+            top -= child.measure(width).height + self.spacing;
+        }
+        ops
+    }
+}
+
+impl Component for Stack {
+    fn render_at(&self, x: f32, y: f32) -> Vec<DrawOp> {
+        self.render(x, y, None)
+    }
+
+    fn measure(&self, width: Option<f32>) -> Size {
+        let sizes: Vec<Size> = self.children.iter().map(|child| child.measure(width)).collect();
+        Size {
+            width: sizes.iter().map(|size| size.width).fold(0.0, f32::max),
+            height: sizes.iter().map(|size| size.height).sum::<f32>() + self.spacing * sizes.len().saturating_sub(1) as f32,
+            ascent: sizes.first().map(|size| size.ascent).unwrap_or(0.0)
+        }
+    }
+
+    fn render_within(&self, x: f32, y: f32, width: f32) -> Vec<DrawOp> {
+        self.render(x, y, Some(width))
+    }
+
+    fn wrapped_height(&self, width: f32) -> f32 {
+        match self.children.first() {
+            Some(first) => self.measure(Some(width)).height - first.measure(Some(width)).height + first.wrapped_height(width),
+            None => 0.0
+        }
+    }
+}
+
+/// Children next to each other at their own widths with the given space between them, the last
+/// child is wrapped to the width left over when the row is rendered within a width
+pub struct Row {
+    pub spacing: f32,
+    pub children: Vec<Box<dyn Component>>
+}
+
+impl Row {
+    /// Left edges of the children and the width the last child is wrapped to
+    fn columns(&self, width: Option<f32>) -> (Vec<f32>, Option<f32>) {
+        let mut lefts = Vec::new();
+        let mut left = 0.0;
+        for child in self.children.iter() {
+            lefts.push(left);
+            left += child.measure(None).width + self.spacing;
+        }
+        let last_width = width.zip(lefts.last()).map(|(width, last_left)| (width - last_left).max(0.0));
+        (lefts, last_width)
+    }
+
+    fn child_width(&self, index: usize, last_width: Option<f32>) -> Option<f32> {
+        if index + 1 == self.children.len() { last_width } else { None }
+    }
+
+    fn render(&self, x: f32, y: f32, width: Option<f32>) -> Vec<DrawOp> {
+        let (lefts, last_width) = self.columns(width);
+        let top = y + self.measure(width).ascent;
+        self.children.iter().zip(lefts).enumerate()
+            .flat_map(|(index, (child, left))| render_top(child.as_ref(), x + left, top, self.child_width(index, last_width)))
+            .collect()
+    }
+}
+
+impl Component for Row {
+    fn render_at(&self, x: f32, y: f32) -> Vec<DrawOp> {
+        self.render(x, y, None)
+    }
+
+    fn measure(&self, width: Option<f32>) -> Size {
+        let (lefts, last_width) = self.columns(width);
+        let sizes: Vec<Size> = self.children.iter().enumerate().map(|(index, child)| child.measure(self.child_width(index, last_width))).collect();
+        Size {
+            width: lefts.last().zip(sizes.last()).map(|(left, size)| left + size.width).unwrap_or(0.0),
+            height: sizes.iter().map(|size| size.height).fold(0.0, f32::max),
+            ascent: sizes.first().map(|size| size.ascent).unwrap_or(0.0)
+        }
+    }
+
+    fn render_within(&self, x: f32, y: f32, width: f32) -> Vec<DrawOp> {
+        self.render(x, y, Some(width))
+    }
+}
+
+/// Cells in columns of fixed widths, each cell is wrapped to the width of its column and the rows
+/// are as high as their highest cell
+pub struct Grid {
+    pub column_widths: Vec<f32>,
+    pub column_spacing: f32,
+    pub row_spacing: f32,
+    pub rows: Vec<Vec<Box<dyn Component>>>
+}
+
+impl Grid {
+    fn row_height(&self, cells: &[Box<dyn Component>]) -> f32 {
+        cells.iter().zip(self.column_widths.iter())
+            .map(|(cell, column_width)| cell.measure(Some(*column_width)).height)
+            .fold(0.0, f32::max)
+    }
+}
+
+impl Component for Grid {
+    fn render_at(&self, x: f32, y: f32) -> Vec<DrawOp> {
+        let mut ops = Vec::new();
+        let mut top = y + self.measure(None).ascent;
+        for cells in self.rows.iter() {
+            let mut left = x;
+            for (cell, column_width) in cells.iter().zip(self.column_widths.iter()) {
+                ops.extend(render_top(cell.as_ref(), left, top, Some(*column_width)));
+                left += column_width + self.column_spacing;
+            }
+            top -= self.row_height(cells) + self.row_spacing;
+        }
+        ops
+    }
+
+    fn measure(&self, _width: Option<f32>) -> Size {
+        let first_cell = self.rows.first().and_then(|cells| cells.first().zip(self.column_widths.first()));
+        Size {
+            width: self.column_widths.iter().sum::<f32>() + self.column_spacing * self.column_widths.len().saturating_sub(1) as f32,
+            height: self.rows.iter().map(|cells| self.row_height(cells)).sum::<f32>() + self.row_spacing * self.rows.len().saturating_sub(1) as f32,
+            ascent: first_cell.map(|(cell, column_width)| cell.measure(Some(*column_width)).ascent).unwrap_or(0.0)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Alignment {
+    Left,
+    Center,
+    Right
+}
+
+/// Moves the child to the center or the right edge of the width it is rendered within
+pub struct Aligned {
+    pub alignment: Alignment,
+    pub child: Box<dyn Component>
+}
+
+impl Component for Aligned {
+    fn render_at(&self, x: f32, y: f32) -> Vec<DrawOp> {
+        self.child.render_at(x, y)
+    }
+
+    fn measure(&self, width: Option<f32>) -> Size {
+        let size = self.child.measure(width);
+        Size {
+            width: width.unwrap_or(size.width),
+            ..size
+        }
+    }
+
+    fn render_within(&self, x: f32, y: f32, width: f32) -> Vec<DrawOp> {
+        let free_width = (width - self.child.measure(Some(width)).width).max(0.0);
+        let offset = match self.alignment {
+            Alignment::Left => 0.0,
+            Alignment::Center => free_width / 2.0,
+            Alignment::Right => free_width
+        };
+        self.child.render_within(x + offset, y, width)
+    }
+
+    fn wrapped_height(&self, width: f32) -> f32 {
+        self.child.wrapped_height(width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rectangle of a fixed size with the given ascent, rendered as a single filled rectangle
+    struct Block {
+        width: f32,
+        height: f32,
+        ascent: f32
+    }
+
+    fn block(width: f32, height: f32, ascent: f32) -> Box<dyn Component> {
+        Box::new(Block { width, height, ascent })
+    }
+
+    impl Component for Block {
+        fn render_at(&self, x: f32, y: f32) -> Vec<DrawOp> {
+            vec![DrawOp::FillRectangles(vec![(x, y + self.ascent - self.height, self.width, self.height)])]
+        }
+
+        fn measure(&self, _width: Option<f32>) -> Size {
+            Size { width: self.width, height: self.height, ascent: self.ascent }
+        }
+    }
+
+    fn rectangles(ops: Vec<DrawOp>) -> Vec<(f32, f32, f32, f32)> {
+        ops.into_iter().flat_map(|op| match op {
+            DrawOp::FillRectangles(rectangles) => rectangles,
+            _ => Vec::new()
+        }).collect()
+    }
+
+    #[test]
+    fn should_stack_children_from_the_top() {
+        let stack = Stack { spacing: 2.0, children: vec![block(10.0, 5.0, 4.0), block(20.0, 10.0, 0.0)] };
+        assert_eq!(stack.measure(None), Size { width: 20.0, height: 17.0, ascent: 4.0 });
+        // The top edge is at 104, the first child is rendered at its own ascent below it
+        assert_eq!(rectangles(render_top(&stack, 0.0, 104.0, None)), vec![(0.0, 99.0, 10.0, 5.0), (0.0, 87.0, 20.0, 10.0)]);
+    }
+
+    #[test]
+    fn should_place_row_children_side_by_side() {
+        let row = Row { spacing: 5.0, children: vec![block(10.0, 5.0, 0.0), block(20.0, 10.0, 0.0)] };
+        assert_eq!(row.measure(None), Size { width: 35.0, height: 10.0, ascent: 0.0 });
+        assert_eq!(rectangles(row.render_at(15.0, 100.0)), vec![(15.0, 95.0, 10.0, 5.0), (30.0, 90.0, 20.0, 10.0)]);
+    }
+
+    #[test]
+    fn should_lay_out_grid_cells_in_columns() {
+        let grid = Grid {
+            column_widths: vec![30.0, 40.0],
+            column_spacing: 5.0,
+            row_spacing: 1.0,
+            rows: vec![
+                vec![block(10.0, 5.0, 0.0), block(20.0, 8.0, 0.0)],
+                vec![block(10.0, 5.0, 0.0)]
+            ]
+        };
+        assert_eq!(grid.measure(None), Size { width: 75.0, height: 14.0, ascent: 0.0 });
+        assert_eq!(
+            rectangles(grid.render_at(0.0, 100.0)),
+            vec![(0.0, 95.0, 10.0, 5.0), (35.0, 92.0, 20.0, 8.0), (0.0, 86.0, 10.0, 5.0)]
+        );
+    }
+
+    #[test]
+    fn should_align_to_the_right_edge() {
+        let aligned = Aligned { alignment: Alignment::Right, child: block(75.0, 15.0, 0.0) };
+        assert_eq!(rectangles(aligned.render_within(15.0, 100.0, 185.0)), vec![(125.0, 85.0, 75.0, 15.0)]);
+        assert_eq!(aligned.measure(Some(185.0)).width, 185.0);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::drawing::{DrawOp, Font};
+use crate::fonts::text_width;
+use super::{Component, Size};
+use super::label::Label;
+
+const MM_PER_PT: f32 = 25.4 / 72.0;
+
+/// Label which opens the given URI when clicked
+pub struct Link {
+    pub label: Label,
+    pub uri: String
+}
+
+impl Link {
+    pub fn new(value: &str, uri: &str, font_size: f32, font: Font) -> Link {
+        Link {
+            label: Label::new(value, font_size, font),
+            uri: uri.to_owned()
+        }
+    }
+
+    pub fn mailto(email: &str, font_size: f32, font: Font) -> Link {
+        Link::new(email, &format!("mailto:{}", email.trim()), font_size, font)
+    }
+
+    /// Link to a web address, "https://" is assumed when the address has no scheme
+    pub fn url(url: &str, font_size: f32, font: Font) -> Link {
+        let url = url.trim();
+        let uri = if url.contains("://") {
+            url.to_owned()
+        } else {
+            format!("https://{}", url)
+        };
+        Link::new(url, &uri, font_size, font)
+    }
+}
+
+impl Link {
+    fn render_lines(&self, x: f32, y: f32, width: Option<f32>) -> Vec<DrawOp> {
+        let lines = self.label.lines(width);
+        let mut ops = match width {
+            Some(width) => self.label.render_within(x, y, width),
+            None => self.label.render_at(x, y)
+        };
+        let font_size = self.label.font_size * MM_PER_PT;
+        let line_height = self.label.line_height();
+        let wrapped_height = line_height * (lines.len() - 1) as f32;
+        ops.push(DrawOp::Link {
+            x,
+            y: y - wrapped_height - font_size * 0.25,
+            width: lines.iter().map(|line| text_width(line, self.label.font_size, self.label.font)).fold(0.0, f32::max),
+            height: wrapped_height + font_size,
+            uri: self.uri.clone()
+        });
+        ops
+    }
+}
+
+impl Component for Link {
+    fn render_at(&self, x: f32, y: f32) -> Vec<DrawOp> {
+        self.render_lines(x, y, None)
+    }
+
+    fn measure(&self, width: Option<f32>) -> Size {
+        self.label.measure(width)
+    }
+
+    fn render_within(&self, x: f32, y: f32, width: f32) -> Vec<DrawOp> {
+        self.render_lines(x, y, Some(width))
+    }
+
+    fn wrapped_height(&self, width: f32) -> f32 {
+        self.label.wrapped_height(width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mailto_link() {
+        let link = Link::mailto("contact@example.com", 7.0, Font::REGULAR);
+        assert_eq!(link.label.value, "contact@example.com");
+        assert_eq!(link.uri, "mailto:contact@example.com");
+    }
+
+    #[test]
+    fn test_url_link() {
+        assert_eq!(Link::url("www.example.com", 7.0, Font::REGULAR).uri, "https://www.example.com");
+        assert_eq!(Link::url("http://example.com/pay", 7.0, Font::REGULAR).uri, "http://example.com/pay");
+    }
+
+    #[test]
+    fn test_link_renders_annotation() {
+        let link = Link::url("example.com", 10.0, Font::REGULAR);
+        let ops = link.render_at(10.0, 100.0);
+        // Text of the single line label and the link
+        assert_eq!(ops.len(), 2);
+        match ops.last() {
+            Some(DrawOp::Link { width, uri, .. }) => {
+                assert_eq!(uri, "https://example.com");
+                assert_eq!(*width, text_width("example.com", 10.0, Font::REGULAR));
+            },
+            _ => panic!("Expected a link annotation")
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::rc::Rc;
+use crate::drawing::DrawOp;
+use crate::image::Image;
+use super::{Component, Size};
+
+/// Image scaled to the given size in mm, (x, y) is the top left corner
+pub struct Picture {
+    pub image: Rc<Image>,
+    pub width: f32,
+    pub height: f32
+}
+
+impl Picture {
+    /// Largest picture fitting into the box of the given size, keeping the aspect ratio of the image
+    pub fn fit(image: Rc<Image>, max_width: f32, max_height: f32) -> Picture {
+        let (pixel_width, pixel_height) = (image.raw.width.max(1) as f32, image.raw.height.max(1) as f32);
+        let scale = (max_width / pixel_width).min(max_height / pixel_height);
+        Picture {
+            width: pixel_width * scale,
+            height: pixel_height * scale,
+            image
+        }
+    }
+}
+
+impl Component for Picture {
+    fn render_at(&self, x: f32, y: f32) -> Vec<DrawOp> {
+        vec![DrawOp::Image {
+            x,
+            y: y - self.height,
+            width: self.width,
+            height: self.height,
+            image: self.image.clone()
+        }]
+    }
+
+    fn measure(&self, _width: Option<f32>) -> Size {
+        Size { width: self.width, height: self.height, ascent: 0.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::load_image;
+
+    #[test]
+    fn should_fit_the_image_into_the_box() {
+        // 375 x 382 pixels
+        let image = Rc::new(load_image("examples/yritys_logo_small.png").unwrap());
+        let wide_box = Picture::fit(image.clone(), 60.0, 10.0);
+        assert!((wide_box.width - 10.0 * 375.0 / 382.0).abs() < 0.001);
+        assert_eq!(wide_box.height, 10.0);
+        let narrow_box = Picture::fit(image, 15.0, 25.0);
+        assert_eq!(narrow_box.width, 15.0);
+        assert!((narrow_box.height - 15.0 * 382.0 / 375.0).abs() < 0.001);
+    }
+}
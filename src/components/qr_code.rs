@@ -0,0 +1,177 @@
+use anyhow::Error;
+use crate::drawing::DrawOp;
+use qrcode::{Color, EcLevel, QrCode as QrMatrix};
+use super::{fill_rectangles, Component, Size};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorCorrection {
+    /// Recovers 7% of the data
+    Low,
+    /// Recovers 15% of the data
+    Medium,
+    /// Recovers 25% of the data
+    Quartile,
+    /// Recovers 30% of the data
+    High
+}
+
+impl From<ErrorCorrection> for EcLevel {
+    fn from(error_correction: ErrorCorrection) -> Self {
+        match error_correction {
+            ErrorCorrection::Low => EcLevel::L,
+            ErrorCorrection::Medium => EcLevel::M,
+            ErrorCorrection::Quartile => EcLevel::Q,
+            ErrorCorrection::High => EcLevel::H
+        }
+    }
+}
+
+/// Light modules around the symbol that scanners need to find it, per ISO/IEC 18004
+const QUIET_ZONE_MODULES: usize = 4;
+
+/// QR code drawn as vector rectangles, (x, y) is the top left corner of the symbol, or of its
+/// quiet zone when it has one
+pub struct QrCode {
+    /// Width and height including the quiet zone
+    pub size: f32,
+    width: usize,
+    modules: Vec<Color>,
+    quiet_zone: usize
+}
+
+impl QrCode {
+    /// QR code with the medium error correction level, as required by EPC069-12 and the Swiss QR-bill
+    pub fn new(data: &str, size: f32) -> Result<QrCode, Error> {
+        QrCode::with_error_correction(data, size, ErrorCorrection::Medium)
+    }
+
+    pub fn with_error_correction(data: &str, size: f32, error_correction: ErrorCorrection) -> Result<QrCode, Error> {
+        let matrix = QrMatrix::with_error_correction_level(data.as_bytes(), error_correction.into())
+            .map_err(|e| Error::msg(format!("Could not encode QR code: {}", e)))?;
+        Ok(QrCode {
+            size,
+            width: matrix.width(),
+            modules: matrix.into_colors(),
+            quiet_zone: 0
+        })
+    }
+
+    /// Keeps a quiet zone of 4 modules free around the symbol inside `size`, for codes printed
+    /// next to other content. The Swiss QR-bill leaves it out, its layout has a margin of its own.
+    pub fn with_quiet_zone(self) -> QrCode {
+        QrCode { quiet_zone: QUIET_ZONE_MODULES, ..self }
+    }
+
+    /// Modules across the symbol and its quiet zone
+    fn total_width(&self) -> usize {
+        self.width + 2 * self.quiet_zone
+    }
+
+    /// Standalone SVG image of the symbol, `size` is used as the width and height in mm
+    pub fn to_svg(&self) -> String {
+        let path: String = self.dark_runs().into_iter()
+            .map(|(row, column, length)| format!("M{} {}h{}v1h-{}z", column + self.quiet_zone, row + self.quiet_zone, length, length))
+            .collect();
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {0} {0}" width="{1}mm" height="{1}mm" shape-rendering="crispEdges"><path d="{2}"/></svg>"#,
+            self.total_width(), self.size, path
+        )
+    }
+
+    fn dark_runs(&self) -> Vec<(usize, usize, usize)> {
+        let mut runs = Vec::new();
+        for (row_index, row) in self.modules.chunks(self.width).enumerate() {
+            let mut run_start: Option<usize> = None;
+            for (column_index, color) in row.iter().enumerate() {
+                match (color, run_start) {
+                    (Color::Dark, None) => run_start = Some(column_index),
+                    (Color::Light, Some(start)) => {
+                        runs.push((row_index, start, column_index - start));
+                        run_start = None;
+                    },
+                    _ => {}
+                }
+            }
+            if let Some(start) = run_start {
+                runs.push((row_index, start, self.width - start));
+            }
+        }
+        runs
+    }
+}
+
+impl Component for QrCode {
+    fn render_at(&self, x: f32, y: f32) -> Vec<DrawOp> {
+        let module_size = self.size / self.total_width() as f32;
+        let quiet_zone = self.quiet_zone as f32 * module_size;
+        let (x, y) = (x + quiet_zone, y - quiet_zone);
+        let rectangles: Vec<(f32, f32, f32, f32)> = self.dark_runs().into_iter().map(|(row, column, length)| {
+            (
+                x + column as f32 * module_size,
+                y - (row + 1) as f32 * module_size,
+                length as f32 * module_size,
+                module_size
+            )
+        }).collect();
+        vec![fill_rectangles(&rectangles)]
+    }
+
+    fn measure(&self, _width: Option<f32>) -> Size {
+        Size { width: self.size, height: self.size, ascent: 0.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qr_code_new() {
+        let qr_code = QrCode::new("BCD\n002\n1\nSCT", 30.0).unwrap();
+        assert_eq!(qr_code.size, 30.0);
+        assert_eq!(qr_code.width, 21);
+        assert_eq!(qr_code.modules.len(), 21 * 21);
+    }
+
+    #[test]
+    fn test_qr_code_error_correction_levels() {
+        let data = "https://example.com/invoices/2025-0001";
+        let widths: Vec<usize> = [ErrorCorrection::Low, ErrorCorrection::Medium, ErrorCorrection::Quartile, ErrorCorrection::High]
+            .into_iter()
+            .map(|level| QrCode::with_error_correction(data, 30.0, level).unwrap().width)
+            .collect();
+        assert_eq!(widths, vec![29, 29, 33, 37]);
+    }
+
+    #[test]
+    fn test_dark_runs_cover_finder_pattern() {
+        let qr_code = QrCode::new("Invoicely", 30.0).unwrap();
+        let runs = qr_code.dark_runs();
+        assert_eq!(runs[0], (0, 0, 7));
+        assert!(runs.iter().all(|&(_, column, length)| column + length <= qr_code.width));
+    }
+
+    #[test]
+    fn test_qr_code_svg() {
+        let svg = QrCode::new("Invoicely", 30.0).unwrap().to_svg();
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 21 21" width="30mm" height="30mm""#));
+        assert!(svg.contains(r#"<path d="M0 0h7v1h-7z"#));
+    }
+
+    #[test]
+    fn test_qr_code_renders_single_polygon() {
+        let qr_code = QrCode::new("Invoicely", 30.0).unwrap();
+        let ops = qr_code.render_at(10.0, 100.0);
+        assert_eq!(ops.len(), 1);
+    }
+
+    #[test]
+    fn test_qr_code_quiet_zone() {
+        let qr_code = QrCode::new("Invoicely", 29.0).unwrap().with_quiet_zone();
+        assert!(qr_code.to_svg().contains(r#"viewBox="0 0 29 29""#));
+        assert!(qr_code.to_svg().contains(r#"<path d="M4 4h7v1h-7z"#));
+        let ops = qr_code.render_at(10.0, 100.0);
+        assert!(matches!(&ops[0], DrawOp::FillRectangles(rectangles) if rectangles[0] == (14.0, 95.0, 7.0, 1.0)));
+        assert_eq!(qr_code.measure(None).width, 29.0);
+    }
+}
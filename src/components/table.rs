@@ -1,61 +1,275 @@
-use printpdf::*;
-use super::Component;
+use serde::Deserialize;
+use crate::drawing::DrawOp;
+use crate::theme::Color;
+use super::{fill_rectangles, stroke_line, Component, Size};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnAlignment {
+    Left,
+    Right,
+    Center,
+    /// Numbers lined up at the given decimal separator, numbers without one end where the separator
+    /// would be and the header is right-aligned
+    Decimal(char)
+}
+
+/// Line drawn by a table, the width is in points
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Border {
+    pub width: f32,
+    pub color: Color
+}
+
+/// Space between the edges of a cell and its content in mm
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Padding {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32
+}
+
+/// Borders, padding and fills of a table, shared by the tables of an invoice. Fields left out of
+/// a template keep their default values.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TableStyle {
+    /// Rectangle around the whole table
+    pub outer_border: Option<Border>,
+    /// Lines between the rows below the header
+    pub row_border: Option<Border>,
+    /// Lines between the columns
+    pub column_border: Option<Border>,
+    /// Line between the header and the rows below it
+    pub header_border: Option<Border>,
+    pub header_fill: Option<Color>,
+    /// Fill of every other row below the header, starting from the second
+    pub stripe_fill: Option<Color>,
+    /// Text colors of the header and the rows, `None` keeps the current fill color
+    pub header_text_color: Option<Color>,
+    pub text_color: Option<Color>,
+    pub cell_padding: Padding
+}
+
+impl Default for TableStyle {
+    /// A thin black line below the header and 1 mm of space before the next column
+    fn default() -> Self {
+        TableStyle {
+            outer_border: None,
+            row_border: None,
+            column_border: None,
+            header_border: Some(Border { width: 0.4, color: Color::BLACK }),
+            header_fill: None,
+            stripe_fill: None,
+            header_text_color: None,
+            text_color: None,
+            cell_padding: Padding { right: 1.0, ..Padding::default() }
+        }
+    }
+}
 
 pub struct Table {
     pub column_widths: Vec<f32>,
+    /// Alignment of the cells within their columns, columns without one are left-aligned
+    pub column_alignments: Vec<ColumnAlignment>,
     pub row_height: f32,
+    pub style: TableStyle,
     pub header: Option<Vec<Box <dyn Component>>>,
     pub rows: Vec<Vec<Box<dyn Component>>>
 }
 
 impl Table {
-    fn render_row_at(&self, values: &Vec<Box<dyn Component>>, x: f32, y: f32) -> Vec<Op> {
-        let mut ops: Vec<Op> = Vec::new();
-        let mut current_x_offset = x;
-        for (column_value, column_width) in values.iter().zip(self.column_widths.iter()) {
-            ops.extend(column_value.render_at(current_x_offset, y));
-            current_x_offset = current_x_offset + column_width;
+    /// Width the content of the cells in the column is wrapped to, inside the padding
+    fn content_width(&self, column: usize) -> f32 {
+        self.column_widths[column] - self.style.cell_padding.left - self.style.cell_padding.right
+    }
+
+    /// Height of the row with its padding, growing from `row_height` when its cells wrap to several lines
+    pub fn height_of(&self, values: &[Box<dyn Component>]) -> f32 {
+        let wrapped_height = values.iter().take(self.column_widths.len()).enumerate()
+            .map(|(column, column_value)| column_value.wrapped_height(self.content_width(column)))
+            .fold(0.0, f32::max);
+        self.row_height + wrapped_height + self.style.cell_padding.top + self.style.cell_padding.bottom
+    }
+
+    /// Height of the header row with half a row of space around its text, 0 without a header
+    pub fn header_height(&self) -> f32 {
+        self.header.as_ref().map(|header_values| self.height_of(header_values) + self.row_height / 2.0).unwrap_or(0.0)
+    }
+
+    /// Heights of the rows below the header
+    pub fn row_heights(&self) -> Vec<f32> {
+        self.rows.iter().map(|row| self.height_of(row)).collect()
+    }
+
+    fn alignment(&self, column: usize) -> ColumnAlignment {
+        self.column_alignments.get(column).copied().unwrap_or(ColumnAlignment::Left)
+    }
+
+    /// Distance from the top edge of the row to the baselines of its first lines
+    fn ascent_of(&self, values: &[Box<dyn Component>], row_box_height: f32) -> f32 {
+        let ascent = values.iter().take(self.column_widths.len()).enumerate()
+            .map(|(column, column_value)| column_value.measure(Some(self.content_width(column))).ascent)
+            .fold(0.0, f32::max);
+        self.style.cell_padding.top + (row_box_height - self.height_of(values)) / 2.0 + ascent
+    }
+
+    /// Distance from the left edge of each column to the decimal separators of its rows, the
+    /// widest part after the separator ends at the right edge of the column content
+    fn decimal_positions(&self) -> Vec<f32> {
+        (0..self.column_widths.len()).map(|column| {
+            let ColumnAlignment::Decimal(separator) = self.alignment(column) else {
+                return 0.0;
+            };
+            let width = self.content_width(column);
+            let widest_fraction = self.rows.iter()
+                .filter_map(|row| row.get(column))
+                .map(|cell| {
+                    let cell_width = cell.measure(Some(width)).width;
+                    cell_width - cell.width_before(separator).unwrap_or(cell_width)
+                })
+                .fold(0.0, f32::max);
+            width - widest_fraction
+        }).collect()
+    }
+
+    /// Distance of the cell from the left edge of its column content
+    fn cell_offset(&self, column: usize, cell: &dyn Component, header: bool, decimal_position: f32) -> f32 {
+        let width = self.content_width(column);
+        let cell_width = cell.measure(Some(width)).width;
+        let offset = match self.alignment(column) {
+            ColumnAlignment::Left => 0.0,
+            ColumnAlignment::Center => (width - cell_width) / 2.0,
+            ColumnAlignment::Right => width - cell_width,
+            ColumnAlignment::Decimal(_) if header => width - cell_width,
+            ColumnAlignment::Decimal(separator) => decimal_position - cell.width_before(separator).unwrap_or(cell_width)
+        };
+        offset.max(0.0)
+    }
+
+    /// Renders the cells of the row into its box starting at the given top edge
+    fn render_row_at(&self, values: &[Box<dyn Component>], x: f32, top: f32, row_box_height: f32, header: bool, decimal_positions: &[f32]) -> Vec<DrawOp> {
+        let text_color = if header { self.style.header_text_color } else { self.style.text_color };
+        let mut ops: Vec<DrawOp> = text_color.map(DrawOp::SetFillColor).into_iter().collect();
+        let y = top - self.ascent_of(values, row_box_height);
+        let mut current_x_offset = x + self.style.cell_padding.left;
+        for (column, (column_value, column_width)) in values.iter().zip(self.column_widths.iter()).enumerate() {
+            let offset = self.cell_offset(column, column_value.as_ref(), header, decimal_positions[column]);
+            ops.extend(column_value.render_within(current_x_offset + offset, y, self.content_width(column)));
+            current_x_offset += column_width;
         }
         ops
     }
 
-    fn render_bottom_border_at(&self, x: f32, y: f32) -> Vec<Op> {
-        let mut ops: Vec<Op> = Vec::new();
-        let total_width: f32 = self.column_widths.iter().sum(); 
-        ops.push(Op::SetOutlineThickness { pt: Pt(0.4) });
-        ops.push(Op::DrawLine {
-            line: Line {
-                points: vec![
-                    LinePoint { p: Point::new(Mm(x), Mm(y)), bezier: false },
-                    LinePoint { p: Point::new(Mm(x + total_width), Mm(y)), bezier: false },
-                ],
-                is_closed: false
+    /// Top edges of the header and the rows, and the bottom edge of the table
+    fn row_edges(&self, top: f32) -> (Vec<f32>, f32) {
+        let mut edges = Vec::new();
+        let mut current_top = top - self.header_height();
+        for row_height in self.row_heights() {
+            edges.push(current_top);
+            current_top -= row_height;
+        }
+        (edges, current_top)
+    }
+
+    fn render_fills(&self, x: f32, top: f32) -> Vec<DrawOp> {
+        let width: f32 = self.column_widths.iter().sum();
+        let mut ops = Vec::new();
+        if let (Some(color), Some(_)) = (self.style.header_fill, &self.header) {
+            ops.push(DrawOp::SetFillColor(color));
+            ops.push(fill_rectangles(&[(x, top - self.header_height(), width, self.header_height())]));
+        }
+        if let Some(color) = self.style.stripe_fill {
+            let (edges, _) = self.row_edges(top);
+            let stripes: Vec<(f32, f32, f32, f32)> = edges.iter().zip(self.row_heights()).skip(1).step_by(2)
+                .map(|(row_top, row_height)| (x, row_top - row_height, width, row_height))
+                .collect();
+            ops.push(DrawOp::SetFillColor(color));
+            ops.push(fill_rectangles(&stripes));
+        }
+        if !ops.is_empty() {
+            ops.push(DrawOp::SetFillColor(Color::BLACK));
+        }
+        ops
+    }
+
+    fn render_borders(&self, x: f32, top: f32) -> Vec<DrawOp> {
+        let width: f32 = self.column_widths.iter().sum();
+        let (edges, bottom) = self.row_edges(top);
+        let mut ops = Vec::new();
+        let mut stroke = |border: Option<Border>, lines: Vec<Vec<(f32, f32)>>| {
+            let Some(border) = border else {
+                return;
+            };
+            if lines.is_empty() {
+                return;
+            }
+            ops.push(DrawOp::SetLineWidth(border.width));
+            if border.color != Color::BLACK {
+                ops.push(DrawOp::SetStrokeColor(border.color));
+            }
+            ops.extend(lines.iter().map(|points| stroke_line(points)));
+            if border.color != Color::BLACK {
+                ops.push(DrawOp::SetStrokeColor(Color::BLACK));
             }
+        };
+        stroke(self.style.row_border, edges.iter().skip(1).map(|&y| vec![(x, y), (x + width, y)]).collect());
+        let column_lefts = self.column_widths.iter().scan(x, |left, column_width| {
+            *left += column_width;
+            Some(*left)
         });
+        let inner_columns: Vec<f32> = column_lefts.take(self.column_widths.len().saturating_sub(1)).collect();
+        stroke(self.style.column_border, inner_columns.iter().map(|&column_x| vec![(column_x, top), (column_x, bottom)]).collect());
+        if self.header.is_some() {
+            let header_bottom = top - self.header_height();
+            stroke(self.style.header_border, vec![vec![(x, header_bottom), (x + width, header_bottom)]]);
+        }
+        stroke(self.style.outer_border, vec![vec![(x, top), (x + width, top), (x + width, bottom), (x, bottom), (x, top)]]);
         ops
     }
 }
 
 impl Component for Table {
-    fn render_at(&self, x: f32, y: f32) -> Vec<Op> {
-        let mut ops: Vec<Op> = Vec::new();
-        let mut current_y_offset = y;
+    fn render_at(&self, x: f32, y: f32) -> Vec<DrawOp> {
+        let top = y + self.measure(None).ascent;
+        let decimal_positions = self.decimal_positions();
+        let mut ops = self.render_fills(x, top);
         if let Some(header_values) = &self.header {
-            ops.extend(self.render_row_at(header_values, x, current_y_offset));
-            ops.extend(self.render_bottom_border_at(x, current_y_offset - self.row_height / 2.0));
-            current_y_offset = current_y_offset - self.row_height - self.row_height / 2.0;
+            ops.extend(self.render_row_at(header_values, x, top, self.header_height(), true, &decimal_positions));
         }
-        for row in self.rows.iter() {
-            ops.extend(self.render_row_at(row, x, current_y_offset));
-            current_y_offset = current_y_offset - self.row_height;
+        let (edges, _) = self.row_edges(top);
+        for ((row, row_top), row_height) in self.rows.iter().zip(edges).zip(self.row_heights()) {
+            ops.extend(self.render_row_at(row, x, row_top, row_height, false, &decimal_positions));
         }
+        if self.style.header_text_color.is_some() || self.style.text_color.is_some() {
+            ops.push(DrawOp::SetFillColor(Color::BLACK));
+        }
+        ops.extend(self.render_borders(x, top));
         ops
     }
+
+    /// The table is as wide as its columns, the ascent is the distance from its top edge to the
+    /// baseline of the first row
+    fn measure(&self, _width: Option<f32>) -> Size {
+        let ascent = match (&self.header, self.rows.first()) {
+            (Some(header_values), _) => self.ascent_of(header_values, self.header_height()),
+            (None, Some(first_row)) => self.ascent_of(first_row, self.height_of(first_row)),
+            (None, None) => 0.0
+        };
+        Size {
+            width: self.column_widths.iter().sum(),
+            height: self.header_height() + self.row_heights().iter().sum::<f32>(),
+            ascent
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::components::render_top;
 
     struct MockComponent {}
 
@@ -66,9 +280,13 @@ mod tests {
     }
 
     impl Component for MockComponent {
-        fn render_at(&self, _x: f32, _y: f32) -> Vec<Op> {
+        fn render_at(&self, _x: f32, _y: f32) -> Vec<DrawOp> {
             Vec::new()
         }
+
+        fn measure(&self, _width: Option<f32>) -> Size {
+            Size { width: 0.0, height: 0.0, ascent: 0.0 }
+        }
     }
 
     #[test]
@@ -80,6 +298,8 @@ mod tests {
 
         let table = Table {
             column_widths: column_widths.clone(),
+            column_alignments: Vec::new(),
+            style: TableStyle::default(),
             row_height,
             header,
             rows,
@@ -105,6 +325,8 @@ mod tests {
         ];
         let table = Table {
             column_widths,
+            column_alignments: Vec::new(),
+            style: TableStyle::default(),
             row_height,
             header,
             rows,
@@ -115,4 +337,115 @@ mod tests {
         assert_eq!(table.rows[0].len(), 2);
         assert_eq!(table.rows[1].len(), 2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_rows_grow_with_wrapped_labels() {
+        use crate::components::label::Label;
+        use crate::drawing::Font;
+        let table = Table {
+            column_widths: vec![20.0, 20.0],
+            column_alignments: Vec::new(),
+            style: TableStyle::default(),
+            row_height: 5.0,
+            header: None,
+            rows: vec![
+                Label::new_row(vec!["A", "Line 1\nLine 2\nLine 3"], 10.0, Font::REGULAR),
+                Label::new_row(vec!["B", "C"], 10.0, Font::REGULAR)
+            ]
+        };
+        assert_eq!(table.row_heights(), vec![15.0, 5.0]);
+        let ops = table.render_at(0.0, 100.0);
+        match ops.last() {
+            Some(DrawOp::Text { y, text, .. }) => {
+                assert_eq!(text, "C");
+                assert_eq!(*y, 85.0);
+            },
+            _ => panic!("Expected a text operation")
+        }
+    }
+
+    fn text_x(ops: &[DrawOp], value: &str) -> f32 {
+        ops.iter().find_map(|op| match op {
+            DrawOp::Text { x, text, .. } if text == value => Some(*x),
+            _ => None
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_column_alignments() {
+        use crate::components::label::Label;
+        use crate::drawing::Font;
+        use crate::fonts::text_width;
+        let table = Table {
+            column_widths: vec![30.0, 30.0, 31.0],
+            column_alignments: vec![ColumnAlignment::Right, ColumnAlignment::Center, ColumnAlignment::Decimal(',')],
+            style: TableStyle::default(),
+            row_height: 5.0,
+            header: Some(Label::new_row(vec!["Price", "Count", "VAT"], 10.0, Font::BOLD)),
+            rows: vec![
+                Label::new_row(vec!["12,00 €", "1", "25,5"], 10.0, Font::REGULAR),
+                Label::new_row(vec!["1200,00 €", "10", "10"], 10.0, Font::REGULAR)
+            ]
+        };
+        let ops = table.render_at(0.0, 100.0);
+        let width = |value: &str| text_width(value, 10.0, Font::REGULAR);
+        // Right edges of the columns are 1 mm from the next column
+        assert_eq!(text_x(&ops, "12,00 €"), 29.0 - width("12,00 €"));
+        assert_eq!(text_x(&ops, "1200,00 €"), 29.0 - width("1200,00 €"));
+        assert_eq!(text_x(&ops, "10"), 30.0 + (29.0 - width("10")) / 2.0);
+        // Numbers line up at the separator, the widest fraction ends at the right edge
+        let separator_x = 90.0 - width(",5");
+        assert!((text_x(&ops, "25,5") - (separator_x - width("25"))).abs() < 0.001);
+        assert!(ops.iter().any(|op| matches!(op, DrawOp::Text { x, text, .. } if text == "10" && (x - (separator_x - width("10"))).abs() < 0.001)));
+        assert_eq!(text_x(&ops, "VAT"), 90.0 - text_width("VAT", 10.0, Font::BOLD));
+    }
+
+    #[test]
+    fn test_table_style() {
+        use crate::components::label::Label;
+        use crate::drawing::Font;
+        let grey = Color { r: 0.9, g: 0.9, b: 0.9 };
+        let table = Table {
+            column_widths: vec![20.0, 30.0],
+            column_alignments: Vec::new(),
+            row_height: 5.0,
+            style: TableStyle {
+                outer_border: Some(Border { width: 1.0, color: Color::BLACK }),
+                row_border: Some(Border { width: 0.2, color: grey }),
+                column_border: None,
+                header_border: None,
+                header_fill: Some(grey),
+                stripe_fill: Some(grey),
+                header_text_color: None,
+                text_color: None,
+                cell_padding: Padding { left: 2.0, right: 2.0, top: 1.0, bottom: 1.0 }
+            },
+            header: Some(Label::new_row(vec!["A", "B"], 10.0, Font::BOLD)),
+            rows: vec![
+                Label::new_row(vec!["1", "2"], 10.0, Font::REGULAR),
+                Label::new_row(vec!["3", "4"], 10.0, Font::REGULAR),
+                Label::new_row(vec!["5", "6"], 10.0, Font::REGULAR)
+            ]
+        };
+        // The header is 7 + 2.5 mm high and the rows 7 mm with the padding
+        assert_eq!(table.measure(None).height, 9.5 + 21.0);
+        let ops = render_top(&table, 0.0, 100.0, None);
+        let fills: Vec<&Vec<(f32, f32, f32, f32)>> = ops.iter().filter_map(|op| match op {
+            DrawOp::FillRectangles(rectangles) => Some(rectangles),
+            _ => None
+        }).collect();
+        assert_eq!(fills, vec![&vec![(0.0, 90.5, 50.0, 9.5)], &vec![(0.0, 76.5, 50.0, 7.0)]]);
+        assert!(matches!(ops.iter().find(|op| matches!(op, DrawOp::Text { .. })), Some(DrawOp::Text { x: 2.0, .. })));
+        let lines: Vec<&Vec<(f32, f32)>> = ops.iter().filter_map(|op| match op {
+            DrawOp::Line(points) => Some(points),
+            _ => None
+        }).collect();
+        assert_eq!(lines, vec![
+            &vec![(0.0, 83.5), (50.0, 83.5)],
+            &vec![(0.0, 76.5), (50.0, 76.5)],
+            &vec![(0.0, 100.0), (50.0, 100.0), (50.0, 69.5), (0.0, 69.5), (0.0, 100.0)]
+        ]);
+        assert!(ops.iter().any(|op| matches!(op, DrawOp::SetStrokeColor(color) if *color == grey)));
+        assert!(matches!(ops.last(), Some(DrawOp::Line(_))));
+    }
+}
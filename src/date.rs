@@ -0,0 +1,96 @@
+use anyhow::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8
+}
+
+impl Date {
+    /// Parses dates written as "dd.mm.yyyy" (as in the invoice JSON), "yyyy-mm-dd" or "yyyymmdd"
+    pub fn parse(value: &str) -> Result<Date, Error> {
+        let value = value.trim();
+        let parts: Vec<&str> = if value.len() == 8 && value.chars().all(|c| c.is_ascii_digit()) {
+            vec![&value[0..4], &value[4..6], &value[6..8]]
+        } else if value.contains('-') {
+            value.split('-').collect()
+        } else {
+            value.split('.').rev().collect()
+        };
+        let invalid_date = || Error::msg(format!("Invalid date: {}", value));
+        if parts.len() != 3 {
+            return Err(invalid_date());
+        }
+        let year: u16 = parts[0].parse().map_err(|_| invalid_date())?;
+        let month: u8 = parts[1].parse().map_err(|_| invalid_date())?;
+        let day: u8 = parts[2].parse().map_err(|_| invalid_date())?;
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return Err(invalid_date());
+        }
+        Ok(Date { year, month, day })
+    }
+
+    pub fn to_yymmdd(&self) -> String {
+        format!("{:02}{:02}{:02}", self.year % 100, self.month, self.day)
+    }
+
+    pub fn to_iso(&self) -> String {
+        format!("{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+
+    pub fn to_ccyymmdd(&self) -> String {
+        format!("{:04}{:02}{:02}", self.year, self.month, self.day)
+    }
+
+    /// The "dd.mm.yyyy" form used in the invoice JSON
+    pub fn to_dd_mm_yyyy(&self) -> String {
+        format!("{:02}.{:02}.{:04}", self.day, self.month, self.year)
+    }
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_finnish_dates() {
+        assert_eq!(Date::parse("04.02.2025").unwrap(), Date { year: 2025, month: 2, day: 4 });
+        assert_eq!(Date::parse("1.5.2016").unwrap(), Date { year: 2016, month: 5, day: 1 });
+    }
+
+    #[test]
+    fn should_parse_iso_dates() {
+        assert_eq!(Date::parse("2024-02-29").unwrap(), Date { year: 2024, month: 2, day: 29 });
+        assert_eq!(Date::parse("20240229").unwrap(), Date { year: 2024, month: 2, day: 29 });
+    }
+
+    #[test]
+    fn should_reject_invalid_dates() {
+        assert!(Date::parse("31.02.2025").is_err());
+        assert!(Date::parse("2023-02-29").is_err());
+        assert!(Date::parse("04/02/2025").is_err());
+        assert!(Date::parse("").is_err());
+    }
+
+    #[test]
+    fn should_format_as_yymmdd() {
+        assert_eq!(Date::parse("12.6.2010").unwrap().to_yymmdd(), "100612");
+    }
+
+    #[test]
+    fn should_format_as_ccyymmdd() {
+        assert_eq!(Date::parse("12.6.2010").unwrap().to_ccyymmdd(), "20100612");
+        assert_eq!(Date::parse("12.6.2010").unwrap().to_iso(), "2010-06-12");
+        assert_eq!(Date::parse("2010-06-12").unwrap().to_dd_mm_yyyy(), "12.06.2010");
+    }
+}
@@ -0,0 +1,101 @@
+use serde::Deserialize;
+use std::rc::Rc;
+use std::sync::Mutex;
+use crate::image::Image;
+use crate::theme::Color;
+
+pub mod pdf;
+pub mod svg;
+
+/// Width and height of portrait A4, A5 and US Letter pages in mm
+pub const A4: (f32, f32) = (210.0, 297.0);
+pub const A5: (f32, f32) = (148.0, 210.0);
+pub const LETTER: (f32, f32) = (215.9, 279.4);
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FontWeight {
+    Regular,
+    Bold
+}
+
+/// Name of an installed font family
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FontFamily(&'static str);
+
+/// Names of the font families used so far, kept for the lifetime of the program so that fonts can
+/// be copied around like the other drawing values
+static FONT_FAMILIES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+impl FontFamily {
+    pub fn new(name: &str) -> FontFamily {
+        let mut families = FONT_FAMILIES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(family) = families.iter().find(|family| **family == name) {
+            return FontFamily(family);
+        }
+        let family: &'static str = Box::leak(name.to_owned().into_boxed_str());
+        families.push(family);
+        FontFamily(family)
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.0
+    }
+}
+
+/// Font of a text, without a family the first installed of the default sans-serif fonts is used
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Font {
+    pub weight: FontWeight,
+    pub family: Option<FontFamily>
+}
+
+impl Font {
+    pub const REGULAR: Font = Font { weight: FontWeight::Regular, family: None };
+    pub const BOLD: Font = Font { weight: FontWeight::Bold, family: None };
+}
+
+/// Backend independent drawing operation, coordinates and sizes are in mm from the lower left
+/// corner of the page. Line widths, dash lengths and font sizes are in points.
+pub enum DrawOp {
+    /// Single line of text, (x, y) is the start of the baseline
+    Text { x: f32, y: f32, text: String, font_size: f32, font: Font },
+    /// Fills the rectangles (x, y of the lower left corner, width, height) with the current fill color
+    FillRectangles(Vec<(f32, f32, f32, f32)>),
+    /// Strokes a line through the points (x, y) with the current line width and dash pattern
+    Line(Vec<(f32, f32)>),
+    SetLineWidth(f32),
+    /// Dash and gap length of the following lines, `None` draws solid lines
+    SetLineDash(Option<f32>),
+    /// Color of the following filled shapes and text
+    SetFillColor(Color),
+    /// Color of the following lines
+    SetStrokeColor(Color),
+    /// Area opening the URI when clicked, (x, y) is the lower left corner
+    Link { x: f32, y: f32, width: f32, height: f32, uri: String },
+    /// Image scaled to the rectangle, (x, y) is the lower left corner
+    Image { x: f32, y: f32, width: f32, height: f32, image: Rc<Image> }
+}
+
+impl DrawOp {
+    /// The operation moved right by `dx` and up by `dy` mm
+    pub fn translated(self, dx: f32, dy: f32) -> DrawOp {
+        match self {
+            DrawOp::Text { x, y, text, font_size, font } => DrawOp::Text { x: x + dx, y: y + dy, text, font_size, font },
+            DrawOp::FillRectangles(rectangles) => DrawOp::FillRectangles(
+                rectangles.into_iter().map(|(x, y, width, height)| (x + dx, y + dy, width, height)).collect()
+            ),
+            DrawOp::Line(points) => DrawOp::Line(points.into_iter().map(|(x, y)| (x + dx, y + dy)).collect()),
+            DrawOp::Link { x, y, width, height, uri } => DrawOp::Link { x: x + dx, y: y + dy, width, height, uri },
+            DrawOp::Image { x, y, width, height, image } => DrawOp::Image { x: x + dx, y: y + dy, width, height, image },
+            op => op
+        }
+    }
+}
+
+/// Page size in mm with its drawing operations
+pub struct Page {
+    pub width: f32,
+    pub height: f32,
+    pub ops: Vec<DrawOp>
+}
@@ -0,0 +1,110 @@
+use anyhow::Error;
+use printpdf::*;
+use std::rc::Rc;
+use crate::drawing::{DrawOp, Page};
+use crate::fonts::FontManager;
+use crate::image::Image;
+use crate::theme;
+
+/// Images are placed at 300 DPI and scaled from there to the requested size
+const IMAGE_DPI: f32 = 300.0;
+
+/// Converts the pages into PDF pages, loading the fonts and images into the document
+pub fn to_pdf_pages(doc: &mut PdfDocument, pages: &[Page]) -> Result<Vec<PdfPage>, Error> {
+    let mut font_manager = FontManager::default();
+    let mut images: Vec<(Rc<Image>, XObjectId)> = Vec::new();
+    let mut pdf_pages = Vec::new();
+    for page in pages {
+        let mut ops = Vec::new();
+        for op in page.ops.iter() {
+            match op {
+                DrawOp::Text { x, y, text, font_size, font } => {
+                    let font = font_manager.font_id(doc, *font)?;
+                    ops.push(Op::SetFontSize { size: Pt(*font_size), font: font.clone() });
+                    ops.push(Op::StartTextSection);
+                    ops.push(Op::SetTextCursor { pos: point(*x, *y) });
+                    ops.push(Op::WriteText { items: vec![TextItem::Text(text.clone())], font });
+                    ops.push(Op::EndTextSection);
+                },
+                DrawOp::FillRectangles(rectangles) => ops.push(fill_rectangles(rectangles)),
+                DrawOp::Line(points) => ops.push(Op::DrawLine {
+                    line: Line {
+                        points: points.iter().map(|&(x, y)| LinePoint { p: point(x, y), bezier: false }).collect(),
+                        is_closed: false
+                    }
+                }),
+                DrawOp::SetLineWidth(width) => ops.push(Op::SetOutlineThickness { pt: Pt(*width) }),
+                DrawOp::SetLineDash(dash) => ops.push(Op::SetLineDashPattern {
+                    dash: match dash {
+                        Some(length) => LineDashPattern { dash_1: Some(length.round() as i64), gap_1: Some(length.round() as i64), ..Default::default() },
+                        None => LineDashPattern::default()
+                    }
+                }),
+                DrawOp::SetFillColor(color) => ops.push(Op::SetFillColor { col: rgb(color) }),
+                DrawOp::SetStrokeColor(color) => ops.push(Op::SetOutlineColor { col: rgb(color) }),
+                DrawOp::Link { x, y, width, height, uri } => ops.push(Op::LinkAnnotation {
+                    link: LinkAnnotation::new(
+                        Rect { x: Mm(*x).into(), y: Mm(*y).into(), width: Mm(*width).into(), height: Mm(*height).into() },
+                        Actions::Uri(uri.clone()),
+                        Some(BorderArray::Solid([0.0, 0.0, 0.0])),
+                        Some(ColorArray::Transparent),
+                        None
+                    )
+                }),
+                DrawOp::Image { x, y, width, height, image } => {
+                    let id = match images.iter().find(|(added, _)| Rc::ptr_eq(added, image)) {
+                        Some((_, id)) => id.clone(),
+                        None => {
+                            let id = doc.add_image(&image.raw);
+                            images.push((image.clone(), id.clone()));
+                            id
+                        }
+                    };
+                    let natural_width = image.raw.width as f32 / IMAGE_DPI * 25.4;
+                    let natural_height = image.raw.height as f32 / IMAGE_DPI * 25.4;
+                    ops.push(Op::UseXobject {
+                        id,
+                        transform: XObjectTransform {
+                            translate_x: Some(Mm(*x).into()),
+                            translate_y: Some(Mm(*y).into()),
+                            rotate: None,
+                            scale_x: Some(width / natural_width),
+                            scale_y: Some(height / natural_height),
+                            dpi: Some(IMAGE_DPI)
+                        }
+                    });
+                }
+            }
+        }
+        pdf_pages.push(PdfPage::new(Mm(page.width), Mm(page.height), ops));
+    }
+    Ok(pdf_pages)
+}
+
+fn point(x: f32, y: f32) -> Point {
+    Point::new(Mm(x), Mm(y))
+}
+
+fn rgb(color: &theme::Color) -> Color {
+    Color::Rgb(Rgb { r: color.r, g: color.g, b: color.b, icc_profile: None })
+}
+
+fn fill_rectangles(rectangles: &[(f32, f32, f32, f32)]) -> Op {
+    let rings = rectangles.iter().map(|&(x, y, width, height)| {
+        PolygonRing {
+            points: vec![
+                LinePoint { p: point(x, y), bezier: false },
+                LinePoint { p: point(x + width, y), bezier: false },
+                LinePoint { p: point(x + width, y + height), bezier: false },
+                LinePoint { p: point(x, y + height), bezier: false },
+            ]
+        }
+    }).collect();
+    Op::DrawPolygon {
+        polygon: Polygon {
+            rings,
+            mode: PaintMode::Fill,
+            winding_order: WindingOrder::NonZero
+        }
+    }
+}
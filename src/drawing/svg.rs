@@ -0,0 +1,115 @@
+use quick_xml::escape::escape;
+use std::fmt::Write;
+use crate::drawing::{DrawOp, FontWeight, Page};
+use crate::theme::Color;
+
+const FONT_FAMILY: &str = "Helvetica, Arial, 'DejaVu Sans', 'Liberation Sans', FreeSans, Ubuntu, 'Noto Sans', sans-serif";
+const MM_PER_PT: f32 = 25.4 / 72.0;
+
+/// Standalone SVG image of the page, the viewBox is in mm with the y axis pointing down. Texts with
+/// a font family prefer it over the default fonts.
+pub fn to_svg(page: &Page) -> String {
+    let mut svg = String::new();
+    let mut line_width = 1.0;
+    let mut line_dash: Option<f32> = None;
+    let mut fill = Color::BLACK;
+    let mut stroke = Color::BLACK;
+    let y = |y: f32| page.height - y;
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}mm" height="{1}mm" viewBox="0 0 {0} {1}">"#,
+        number(page.width), number(page.height));
+    let _ = writeln!(svg, r#"<rect width="{}" height="{}" fill="white"/>"#, number(page.width), number(page.height));
+    let _ = writeln!(svg, r#"<g font-family="{}" style="white-space: pre">"#, FONT_FAMILY);
+    for op in page.ops.iter() {
+        let _ = match op {
+            DrawOp::Text { x, y: baseline, text, font_size, font } => writeln!(svg,
+                r#"<text x="{}" y="{}" font-size="{}"{}{} fill="{}">{}</text>"#,
+                number(*x), number(y(*baseline)), number(font_size * MM_PER_PT),
+                font.family.map(|family| format!(r#" font-family="'{}', {}""#, escape(family.name()), FONT_FAMILY)).unwrap_or_default(),
+                if font.weight == FontWeight::Bold { r#" font-weight="bold""# } else { "" },
+                hex(&fill), escape(text.as_str())),
+            DrawOp::FillRectangles(rectangles) => {
+                let path: String = rectangles.iter()
+                    .map(|&(x, bottom, width, height)| format!("M{} {}h{}v{}h{}z", number(x), number(y(bottom + height)), number(width), number(height), number(-width)))
+                    .collect();
+                writeln!(svg, r#"<path d="{}" fill="{}"/>"#, path, hex(&fill))
+            },
+            DrawOp::Line(points) => {
+                let points: Vec<String> = points.iter().map(|&(x, point_y)| format!("{},{}", number(x), number(y(point_y)))).collect();
+                let dash = line_dash.map(|length| format!(r#" stroke-dasharray="{}""#, number(length * MM_PER_PT))).unwrap_or_default();
+                writeln!(svg, r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}"{}/>"#,
+                    points.join(" "), hex(&stroke), number(line_width * MM_PER_PT), dash)
+            },
+            DrawOp::SetLineWidth(width) => {
+                line_width = *width;
+                Ok(())
+            },
+            DrawOp::SetLineDash(dash) => {
+                line_dash = *dash;
+                Ok(())
+            },
+            DrawOp::SetFillColor(color) => {
+                fill = *color;
+                Ok(())
+            },
+            DrawOp::SetStrokeColor(color) => {
+                stroke = *color;
+                Ok(())
+            },
+            DrawOp::Link { x, y: bottom, width, height, uri } => writeln!(svg,
+                r#"<a href="{}"><rect x="{}" y="{}" width="{}" height="{}" fill="transparent"/></a>"#,
+                escape(uri.as_str()), number(*x), number(y(bottom + height)), number(*width), number(*height)),
+            DrawOp::Image { x, y: bottom, width, height, image } => writeln!(svg,
+                r#"<image x="{}" y="{}" width="{}" height="{}" preserveAspectRatio="none" href="{}"/>"#,
+                number(*x), number(y(bottom + height)), number(*width), number(*height), image.to_data_uri())
+        };
+    }
+    let _ = writeln!(svg, "</g>");
+    let _ = writeln!(svg, "</svg>");
+    svg
+}
+
+/// Rounds to 0.001 mm and drops the trailing zeros to keep the output small and stable for diffs
+fn number(value: f32) -> String {
+    let formatted = format!("{:.3}", value);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    match trimmed {
+        "-0" => "0".to_owned(),
+        _ => trimmed.to_owned()
+    }
+}
+
+fn hex(color: &Color) -> String {
+    color.to_hex()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drawing::{Font, FontFamily};
+
+    #[test]
+    fn should_flip_coordinates() {
+        let page = Page {
+            width: 210.0,
+            height: 297.0,
+            ops: vec![
+                DrawOp::Text { x: 15.0, y: 270.0, text: "Lasku <1>".to_owned(), font_size: 22.0, font: Font::BOLD },
+                DrawOp::Text { x: 15.0, y: 260.0, text: "Aino".to_owned(), font_size: 10.0, font: Font { weight: FontWeight::Regular, family: Some(FontFamily::new("Liberation Serif")) } },
+                DrawOp::SetLineWidth(0.8),
+                DrawOp::SetLineDash(Some(3.0)),
+                DrawOp::Line(vec![(15.0, 25.0), (200.0, 25.0)]),
+                DrawOp::SetFillColor(Color::WHITE),
+                DrawOp::FillRectangles(vec![(10.0, 100.0, 5.0, 2.5)]),
+                DrawOp::Link { x: 10.0, y: 20.0, width: 30.0, height: 3.5, uri: "mailto:a@example.com".to_owned() }
+            ]
+        };
+        let svg = to_svg(&page);
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="210mm" height="297mm" viewBox="0 0 210 297">"#));
+        assert!(svg.contains(r##"<text x="15" y="27" font-size="7.761" font-weight="bold" fill="#000000">Lasku &lt;1&gt;</text>"##));
+        assert!(svg.contains(&format!(r##"<text x="15" y="37" font-size="3.528" font-family="'Liberation Serif', {}" fill="#000000">Aino</text>"##, FONT_FAMILY)));
+        assert!(svg.contains(r##"<polyline points="15,272 200,272" fill="none" stroke="#000000" stroke-width="0.282" stroke-dasharray="1.058"/>"##));
+        assert!(svg.contains(r##"<path d="M10 194.5h5v2.5h-5z" fill="#ffffff"/>"##));
+        assert!(svg.contains(r#"<a href="mailto:a@example.com"><rect x="10" y="273.5" width="30" height="3.5" fill="transparent"/></a>"#));
+        assert!(svg.ends_with("</g>\n</svg>\n"));
+    }
+}
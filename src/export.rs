@@ -0,0 +1,58 @@
+use anyhow::{Context, Error};
+use bigdecimal::{BigDecimal, RoundingMode, Zero};
+use quick_xml::events::{BytesDecl, BytesText, Event};
+use quick_xml::Writer;
+use std::io;
+
+pub mod cii;
+pub mod en16931;
+pub mod factur_x;
+pub mod finvoice;
+pub mod ubl;
+pub mod validation;
+pub mod xrechnung;
+
+pub(crate) type XmlWriter = Writer<Vec<u8>>;
+
+/// Writes an indented UTF-8 XML document, `write_root` writes the root element
+pub(crate) fn xml_document<F>(write_root: F) -> Result<String, Error>
+where
+    F: FnOnce(&mut XmlWriter) -> io::Result<()>
+{
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    write_root(&mut writer)?;
+    String::from_utf8(writer.into_inner()).context("Generated XML is not valid UTF-8")
+}
+
+pub(crate) fn text_element(writer: &mut XmlWriter, name: &str, value: &str) -> io::Result<()> {
+    writer.create_element(name).write_text_content(BytesText::new(value))?;
+    Ok(())
+}
+
+pub(crate) fn text_element_with_attributes(writer: &mut XmlWriter, name: &str, attributes: &[(&str, &str)], value: &str) -> io::Result<()> {
+    writer.create_element(name)
+        .with_attributes(attributes.iter().copied())
+        .write_text_content(BytesText::new(value))?;
+    Ok(())
+}
+
+pub(crate) fn optional_text_element(writer: &mut XmlWriter, name: &str, value: Option<&str>) -> io::Result<()> {
+    match value {
+        Some(value) => text_element(writer, name, value),
+        None => Ok(())
+    }
+}
+
+/// Amount rounded to cents, e.g. "1523.90"
+pub(crate) fn amount_text(amount: &BigDecimal) -> String {
+    format!("{}", amount.with_scale_round(2, RoundingMode::HalfUp))
+}
+
+/// Percentage or quantity without trailing zeros, e.g. "25.5"
+pub(crate) fn decimal_text(value: &BigDecimal) -> String {
+    if value.is_zero() {
+        return "0".to_owned();
+    }
+    value.normalized().to_plain_string()
+}
@@ -0,0 +1,247 @@
+use anyhow::Error;
+use bigdecimal::BigDecimal;
+use quick_xml::events::BytesText;
+use std::io;
+use crate::date::Date;
+use crate::export::en16931::{En16931Invoice, Endpoint};
+use crate::export::validation::{validate_en16931, ValidationErrors};
+use crate::export::{amount_text, decimal_text, optional_text_element, text_element, text_element_with_attributes, xml_document, XmlWriter};
+use crate::invoice::{BillingInformation, Invoice};
+
+/// Specification identifier (BT-24) of the document, written as the guideline parameter
+pub struct CiiProfile {
+    pub guideline_id: &'static str
+}
+
+pub const EN16931_PROFILE: CiiProfile = CiiProfile {
+    guideline_id: "urn:cen.eu:en16931:2017"
+};
+
+/// Maps the invoice to an UN/CEFACT Cross Industry Invoice (D16B), as used by Factur-X and ZUGFeRD
+pub fn cii_xml(invoice: &Invoice, profile: &CiiProfile) -> Result<String, Error> {
+    ValidationErrors::check(validate_en16931(invoice))?;
+    let document = En16931Invoice::new(invoice)?;
+    xml_document(|writer| {
+        writer.create_element("rsm:CrossIndustryInvoice")
+            .with_attributes([
+                ("xmlns:rsm", "urn:un:unece:uncefact:data:standard:CrossIndustryInvoice:100"),
+                ("xmlns:ram", "urn:un:unece:uncefact:data:standard:ReusableAggregateBusinessInformationEntity:100"),
+                ("xmlns:qdt", "urn:un:unece:uncefact:data:standard:QualifiedDataType:100"),
+                ("xmlns:udt", "urn:un:unece:uncefact:data:standard:UnqualifiedDataType:100")
+            ])
+            .write_inner_content(|writer| write_document(writer, &document, profile))?;
+        Ok(())
+    })
+}
+
+fn write_document(writer: &mut XmlWriter, document: &En16931Invoice, profile: &CiiProfile) -> io::Result<()> {
+    let invoice = document.invoice;
+    writer.create_element("rsm:ExchangedDocumentContext").write_inner_content(|writer| {
+        writer.create_element("ram:GuidelineSpecifiedDocumentContextParameter").write_inner_content(|writer| {
+            text_element(writer, "ram:ID", profile.guideline_id)
+        })?;
+        Ok(())
+    })?;
+    writer.create_element("rsm:ExchangedDocument").write_inner_content(|writer| {
+        text_element(writer, "ram:ID", &invoice.invoice_number)?;
+        text_element(writer, "ram:TypeCode", document.type_code())?;
+        date_time_element(writer, "ram:IssueDateTime", &document.issue_date)?;
+        for note in [invoice.invoice_description.as_ref(), invoice.note.as_ref()].into_iter().flatten() {
+            writer.create_element("ram:IncludedNote").write_inner_content(|writer| {
+                text_element(writer, "ram:Content", note)
+            })?;
+        }
+        Ok(())
+    })?;
+    writer.create_element("rsm:SupplyChainTradeTransaction").write_inner_content(|writer| {
+        write_lines(writer, document)?;
+        writer.create_element("ram:ApplicableHeaderTradeAgreement").write_inner_content(|writer| {
+            optional_text_element(writer, "ram:BuyerReference", invoice.buyer_reference.as_deref())?;
+            write_trade_party(writer, "ram:SellerTradeParty", &invoice.billed_by)?;
+            write_trade_party(writer, "ram:BuyerTradeParty", &invoice.billed_to)
+        })?;
+        writer.create_element("ram:ApplicableHeaderTradeDelivery").write_empty()?;
+        write_header_settlement(writer, document)
+    })?;
+    Ok(())
+}
+
+fn write_lines(writer: &mut XmlWriter, document: &En16931Invoice) -> io::Result<()> {
+    for (index, line) in document.lines.iter().enumerate() {
+        writer.create_element("ram:IncludedSupplyChainTradeLineItem").write_inner_content(|writer| {
+            writer.create_element("ram:AssociatedDocumentLineDocument").write_inner_content(|writer| {
+                text_element(writer, "ram:LineID", &(index + 1).to_string())
+            })?;
+            writer.create_element("ram:SpecifiedTradeProduct").write_inner_content(|writer| {
+                text_element(writer, "ram:Name", line.name)
+            })?;
+            writer.create_element("ram:SpecifiedLineTradeAgreement").write_inner_content(|writer| {
+                writer.create_element("ram:NetPriceProductTradePrice").write_inner_content(|writer| {
+                    text_element(writer, "ram:ChargeAmount", &decimal_text(&line.unit_price))
+                })?;
+                Ok(())
+            })?;
+            writer.create_element("ram:SpecifiedLineTradeDelivery").write_inner_content(|writer| {
+                text_element_with_attributes(writer, "ram:BilledQuantity", &[("unitCode", "C62")], &decimal_text(&line.quantity))
+            })?;
+            writer.create_element("ram:SpecifiedLineTradeSettlement").write_inner_content(|writer| {
+                writer.create_element("ram:ApplicableTradeTax").write_inner_content(|writer| {
+                    text_element(writer, "ram:TypeCode", "VAT")?;
+                    text_element(writer, "ram:CategoryCode", document.vat_category.code())?;
+                    if document.has_vat_rate() {
+                        text_element(writer, "ram:RateApplicablePercent", &decimal_text(&document.vat_rate))?;
+                    }
+                    Ok(())
+                })?;
+                writer.create_element("ram:SpecifiedTradeSettlementLineMonetarySummation").write_inner_content(|writer| {
+                    text_element(writer, "ram:LineTotalAmount", &amount_text(&line.amount))
+                })?;
+                Ok(())
+            })?;
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+fn write_trade_party(writer: &mut XmlWriter, name: &str, party: &BillingInformation) -> io::Result<()> {
+    writer.create_element(name).write_inner_content(|writer| {
+        text_element(writer, "ram:Name", &party.name)?;
+        if let Some(company_id) = party.company_id.as_ref() {
+            writer.create_element("ram:SpecifiedLegalOrganization").write_inner_content(|writer| {
+                text_element(writer, "ram:ID", company_id)
+            })?;
+        }
+        if party.contact_name.is_some() || party.phone_number.is_some() || party.email.is_some() {
+            writer.create_element("ram:DefinedTradeContact").write_inner_content(|writer| {
+                optional_text_element(writer, "ram:PersonName", party.contact_name.as_deref())?;
+                if let Some(phone_number) = party.phone_number.as_ref() {
+                    writer.create_element("ram:TelephoneUniversalCommunication").write_inner_content(|writer| {
+                        text_element(writer, "ram:CompleteNumber", phone_number)
+                    })?;
+                }
+                if let Some(email) = party.email.as_ref() {
+                    writer.create_element("ram:EmailURIUniversalCommunication").write_inner_content(|writer| {
+                        text_element(writer, "ram:URIID", email)
+                    })?;
+                }
+                Ok(())
+            })?;
+        }
+        writer.create_element("ram:PostalTradeAddress").write_inner_content(|writer| {
+            optional_text_element(writer, "ram:PostcodeCode", party.postal_code.as_deref())?;
+            text_element(writer, "ram:LineOne", &party.address_line_1)?;
+            optional_text_element(writer, "ram:CityName", party.town.as_deref())?;
+            optional_text_element(writer, "ram:CountryID", party.country_code.as_deref())
+        })?;
+        if let Ok(endpoint) = Endpoint::of_party(party) {
+            writer.create_element("ram:URIUniversalCommunication").write_inner_content(|writer| {
+                text_element_with_attributes(writer, "ram:URIID", &[("schemeID", &endpoint.scheme)], &endpoint.identifier)
+            })?;
+        }
+        if let Some(vat_id) = party.vat_id.as_ref() {
+            writer.create_element("ram:SpecifiedTaxRegistration").write_inner_content(|writer| {
+                text_element_with_attributes(writer, "ram:ID", &[("schemeID", "VA")], vat_id)
+            })?;
+        }
+        Ok(())
+    })?;
+    Ok(())
+}
+
+fn write_header_settlement(writer: &mut XmlWriter, document: &En16931Invoice) -> io::Result<()> {
+    let invoice = document.invoice;
+    let currency = invoice.currency.as_str();
+    let bic = invoice.bank_details.bic_code.trim();
+    writer.create_element("ram:ApplicableHeaderTradeSettlement").write_inner_content(|writer| {
+        optional_text_element(writer, "ram:PaymentReference", document.payment_reference.as_deref())?;
+        text_element(writer, "ram:InvoiceCurrencyCode", currency)?;
+        writer.create_element("ram:SpecifiedTradeSettlementPaymentMeans").write_inner_content(|writer| {
+            text_element(writer, "ram:TypeCode", document.payment_means_code())?;
+            writer.create_element("ram:PayeePartyCreditorFinancialAccount").write_inner_content(|writer| {
+                text_element(writer, "ram:IBANID", &document.iban)
+            })?;
+            if !bic.is_empty() {
+                writer.create_element("ram:PayeeSpecifiedCreditorFinancialInstitution").write_inner_content(|writer| {
+                    text_element(writer, "ram:BICID", bic)
+                })?;
+            }
+            Ok(())
+        })?;
+        writer.create_element("ram:ApplicableTradeTax").write_inner_content(|writer| {
+            text_element(writer, "ram:CalculatedAmount", &amount_text(&document.total_vat))?;
+            text_element(writer, "ram:TypeCode", "VAT")?;
+            if document.vat_category.requires_exemption_reason() {
+                optional_text_element(writer, "ram:ExemptionReason", invoice.vat_exemption_reason.as_deref())?;
+            }
+            text_element(writer, "ram:BasisAmount", &amount_text(&document.total_price_without_vat))?;
+            text_element(writer, "ram:CategoryCode", document.vat_category.code())?;
+            if document.vat_category.requires_exemption_reason() {
+                optional_text_element(writer, "ram:ExemptionReasonCode", document.vat_category.exemption_reason_code())?;
+            }
+            if document.has_vat_rate() {
+                text_element(writer, "ram:RateApplicablePercent", &decimal_text(&document.vat_rate))?;
+            }
+            Ok(())
+        })?;
+        writer.create_element("ram:SpecifiedTradePaymentTerms").write_inner_content(|writer| {
+            date_time_element(writer, "ram:DueDateDateTime", &document.due_date)
+        })?;
+        writer.create_element("ram:SpecifiedTradeSettlementHeaderMonetarySummation").write_inner_content(|writer| {
+            text_element(writer, "ram:LineTotalAmount", &amount_text(&document.total_price_without_vat))?;
+            text_element(writer, "ram:TaxBasisTotalAmount", &amount_text(&document.total_price_without_vat))?;
+            amount_element(writer, "ram:TaxTotalAmount", &document.total_vat, currency)?;
+            text_element(writer, "ram:GrandTotalAmount", &amount_text(&document.total_price))?;
+            text_element(writer, "ram:DuePayableAmount", &amount_text(&document.total_price))
+        })?;
+        Ok(())
+    })?;
+    Ok(())
+}
+
+/// Dates are written in the UNTDID 2379 format 102 (CCYYMMDD)
+fn date_time_element(writer: &mut XmlWriter, name: &str, date: &Date) -> io::Result<()> {
+    writer.create_element(name).write_inner_content(|writer| {
+        text_element_with_attributes(writer, "udt:DateTimeString", &[("format", "102")], &date.to_ccyymmdd())
+    })?;
+    Ok(())
+}
+
+fn amount_element(writer: &mut XmlWriter, name: &str, amount: &BigDecimal, currency: &str) -> io::Result<()> {
+    writer.create_element(name)
+        .with_attribute(("currencyID", currency))
+        .write_text_content(BytesText::new(&amount_text(amount)))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invoice::parse_invoice_json;
+    use crate::test_fixtures::INVOICE;
+
+    #[test]
+    fn should_map_invoice_to_cii() {
+        let xml = cii_xml(&parse_invoice_json(INVOICE).unwrap(), &EN16931_PROFILE).unwrap();
+        assert!(xml.contains("<ram:ID>urn:cen.eu:en16931:2017</ram:ID>"));
+        assert!(xml.contains("<ram:TypeCode>380</ram:TypeCode>"));
+        assert!(xml.contains("<udt:DateTimeString format=\"102\">20250204</udt:DateTimeString>"));
+        assert!(xml.contains("<ram:URIID schemeID=\"0216\">003712345678</ram:URIID>"));
+        assert!(xml.contains("<ram:ID schemeID=\"VA\">DE123456789</ram:ID>"));
+        assert!(xml.contains("<ram:PaymentReference>RF18539007547034</ram:PaymentReference>"));
+        assert!(xml.contains("<ram:ChargeAmount>100</ram:ChargeAmount>"));
+        assert!(xml.contains("<ram:BasisAmount>200.00</ram:BasisAmount>"));
+        assert!(xml.contains("<ram:TaxTotalAmount currencyID=\"EUR\">51.00</ram:TaxTotalAmount>"));
+        assert!(xml.contains("<ram:DuePayableAmount>251.00</ram:DuePayableAmount>"));
+    }
+
+    #[test]
+    fn should_write_credit_notes_with_positive_totals() {
+        let mut invoice = parse_invoice_json(INVOICE).unwrap();
+        invoice.invoice_lines[0].price = -invoice.invoice_lines[0].price.clone();
+        let xml = cii_xml(&invoice, &EN16931_PROFILE).unwrap();
+        assert!(xml.contains("<ram:TypeCode>381</ram:TypeCode>"));
+        assert!(xml.contains("<ram:BilledQuantity unitCode=\"C62\">2</ram:BilledQuantity>"));
+        assert!(xml.contains("<ram:GrandTotalAmount>251.00</ram:GrandTotalAmount>"));
+    }
+}
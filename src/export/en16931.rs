@@ -0,0 +1,143 @@
+use anyhow::Error;
+use bigdecimal::{BigDecimal, RoundingMode, Signed};
+use crate::date::Date;
+use crate::invoice::{BillingInformation, Invoice, VatCategory};
+use crate::payment::iban::validate_iban;
+use crate::payment::reference::normalize_reference;
+
+/// Invoice data shared by the EN 16931 syntaxes (UBL and CII). A negative total makes the
+/// document a credit note, amounts of credit notes are negated so that the totals are positive.
+pub(crate) struct En16931Invoice<'a> {
+    pub invoice: &'a Invoice,
+    pub credit_note: bool,
+    pub issue_date: Date,
+    pub due_date: Date,
+    pub vat_category: VatCategory,
+    pub vat_rate: BigDecimal,
+    pub total_price: BigDecimal,
+    pub total_price_without_vat: BigDecimal,
+    pub total_vat: BigDecimal,
+    pub lines: Vec<En16931Line<'a>>,
+    pub iban: String,
+    pub payment_reference: Option<String>
+}
+
+/// Item prices can't be negative, so negative line amounts have negative quantities
+pub(crate) struct En16931Line<'a> {
+    pub name: &'a str,
+    pub quantity: BigDecimal,
+    pub unit_price: BigDecimal,
+    pub amount: BigDecimal
+}
+
+impl<'a> En16931Invoice<'a> {
+    pub fn new(invoice: &'a Invoice) -> Result<En16931Invoice<'a>, Error> {
+        let totals = invoice.totals();
+        let credit_note = totals.total_price.is_negative();
+        let sign = if credit_note { BigDecimal::from(-1) } else { BigDecimal::from(1) };
+        let vat_category = invoice.vat_category();
+        if vat_category.requires_exemption_reason() && vat_category.exemption_reason_code().is_none() && invoice.vat_exemption_reason.is_none() {
+            return Err(Error::msg(format!("VAT exemption reason is required for VAT category {}", vat_category.code())));
+        }
+        let lines = invoice.invoice_lines.iter().zip(totals.line_prices_without_vat.iter())
+            .map(|(line, price_without_vat)| {
+                let amount = price_without_vat * &sign;
+                let quantity = BigDecimal::from(line.count.max(1) as u64);
+                let unit_price = (amount.abs() / &quantity).with_scale_round(4, RoundingMode::HalfUp);
+                En16931Line {
+                    name: &line.name,
+                    quantity: if amount.is_negative() { -quantity } else { quantity },
+                    unit_price,
+                    amount
+                }
+            })
+            .collect();
+        Ok(En16931Invoice {
+            invoice,
+            credit_note,
+            issue_date: Date::parse(&invoice.billed_at)?,
+            due_date: Date::parse(&invoice.due_date)?,
+            vat_category,
+            vat_rate: invoice.vat_rate(),
+            total_price: &totals.total_price * &sign,
+            total_price_without_vat: &totals.total_price_without_vat * &sign,
+            total_vat: &totals.total_vat * &sign,
+            lines,
+            iban: validate_iban(&invoice.bank_details.account_number)?,
+            payment_reference: invoice.reference_id.as_deref().map(normalize_reference)
+        })
+    }
+
+    /// UNTDID 1001 document type code, 380 for a commercial invoice and 381 for a credit note
+    pub fn type_code(&self) -> &'static str {
+        if self.credit_note { "381" } else { "380" }
+    }
+
+    /// UNTDID 4461 payment means code, 58 for a SEPA credit transfer and 30 for any other credit transfer
+    pub fn payment_means_code(&self) -> &'static str {
+        if self.invoice.currency == "EUR" { "58" } else { "30" }
+    }
+
+    /// There is no VAT rate for supplies outside the scope of VAT
+    pub fn has_vat_rate(&self) -> bool {
+        self.vat_category != VatCategory::OutsideScope
+    }
+}
+
+/// Electronic address of a party, e.g. scheme "0216" (Finnish OVT code) and identifier "003712345678"
+#[derive(Debug, PartialEq)]
+pub struct Endpoint {
+    pub scheme: String,
+    pub identifier: String
+}
+
+impl Endpoint {
+    /// Uses `endpoint_id` of the party, or the OVT code derived from the business ID of a Finnish party
+    pub fn of_party(party: &BillingInformation) -> Result<Endpoint, Error> {
+        if let Some(endpoint_id) = party.endpoint_id.as_ref() {
+            return match endpoint_id.split_once(':') {
+                Some((scheme, identifier)) if !scheme.trim().is_empty() && !identifier.trim().is_empty() => Ok(Endpoint {
+                    scheme: scheme.trim().to_owned(),
+                    identifier: identifier.trim().to_owned()
+                }),
+                _ => Err(Error::msg(format!("Endpoint ID should be written as \"scheme:identifier\": {}", endpoint_id)))
+            };
+        }
+        let business_id: Option<String> = match (party.country_code.as_deref(), party.company_id.as_ref()) {
+            (Some("FI"), Some(company_id)) => Some(company_id.chars().filter(|c| c.is_ascii_digit()).collect()),
+            _ => None
+        };
+        match business_id {
+            Some(business_id) if business_id.len() == 8 => Ok(Endpoint {
+                scheme: "0216".to_owned(),
+                identifier: format!("0037{}", business_id)
+            }),
+            _ => Err(Error::msg(format!("Endpoint ID of {} is missing", party.name)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn party(json: &str) -> BillingInformation {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn should_parse_endpoint_ids() {
+        let buyer = party(r#"{ "name": "Stadt Musterhausen", "address_line_1": "Rathausplatz 1", "endpoint_id": "0204:991-12345-67" }"#);
+        assert_eq!(Endpoint::of_party(&buyer).unwrap(), Endpoint { scheme: "0204".to_owned(), identifier: "991-12345-67".to_owned() });
+        let buyer = party(r#"{ "name": "Stadt Musterhausen", "address_line_1": "Rathausplatz 1", "endpoint_id": "991-12345-67" }"#);
+        assert!(Endpoint::of_party(&buyer).is_err());
+    }
+
+    #[test]
+    fn should_derive_ovt_code_for_finnish_companies() {
+        let seller = party(r#"{ "name": "Example Oy", "address_line_1": "Katu 1", "country_code": "FI", "company_id": "1234567-8" }"#);
+        assert_eq!(Endpoint::of_party(&seller).unwrap(), Endpoint { scheme: "0216".to_owned(), identifier: "003712345678".to_owned() });
+        let seller = party(r#"{ "name": "Example GmbH", "address_line_1": "Strasse 1", "country_code": "DE", "company_id": "HRB 1234" }"#);
+        assert!(Endpoint::of_party(&seller).is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::Error;
+use crate::export::cii::{cii_xml, CiiProfile, EN16931_PROFILE};
+use crate::invoice::Invoice;
+use crate::pdf::{AttachmentRelationship, PdfAttachment, PdfOutputOptions, XmpExtension, XmpProperty};
+
+/// Name of the embedded CII invoice required by Factur-X and ZUGFeRD 2
+pub const FACTUR_X_FILE_NAME: &str = "factur-x.xml";
+
+const FACTUR_X_NAMESPACE: &str = "urn:factur-x:pdfa:CrossIndustryDocument:invoice:1p0#";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FacturXProfile {
+    /// EN 16931, called Comfort in ZUGFeRD
+    En16931
+}
+
+impl FacturXProfile {
+    /// Value of the ConformanceLevel XMP property
+    pub fn conformance_level(&self) -> &'static str {
+        match self {
+            FacturXProfile::En16931 => "EN 16931"
+        }
+    }
+
+    pub fn cii_profile(&self) -> &'static CiiProfile {
+        match self {
+            FacturXProfile::En16931 => &EN16931_PROFILE
+        }
+    }
+}
+
+/// PDF/A-3 output options embedding the invoice as factur-x.xml
+pub fn factur_x_output_options(invoice: &Invoice, profile: FacturXProfile) -> Result<PdfOutputOptions, Error> {
+    Ok(PdfOutputOptions {
+        pdf_a3: true,
+        attachments: vec![factur_x_attachment(invoice, profile)?],
+        xmp_extensions: vec![factur_x_xmp_extension(profile)]
+    })
+}
+
+pub fn factur_x_attachment(invoice: &Invoice, profile: FacturXProfile) -> Result<PdfAttachment, Error> {
+    Ok(PdfAttachment {
+        file_name: FACTUR_X_FILE_NAME.to_owned(),
+        mime_type: "text/xml".to_owned(),
+        description: format!("Factur-X invoice {}", invoice.invoice_number),
+        relationship: AttachmentRelationship::Alternative,
+        content: cii_xml(invoice, profile.cii_profile())?.into_bytes()
+    })
+}
+
+pub fn factur_x_xmp_extension(profile: FacturXProfile) -> XmpExtension {
+    let property = |name: &str, description: &str, value: &str| XmpProperty {
+        name: name.to_owned(),
+        description: description.to_owned(),
+        value: value.to_owned()
+    };
+    XmpExtension {
+        schema: "Factur-X PDFA Extension Schema".to_owned(),
+        namespace_uri: FACTUR_X_NAMESPACE.to_owned(),
+        prefix: "fx".to_owned(),
+        properties: vec![
+            property("DocumentFileName", "The name of the embedded XML document", FACTUR_X_FILE_NAME),
+            property("DocumentType", "The type of the hybrid document in capital letters, e.g. INVOICE or ORDER", "INVOICE"),
+            property("Version", "The actual version of the standard applying to the embedded XML document", "1.0"),
+            property("ConformanceLevel", "The conformance level of the embedded XML document", profile.conformance_level())
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::INVOICE;
+    use crate::invoice::parse_invoice_json;
+    use crate::pdf::save_pdf_with_options;
+    use lopdf::Document;
+    use printpdf::*;
+
+    #[test]
+    fn should_embed_cii_invoice_into_pdf_a3() {
+        let invoice = parse_invoice_json(INVOICE).unwrap();
+        let mut doc = PdfDocument::new("Invoice 2025-0001");
+        doc.with_pages(vec![PdfPage::new(Mm(210.0), Mm(297.0), Vec::new())]);
+        let options = factur_x_output_options(&invoice, FacturXProfile::En16931).unwrap();
+        let pdf = Document::load_mem(&save_pdf_with_options(&doc, &options).unwrap()).unwrap();
+        let catalog = pdf.catalog().unwrap();
+        let file_spec_id = catalog.get(b"AF").unwrap().as_array().unwrap()[0].as_reference().unwrap();
+        let file_spec = pdf.get_dictionary(file_spec_id).unwrap();
+        assert_eq!(file_spec.get(b"F").unwrap().as_str().unwrap(), b"factur-x.xml");
+        let file_id = file_spec.get(b"EF").unwrap().as_dict().unwrap().get(b"F").unwrap().as_reference().unwrap();
+        let xml = String::from_utf8(pdf.get_object(file_id).unwrap().as_stream().unwrap().content.clone()).unwrap();
+        assert!(xml.contains("<ram:ID>urn:cen.eu:en16931:2017</ram:ID>"));
+        let metadata_id = catalog.get(b"Metadata").unwrap().as_reference().unwrap();
+        let metadata = String::from_utf8(pdf.get_object(metadata_id).unwrap().as_stream().unwrap().content.clone()).unwrap();
+        assert!(metadata.contains("<fx:ConformanceLevel>EN 16931</fx:ConformanceLevel>"));
+        assert!(metadata.contains("<fx:DocumentFileName>factur-x.xml</fx:DocumentFileName>"));
+    }
+}
@@ -0,0 +1,281 @@
+use anyhow::Error;
+use bigdecimal::{BigDecimal, Signed};
+use quick_xml::events::BytesText;
+use std::io;
+use crate::date::Date;
+use crate::export::{amount_text, decimal_text, optional_text_element, text_element, text_element_with_attributes, xml_document, XmlWriter};
+use crate::invoice::{BillingInformation, Invoice};
+use crate::payment::iban::validate_iban;
+use crate::payment::reference::{is_rf_reference, validate_finnish_reference, validate_rf_reference};
+
+const MAX_EPI_NAME_LENGTH: usize = 35;
+
+/// Invoice data in the form written to a Finvoice 3.0 document
+struct FinvoiceData<'a> {
+    invoice: &'a Invoice,
+    billed_at: Date,
+    due_date: Date,
+    iban: String,
+    reference: Option<String>
+}
+
+/// Maps the invoice to a Finvoice 3.0 XML document, typed as a credit note when the total price is negative
+pub fn finvoice_xml(invoice: &Invoice) -> Result<String, Error> {
+    let reference = match invoice.reference_id.as_ref() {
+        Some(reference) if is_rf_reference(reference) => Some(validate_rf_reference(reference)?),
+        Some(reference) => Some(validate_finnish_reference(reference)?),
+        None => None
+    };
+    let data = FinvoiceData {
+        invoice,
+        billed_at: Date::parse(&invoice.billed_at)?,
+        due_date: Date::parse(&invoice.due_date)?,
+        iban: validate_iban(&invoice.bank_details.account_number)?,
+        reference
+    };
+    xml_document(|writer| {
+        writer.create_element("Finvoice")
+            .with_attributes([
+                ("Version", "3.0"),
+                ("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance"),
+                ("xsi:noNamespaceSchemaLocation", "Finvoice3.0.xsd")
+            ])
+            .write_inner_content(|writer| write_finvoice(writer, &data))?;
+        Ok(())
+    })
+}
+
+fn write_finvoice(writer: &mut XmlWriter, data: &FinvoiceData) -> io::Result<()> {
+    let invoice = data.invoice;
+    write_party_details(writer, "Seller", &invoice.billed_by)?;
+    write_communication_details(writer, "Seller", &invoice.billed_by)?;
+    writer.create_element("SellerInformationDetails").write_inner_content(|writer| {
+        optional_text_element(writer, "SellerWebaddressIdentifier", invoice.billed_by.website.as_deref())?;
+        writer.create_element("SellerAccountDetails").write_inner_content(|writer| {
+            text_element_with_attributes(writer, "SellerAccountID", &[("IdentificationSchemeName", "IBAN")], &data.iban)?;
+            text_element_with_attributes(writer, "SellerBic", &[("IdentificationSchemeName", "BIC")], &invoice.bank_details.bic_code)
+        })?;
+        Ok(())
+    })?;
+    write_party_details(writer, "Buyer", &invoice.billed_to)?;
+    write_communication_details(writer, "Buyer", &invoice.billed_to)?;
+    write_invoice_details(writer, data)?;
+    write_invoice_rows(writer, invoice)?;
+    write_epi_details(writer, data)
+}
+
+/// Writes `SellerPartyDetails` or `BuyerPartyDetails`, their structure only differs by the prefix
+fn write_party_details(writer: &mut XmlWriter, prefix: &str, party: &BillingInformation) -> io::Result<()> {
+    writer.create_element(format!("{}PartyDetails", prefix)).write_inner_content(|writer| {
+        optional_text_element(writer, &format!("{}PartyIdentifier", prefix), party.company_id.as_deref())?;
+        text_element(writer, &format!("{}OrganisationName", prefix), &party.name)?;
+        optional_text_element(writer, &format!("{}OrganisationTaxCode", prefix), party.vat_id.as_deref())?;
+        if let (Some(postal_code), Some(town)) = (party.postal_code.as_ref(), party.town.as_ref()) {
+            writer.create_element(format!("{}PostalAddressDetails", prefix)).write_inner_content(|writer| {
+                text_element(writer, &format!("{}StreetName", prefix), &party.address_line_1)?;
+                text_element(writer, &format!("{}TownName", prefix), town)?;
+                text_element(writer, &format!("{}PostCodeIdentifier", prefix), postal_code)?;
+                optional_text_element(writer, "CountryCode", party.country_code.as_deref())
+            })?;
+        }
+        Ok(())
+    })?;
+    Ok(())
+}
+
+fn write_communication_details(writer: &mut XmlWriter, prefix: &str, party: &BillingInformation) -> io::Result<()> {
+    if party.phone_number.is_none() && party.email.is_none() {
+        return Ok(());
+    }
+    writer.create_element(format!("{}CommunicationDetails", prefix)).write_inner_content(|writer| {
+        optional_text_element(writer, &format!("{}PhoneNumberIdentifier", prefix), party.phone_number.as_deref())?;
+        optional_text_element(writer, &format!("{}EmailaddressIdentifier", prefix), party.email.as_deref())
+    })?;
+    Ok(())
+}
+
+fn write_invoice_details(writer: &mut XmlWriter, data: &FinvoiceData) -> io::Result<()> {
+    let invoice = data.invoice;
+    let totals = invoice.totals();
+    let currency = invoice.currency.as_str();
+    writer.create_element("InvoiceDetails").write_inner_content(|writer| {
+        let (type_code, type_code_un, type_text) = if totals.total_price.is_negative() {
+            ("INV02", "381", "HYVITYSLASKU")
+        } else {
+            ("INV01", "380", "LASKU")
+        };
+        text_element(writer, "InvoiceTypeCode", type_code)?;
+        text_element(writer, "InvoiceTypeCodeUN", type_code_un)?;
+        text_element(writer, "InvoiceTypeText", type_text)?;
+        text_element(writer, "OriginCode", "Original")?;
+        text_element(writer, "InvoiceNumber", &invoice.invoice_number)?;
+        date_element(writer, "InvoiceDate", &data.billed_at)?;
+        amount_element(writer, "InvoiceTotalVatExcludedAmount", &totals.total_price_without_vat, currency)?;
+        amount_element(writer, "InvoiceTotalVatAmount", &totals.total_vat, currency)?;
+        amount_element(writer, "InvoiceTotalVatIncludedAmount", &totals.total_price, currency)?;
+        writer.create_element("VatSpecificationDetails").write_inner_content(|writer| {
+            amount_element(writer, "VatBaseAmount", &totals.total_price_without_vat, currency)?;
+            text_element(writer, "VatRatePercent", &percentage(&invoice.vat_rate()))?;
+            text_element(writer, "VatCode", invoice.vat_category().code())?;
+            amount_element(writer, "VatRateAmount", &totals.total_vat, currency)?;
+            optional_text_element(writer, "VatFreeText", invoice.vat_exemption_reason.as_deref())
+        })?;
+        optional_text_element(writer, "InvoiceFreeText", invoice.invoice_description.as_deref())?;
+        optional_text_element(writer, "InvoiceFreeText", invoice.note.as_deref())?;
+        writer.create_element("PaymentTermsDetails").write_inner_content(|writer| {
+            date_element(writer, "InvoiceDueDate", &data.due_date)
+        })?;
+        Ok(())
+    })?;
+    Ok(())
+}
+
+fn write_invoice_rows(writer: &mut XmlWriter, invoice: &Invoice) -> io::Result<()> {
+    let currency = invoice.currency.as_str();
+    let vat_rate = percentage(&invoice.vat_rate());
+    let line_prices_without_vat = invoice.totals().line_prices_without_vat;
+    for (line, price_without_vat) in invoice.invoice_lines.iter().zip(line_prices_without_vat.iter()) {
+        writer.create_element("InvoiceRow").write_inner_content(|writer| {
+            text_element(writer, "ArticleName", &line.name)?;
+            text_element(writer, "DeliveredQuantity", &line.count.to_string())?;
+            if line.count > 0 {
+                let unit_price = price_without_vat / BigDecimal::from(line.count as u64);
+                amount_element(writer, "UnitPriceAmount", &unit_price, currency)?;
+            }
+            text_element(writer, "RowVatRatePercent", &vat_rate)?;
+            text_element(writer, "RowVatCode", invoice.vat_category().code())?;
+            amount_element(writer, "RowVatAmount", &(&line.price - price_without_vat), currency)?;
+            amount_element(writer, "RowVatExcludedAmount", price_without_vat, currency)?;
+            amount_element(writer, "RowAmount", &line.price, currency)
+        })?;
+    }
+    Ok(())
+}
+
+/// Payment details used by the banks ("EPI", the EACT Payment Instruction)
+fn write_epi_details(writer: &mut XmlWriter, data: &FinvoiceData) -> io::Result<()> {
+    let invoice = data.invoice;
+    writer.create_element("EpiDetails").write_inner_content(|writer| {
+        writer.create_element("EpiIdentificationDetails").write_inner_content(|writer| {
+            date_element(writer, "EpiDate", &data.billed_at)?;
+            writer.create_element("EpiReference").write_empty()?;
+            Ok(())
+        })?;
+        writer.create_element("EpiPartyDetails").write_inner_content(|writer| {
+            writer.create_element("EpiBfiPartyDetails").write_inner_content(|writer| {
+                text_element_with_attributes(writer, "EpiBfiIdentifier", &[("IdentificationSchemeName", "BIC")], &invoice.bank_details.bic_code)
+            })?;
+            writer.create_element("EpiBeneficiaryPartyDetails").write_inner_content(|writer| {
+                let beneficiary_name: String = invoice.billed_by.name.chars().take(MAX_EPI_NAME_LENGTH).collect();
+                text_element(writer, "EpiNameAddressDetails", &beneficiary_name)?;
+                text_element_with_attributes(writer, "EpiAccountID", &[("IdentificationSchemeName", "IBAN")], &data.iban)
+            })?;
+            Ok(())
+        })?;
+        writer.create_element("EpiPaymentInstructionDetails").write_inner_content(|writer| {
+            if let Some(reference) = data.reference.as_ref() {
+                let scheme = if is_rf_reference(reference) { "ISO" } else { "SPY" };
+                text_element_with_attributes(writer, "EpiRemittanceInfoIdentifier", &[("IdentificationSchemeName", scheme)], reference)?;
+            }
+            amount_element(writer, "EpiInstructedAmount", &invoice.totals().total_price, &invoice.currency)?;
+            text_element_with_attributes(writer, "EpiCharge", &[("ChargeOption", "SLEV")], "SLEV")?;
+            date_element(writer, "EpiDateOptionDate", &data.due_date)
+        })?;
+        Ok(())
+    })?;
+    Ok(())
+}
+
+fn date_element(writer: &mut XmlWriter, name: &str, date: &Date) -> io::Result<()> {
+    text_element_with_attributes(writer, name, &[("Format", "CCYYMMDD")], &date.to_ccyymmdd())
+}
+
+fn amount_element(writer: &mut XmlWriter, name: &str, amount: &BigDecimal, currency: &str) -> io::Result<()> {
+    writer.create_element(name)
+        .with_attribute(("AmountCurrencyIdentifier", currency))
+        .write_text_content(BytesText::new(&finvoice_amount(amount)))?;
+    Ok(())
+}
+
+/// Finvoice amounts use a decimal comma, e.g. "1523,90"
+fn finvoice_amount(amount: &BigDecimal) -> String {
+    amount_text(amount).replace('.', ",")
+}
+
+fn percentage(value: &BigDecimal) -> String {
+    decimal_text(value).replace('.', ",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::invoice::parse_invoice_json;
+    use crate::test_fixtures::{invoice_with, INVOICE};
+    use std::path::Path;
+    use std::process::Command;
+    use std::str::FromStr;
+
+    #[test]
+    fn should_map_invoice_to_finvoice() {
+        let invoice = invoice_with(json!({
+            "billed_to": { "name": "Client & Partners Oy" },
+            "reference_id": "86851 62596 19897",
+            "invoice_lines": [
+                { "name": "Consulting", "count": 2, "price": 251 },
+                { "name": "Travel", "count": 1, "price": 100.40 }
+            ]
+        }));
+        let xml = finvoice_xml(&invoice).unwrap();
+        assert!(xml.contains("<SellerOrganisationName>Example Company Ltd</SellerOrganisationName>"));
+        assert!(xml.contains("<BuyerOrganisationName>Client &amp; Partners Oy</BuyerOrganisationName>"));
+        assert!(xml.contains("<InvoiceDate Format=\"CCYYMMDD\">20250204</InvoiceDate>"));
+        assert!(xml.contains("<InvoiceTotalVatExcludedAmount AmountCurrencyIdentifier=\"EUR\">280,00</InvoiceTotalVatExcludedAmount>"));
+        assert!(xml.contains("<InvoiceTotalVatAmount AmountCurrencyIdentifier=\"EUR\">71,40</InvoiceTotalVatAmount>"));
+        assert!(xml.contains("<VatRatePercent>25,5</VatRatePercent>"));
+        assert!(xml.contains("<VatCode>S</VatCode>"));
+        assert!(xml.contains("<UnitPriceAmount AmountCurrencyIdentifier=\"EUR\">100,00</UnitPriceAmount>"));
+        assert!(xml.contains("<EpiAccountID IdentificationSchemeName=\"IBAN\">FI2112345600000785</EpiAccountID>"));
+        assert!(xml.contains("<EpiRemittanceInfoIdentifier IdentificationSchemeName=\"SPY\">868516259619897</EpiRemittanceInfoIdentifier>"));
+    }
+
+    #[test]
+    fn should_write_credit_note_for_negative_total() {
+        let invoice = invoice_with(json!({ "invoice_lines": [{ "name": "Discount", "count": 1, "price": -500 }] }));
+        let xml = finvoice_xml(&invoice).unwrap();
+        assert!(xml.contains("<InvoiceTypeCode>INV02</InvoiceTypeCode>"));
+        assert!(xml.contains("<InvoiceTypeCodeUN>381</InvoiceTypeCodeUN>"));
+        assert!(xml.contains("<InvoiceTypeText>HYVITYSLASKU</InvoiceTypeText>"));
+        assert!(xml.contains("<InvoiceTotalVatIncludedAmount AmountCurrencyIdentifier=\"EUR\">-500,00</InvoiceTotalVatIncludedAmount>"));
+    }
+
+    #[test]
+    fn should_reject_invalid_references() {
+        let invoice = invoice_with(json!({ "reference_id": "86851 62596 19898" }));
+        assert!(finvoice_xml(&invoice).is_err());
+    }
+
+    #[test]
+    fn should_format_amounts_and_percentages() {
+        assert_eq!(finvoice_amount(&BigDecimal::from_str("1523.9").unwrap()), "1523,90");
+        assert_eq!(finvoice_amount(&BigDecimal::from(7500)), "7500,00");
+        assert_eq!(percentage(&BigDecimal::from_str("24").unwrap()), "24");
+        assert_eq!(percentage(&BigDecimal::from_str("25.50").unwrap()), "25,5");
+    }
+
+    #[test]
+    #[ignore = "the official schema is not vendored yet, save it as schemas/finvoice/Finvoice3.0.xsd, see schemas/finvoice/README.md"]
+    fn should_conform_to_finvoice_schema() {
+        let schema_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas/finvoice/Finvoice3.0.xsd");
+        assert!(schema_path.exists(), "Finvoice 3.0 schema is missing: {}", schema_path.display());
+        if Command::new("xmllint").arg("--version").output().is_err() {
+            eprintln!("Skipping Finvoice schema validation, xmllint is not installed");
+            return;
+        }
+        let xml = finvoice_xml(&parse_invoice_json(INVOICE).unwrap()).unwrap();
+        let xml_path = std::env::temp_dir().join("invoicely-finvoice-test.xml");
+        std::fs::write(&xml_path, xml).unwrap();
+        let output = Command::new("xmllint").arg("--noout").arg("--schema").arg(&schema_path).arg(&xml_path).output().unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    }
+}
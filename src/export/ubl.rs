@@ -0,0 +1,286 @@
+use anyhow::Error;
+use bigdecimal::BigDecimal;
+use quick_xml::events::BytesText;
+use std::io;
+use crate::export::en16931::{En16931Invoice, Endpoint};
+use crate::export::validation::{validate_en16931, ValidationErrors};
+use crate::export::{amount_text, decimal_text, optional_text_element, text_element, text_element_with_attributes, xml_document, XmlWriter};
+use crate::invoice::{BillingInformation, Invoice};
+
+const CAC_NAMESPACE: &str = "urn:oasis:names:specification:ubl:schema:xsd:CommonAggregateComponents-2";
+const CBC_NAMESPACE: &str = "urn:oasis:names:specification:ubl:schema:xsd:CommonBasicComponents-2";
+
+/// Identifiers of the specification (BT-24) and the business process (BT-23) the document follows
+pub struct UblProfile {
+    pub customization_id: &'static str,
+    pub profile_id: &'static str
+}
+
+pub const PEPPOL_BIS_BILLING: UblProfile = UblProfile {
+    customization_id: "urn:cen.eu:en16931:2017#compliant#urn:fdc:peppol.eu:2017:poacc:billing:3.0",
+    profile_id: "urn:fdc:peppol.eu:2017:poacc:billing:01:1.0"
+};
+
+/// Maps the invoice to a PEPPOL BIS Billing 3.0 UBL Invoice, or to a CreditNote when the total price is negative
+pub fn peppol_xml(invoice: &Invoice) -> Result<String, Error> {
+    ubl_xml(invoice, &PEPPOL_BIS_BILLING)
+}
+
+pub fn ubl_xml(invoice: &Invoice, profile: &UblProfile) -> Result<String, Error> {
+    ValidationErrors::check(validate_en16931(invoice))?;
+    let data = UblData {
+        document: En16931Invoice::new(invoice)?,
+        profile,
+        buyer_reference: invoice.buyer_reference.as_deref()
+            .ok_or_else(|| Error::msg("Buyer reference is required, e.g. a purchase order number"))?,
+        seller_endpoint: Endpoint::of_party(&invoice.billed_by)?,
+        buyer_endpoint: Endpoint::of_party(&invoice.billed_to)?
+    };
+    let root = if data.document.credit_note { "CreditNote" } else { "Invoice" };
+    let namespace = format!("urn:oasis:names:specification:ubl:schema:xsd:{}-2", root);
+    xml_document(|writer| {
+        writer.create_element(root)
+            .with_attributes([
+                ("xmlns", namespace.as_str()),
+                ("xmlns:cac", CAC_NAMESPACE),
+                ("xmlns:cbc", CBC_NAMESPACE)
+            ])
+            .write_inner_content(|writer| write_document(writer, &data))?;
+        Ok(())
+    })
+}
+
+struct UblData<'a> {
+    document: En16931Invoice<'a>,
+    profile: &'a UblProfile,
+    buyer_reference: &'a str,
+    seller_endpoint: Endpoint,
+    buyer_endpoint: Endpoint
+}
+
+fn write_document(writer: &mut XmlWriter, data: &UblData) -> io::Result<()> {
+    let document = &data.document;
+    let invoice = document.invoice;
+    text_element(writer, "cbc:CustomizationID", data.profile.customization_id)?;
+    text_element(writer, "cbc:ProfileID", data.profile.profile_id)?;
+    text_element(writer, "cbc:ID", &invoice.invoice_number)?;
+    text_element(writer, "cbc:IssueDate", &document.issue_date.to_iso())?;
+    if document.credit_note {
+        text_element(writer, "cbc:CreditNoteTypeCode", document.type_code())?;
+    } else {
+        text_element(writer, "cbc:DueDate", &document.due_date.to_iso())?;
+        text_element(writer, "cbc:InvoiceTypeCode", document.type_code())?;
+    }
+    optional_text_element(writer, "cbc:Note", invoice.note.as_deref())?;
+    text_element(writer, "cbc:DocumentCurrencyCode", &invoice.currency)?;
+    text_element(writer, "cbc:BuyerReference", data.buyer_reference)?;
+    writer.create_element("cac:AccountingSupplierParty").write_inner_content(|writer| {
+        write_party(writer, &invoice.billed_by, &data.seller_endpoint)
+    })?;
+    writer.create_element("cac:AccountingCustomerParty").write_inner_content(|writer| {
+        write_party(writer, &invoice.billed_to, &data.buyer_endpoint)
+    })?;
+    write_payment_means(writer, document)?;
+    write_tax_total(writer, document)?;
+    write_monetary_total(writer, document)?;
+    write_lines(writer, document)
+}
+
+fn write_party(writer: &mut XmlWriter, party: &BillingInformation, endpoint: &Endpoint) -> io::Result<()> {
+    writer.create_element("cac:Party").write_inner_content(|writer| {
+        text_element_with_attributes(writer, "cbc:EndpointID", &[("schemeID", &endpoint.scheme)], &endpoint.identifier)?;
+        writer.create_element("cac:PartyName").write_inner_content(|writer| {
+            text_element(writer, "cbc:Name", &party.name)
+        })?;
+        writer.create_element("cac:PostalAddress").write_inner_content(|writer| {
+            text_element(writer, "cbc:StreetName", &party.address_line_1)?;
+            optional_text_element(writer, "cbc:CityName", party.town.as_deref())?;
+            optional_text_element(writer, "cbc:PostalZone", party.postal_code.as_deref())?;
+            writer.create_element("cac:Country").write_inner_content(|writer| {
+                text_element(writer, "cbc:IdentificationCode", party.country_code.as_deref().unwrap_or_default())
+            })?;
+            Ok(())
+        })?;
+        if let Some(vat_id) = party.vat_id.as_ref() {
+            writer.create_element("cac:PartyTaxScheme").write_inner_content(|writer| {
+                text_element(writer, "cbc:CompanyID", vat_id)?;
+                write_vat_tax_scheme(writer)
+            })?;
+        }
+        writer.create_element("cac:PartyLegalEntity").write_inner_content(|writer| {
+            text_element(writer, "cbc:RegistrationName", &party.name)?;
+            optional_text_element(writer, "cbc:CompanyID", party.company_id.as_deref())
+        })?;
+        if party.contact_name.is_some() || party.phone_number.is_some() || party.email.is_some() {
+            writer.create_element("cac:Contact").write_inner_content(|writer| {
+                optional_text_element(writer, "cbc:Name", party.contact_name.as_deref())?;
+                optional_text_element(writer, "cbc:Telephone", party.phone_number.as_deref())?;
+                optional_text_element(writer, "cbc:ElectronicMail", party.email.as_deref())
+            })?;
+        }
+        Ok(())
+    })?;
+    Ok(())
+}
+
+fn write_payment_means(writer: &mut XmlWriter, document: &En16931Invoice) -> io::Result<()> {
+    let bic = document.invoice.bank_details.bic_code.trim();
+    writer.create_element("cac:PaymentMeans").write_inner_content(|writer| {
+        text_element(writer, "cbc:PaymentMeansCode", document.payment_means_code())?;
+        if document.credit_note {
+            text_element(writer, "cbc:PaymentDueDate", &document.due_date.to_iso())?;
+        }
+        optional_text_element(writer, "cbc:PaymentID", document.payment_reference.as_deref())?;
+        writer.create_element("cac:PayeeFinancialAccount").write_inner_content(|writer| {
+            text_element(writer, "cbc:ID", &document.iban)?;
+            if !bic.is_empty() {
+                writer.create_element("cac:FinancialInstitutionBranch").write_inner_content(|writer| {
+                    text_element(writer, "cbc:ID", bic)
+                })?;
+            }
+            Ok(())
+        })?;
+        Ok(())
+    })?;
+    Ok(())
+}
+
+fn write_tax_total(writer: &mut XmlWriter, document: &En16931Invoice) -> io::Result<()> {
+    let currency = document.invoice.currency.as_str();
+    writer.create_element("cac:TaxTotal").write_inner_content(|writer| {
+        amount_element(writer, "cbc:TaxAmount", &document.total_vat, currency)?;
+        writer.create_element("cac:TaxSubtotal").write_inner_content(|writer| {
+            amount_element(writer, "cbc:TaxableAmount", &document.total_price_without_vat, currency)?;
+            amount_element(writer, "cbc:TaxAmount", &document.total_vat, currency)?;
+            writer.create_element("cac:TaxCategory").write_inner_content(|writer| {
+                write_tax_category_content(writer, document)?;
+                if document.vat_category.requires_exemption_reason() {
+                    optional_text_element(writer, "cbc:TaxExemptionReasonCode", document.vat_category.exemption_reason_code())?;
+                    optional_text_element(writer, "cbc:TaxExemptionReason", document.invoice.vat_exemption_reason.as_deref())?;
+                }
+                write_vat_tax_scheme(writer)
+            })?;
+            Ok(())
+        })?;
+        Ok(())
+    })?;
+    Ok(())
+}
+
+fn write_monetary_total(writer: &mut XmlWriter, document: &En16931Invoice) -> io::Result<()> {
+    let currency = document.invoice.currency.as_str();
+    writer.create_element("cac:LegalMonetaryTotal").write_inner_content(|writer| {
+        amount_element(writer, "cbc:LineExtensionAmount", &document.total_price_without_vat, currency)?;
+        amount_element(writer, "cbc:TaxExclusiveAmount", &document.total_price_without_vat, currency)?;
+        amount_element(writer, "cbc:TaxInclusiveAmount", &document.total_price, currency)?;
+        amount_element(writer, "cbc:PayableAmount", &document.total_price, currency)
+    })?;
+    Ok(())
+}
+
+fn write_lines(writer: &mut XmlWriter, document: &En16931Invoice) -> io::Result<()> {
+    let currency = document.invoice.currency.as_str();
+    let (line_element, quantity_element) = if document.credit_note {
+        ("cac:CreditNoteLine", "cbc:CreditedQuantity")
+    } else {
+        ("cac:InvoiceLine", "cbc:InvoicedQuantity")
+    };
+    for (index, line) in document.lines.iter().enumerate() {
+        writer.create_element(line_element).write_inner_content(|writer| {
+            text_element(writer, "cbc:ID", &(index + 1).to_string())?;
+            text_element_with_attributes(writer, quantity_element, &[("unitCode", "C62")], &decimal_text(&line.quantity))?;
+            amount_element(writer, "cbc:LineExtensionAmount", &line.amount, currency)?;
+            writer.create_element("cac:Item").write_inner_content(|writer| {
+                text_element(writer, "cbc:Name", line.name)?;
+                writer.create_element("cac:ClassifiedTaxCategory").write_inner_content(|writer| {
+                    write_tax_category_content(writer, document)?;
+                    write_vat_tax_scheme(writer)
+                })?;
+                Ok(())
+            })?;
+            writer.create_element("cac:Price").write_inner_content(|writer| {
+                writer.create_element("cbc:PriceAmount")
+                    .with_attribute(("currencyID", currency))
+                    .write_text_content(BytesText::new(&decimal_text(&line.unit_price)))?;
+                Ok(())
+            })?;
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+fn write_tax_category_content(writer: &mut XmlWriter, document: &En16931Invoice) -> io::Result<()> {
+    text_element(writer, "cbc:ID", document.vat_category.code())?;
+    if document.has_vat_rate() {
+        text_element(writer, "cbc:Percent", &decimal_text(&document.vat_rate))?;
+    }
+    Ok(())
+}
+
+fn write_vat_tax_scheme(writer: &mut XmlWriter) -> io::Result<()> {
+    writer.create_element("cac:TaxScheme").write_inner_content(|writer| {
+        text_element(writer, "cbc:ID", "VAT")
+    })?;
+    Ok(())
+}
+
+fn amount_element(writer: &mut XmlWriter, name: &str, amount: &BigDecimal, currency: &str) -> io::Result<()> {
+    writer.create_element(name)
+        .with_attribute(("currencyID", currency))
+        .write_text_content(BytesText::new(&amount_text(amount)))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::test_fixtures::invoice_with;
+
+    #[test]
+    fn should_map_invoice_to_peppol_ubl() {
+        let invoice = invoice_with(json!({
+            "vat_percent": 0,
+            "vat_category": "AE",
+            "invoice_lines": [
+                { "name": "Consulting", "count": 4, "price": 7500 },
+                { "name": "Discount", "count": 1, "price": -500 }
+            ]
+        }));
+        let xml = peppol_xml(&invoice).unwrap();
+        assert!(xml.contains("<Invoice xmlns=\"urn:oasis:names:specification:ubl:schema:xsd:Invoice-2\""));
+        assert!(xml.contains("<cbc:CustomizationID>urn:cen.eu:en16931:2017#compliant#urn:fdc:peppol.eu:2017:poacc:billing:3.0</cbc:CustomizationID>"));
+        assert!(xml.contains("<cbc:DueDate>2025-02-28</cbc:DueDate>"));
+        assert!(xml.contains("<cbc:EndpointID schemeID=\"0216\">003712345678</cbc:EndpointID>"));
+        assert!(xml.contains("<cbc:EndpointID schemeID=\"0204\">991-12345-67</cbc:EndpointID>"));
+        assert!(xml.contains("<cbc:TaxExemptionReasonCode>VATEX-EU-AE</cbc:TaxExemptionReasonCode>"));
+        assert!(xml.contains("<cbc:PayableAmount currencyID=\"EUR\">7000.00</cbc:PayableAmount>"));
+        assert!(xml.contains("<cbc:InvoicedQuantity unitCode=\"C62\">4</cbc:InvoicedQuantity>"));
+        assert!(xml.contains("<cbc:PriceAmount currencyID=\"EUR\">1875</cbc:PriceAmount>"));
+        assert!(xml.contains("<cbc:InvoicedQuantity unitCode=\"C62\">-1</cbc:InvoicedQuantity>"));
+        assert!(xml.contains("<cbc:LineExtensionAmount currencyID=\"EUR\">-500.00</cbc:LineExtensionAmount>"));
+    }
+
+    #[test]
+    fn should_write_credit_note_for_negative_total() {
+        let invoice = invoice_with(json!({ "vat_percent": 24, "invoice_lines": [{ "name": "Discount", "count": 1, "price": -500 }] }));
+        let xml = peppol_xml(&invoice).unwrap();
+        assert!(xml.contains("<CreditNote xmlns=\"urn:oasis:names:specification:ubl:schema:xsd:CreditNote-2\""));
+        assert!(xml.contains("<cbc:CreditNoteTypeCode>381</cbc:CreditNoteTypeCode>"));
+        assert!(xml.contains("<cbc:CreditedQuantity unitCode=\"C62\">1</cbc:CreditedQuantity>"));
+        assert!(xml.contains("<cbc:TaxAmount currencyID=\"EUR\">96.77</cbc:TaxAmount>"));
+        assert!(xml.contains("<cbc:PayableAmount currencyID=\"EUR\">500.00</cbc:PayableAmount>"));
+        assert!(xml.contains("<cbc:Percent>24</cbc:Percent>"));
+        assert!(!xml.contains("<cbc:DueDate>"));
+    }
+
+    #[test]
+    fn should_require_buyer_reference_and_endpoints() {
+        let invoice = invoice_with(json!({ "buyer_reference": null }));
+        assert!(peppol_xml(&invoice).is_err());
+
+        let invoice = invoice_with(json!({ "billed_to": { "endpoint_id": null } }));
+        assert!(peppol_xml(&invoice).is_err());
+    }
+}
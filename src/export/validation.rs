@@ -0,0 +1,235 @@
+use bigdecimal::{Signed, Zero};
+use std::fmt;
+use crate::date::Date;
+use crate::invoice::{BillingInformation, Invoice, VatCategory};
+use crate::payment::iban::validate_iban;
+
+/// A business rule the invoice breaks, e.g. rule "BR-DE-15" with message "Buyer reference (Leitweg-ID) is missing"
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleViolation {
+    pub rule: String,
+    pub message: String
+}
+
+impl RuleViolation {
+    pub fn new(rule: &str, message: impl Into<String>) -> RuleViolation {
+        RuleViolation {
+            rule: rule.to_owned(),
+            message: message.into()
+        }
+    }
+}
+
+impl fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.rule, self.message)
+    }
+}
+
+/// All the rule violations found in an invoice, returned inside `anyhow::Error` so that callers
+/// can get the list back with `error.downcast_ref::<ValidationErrors>()`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationErrors {
+    pub violations: Vec<RuleViolation>
+}
+
+impl ValidationErrors {
+    /// `Ok` when there are no violations
+    pub fn check(violations: Vec<RuleViolation>) -> Result<(), ValidationErrors> {
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationErrors { violations })
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invoice breaks {} business rule(s):", self.violations.len())?;
+        for violation in &self.violations {
+            write!(f, "\n  {}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+/// Checks the core EN 16931 business rules (BR-*, BR-CO-*, the VAT category rules and the decimal rules)
+/// over the invoice and its computed totals. Rules about data the invoice can't hold, e.g. the delivery
+/// date required for intra-community supplies (BR-IC-11), are not checked. Neither are the sums of the
+/// totals (BR-CO-10, BR-CO-15, BR-CO-17): they hold by construction, `Invoice::totals` takes the VAT as
+/// the difference of the totals with and without VAT and spreads the rounding over the line amounts.
+pub fn validate_en16931(invoice: &Invoice) -> Vec<RuleViolation> {
+    let mut violations = Vec::new();
+    let seller = &invoice.billed_by;
+    let buyer = &invoice.billed_to;
+    if invoice.invoice_number.trim().is_empty() {
+        violations.push(RuleViolation::new("BR-2", "Invoice number is missing"));
+    }
+    if let Err(e) = Date::parse(&invoice.billed_at) {
+        violations.push(RuleViolation::new("BR-3", format!("Invoice issue date is missing or invalid: {}", e)));
+    }
+    if invoice.currency.len() != 3 || !invoice.currency.chars().all(|c| c.is_ascii_uppercase()) {
+        violations.push(RuleViolation::new("BR-5", format!("Invoice currency should be an ISO 4217 code: {}", invoice.currency)));
+    }
+    check_party(&mut violations, seller, ["BR-6", "BR-8", "BR-9"]);
+    check_party(&mut violations, buyer, ["BR-7", "BR-10", "BR-11"]);
+    if invoice.invoice_lines.is_empty() {
+        violations.push(RuleViolation::new("BR-16", "Invoice has no lines"));
+    }
+    for (index, line) in invoice.invoice_lines.iter().enumerate() {
+        if line.name.trim().is_empty() {
+            violations.push(RuleViolation::new("BR-25", format!("Item name of line {} is missing", index + 1)));
+        }
+    }
+    if let Err(e) = validate_iban(&invoice.bank_details.account_number) {
+        violations.push(RuleViolation::new("BR-61", format!("Payment account identifier is required for a credit transfer: {}", e)));
+    }
+    if is_blank(&seller.vat_id) && is_blank(&seller.company_id) {
+        violations.push(RuleViolation::new("BR-CO-26", format!("VAT ID or business ID of {} is required", seller.name)));
+    }
+    for party in [seller, buyer] {
+        if let Some(vat_id) = party.vat_id.as_deref().filter(|vat_id| !vat_id.trim().is_empty()) {
+            if !vat_id.trim().chars().take(2).all(|c| c.is_ascii_uppercase()) {
+                violations.push(RuleViolation::new("BR-CO-9", format!("VAT ID of {} should start with a country code: {}", party.name, vat_id)));
+            }
+        }
+    }
+    check_vat_category(&mut violations, invoice);
+
+    let totals = invoice.totals();
+    if totals.total_price.is_positive() && Date::parse(&invoice.due_date).is_err() {
+        violations.push(RuleViolation::new("BR-CO-25", format!("Payment due date is missing or invalid: {}", invoice.due_date)));
+    }
+    if totals.total_price.normalized().fractional_digit_count() > 2 {
+        violations.push(RuleViolation::new("BR-DEC-14", format!("Total with VAT {} has more than two decimals", totals.total_price)));
+    }
+    violations
+}
+
+fn check_party(violations: &mut Vec<RuleViolation>, party: &BillingInformation, [name_rule, address_rule, country_rule]: [&str; 3]) {
+    if party.name.trim().is_empty() {
+        violations.push(RuleViolation::new(name_rule, "Party name is missing"));
+    }
+    if party.address_line_1.trim().is_empty() {
+        violations.push(RuleViolation::new(address_rule, format!("Postal address of {} is missing", party.name)));
+    }
+    if is_blank(&party.country_code) {
+        violations.push(RuleViolation::new(country_rule, format!("Country code of {} is missing", party.name)));
+    }
+}
+
+/// The rules of each VAT category are numbered alike, e.g. BR-S-2 and BR-AE-2 are about the VAT IDs
+fn check_vat_category(violations: &mut Vec<RuleViolation>, invoice: &Invoice) {
+    let category = invoice.vat_category();
+    let rule_prefix = match category {
+        VatCategory::Standard => "BR-S",
+        VatCategory::ZeroRated => "BR-Z",
+        VatCategory::Exempt => "BR-E",
+        VatCategory::ReverseCharge => "BR-AE",
+        VatCategory::IntraCommunitySupply => "BR-IC",
+        VatCategory::Export => "BR-G",
+        VatCategory::OutsideScope => "BR-O"
+    };
+    let rule = |number: u8| format!("{}-{}", rule_prefix, number);
+    let seller = &invoice.billed_by;
+    let buyer = &invoice.billed_to;
+    let code = category.code();
+
+    if category == VatCategory::OutsideScope {
+        if !is_blank(&seller.vat_id) || !is_blank(&buyer.vat_id) {
+            violations.push(RuleViolation::new(&rule(2), "VAT IDs of the parties must not be given for supplies outside the scope of VAT"));
+        }
+    } else if is_blank(&seller.vat_id) {
+        violations.push(RuleViolation::new(&rule(2), format!("VAT ID of {} is required for VAT category {}", seller.name, code)));
+    }
+    let buyer_id_missing = match category {
+        VatCategory::ReverseCharge => is_blank(&buyer.vat_id) && is_blank(&buyer.company_id),
+        VatCategory::IntraCommunitySupply => is_blank(&buyer.vat_id),
+        _ => false
+    };
+    if buyer_id_missing {
+        violations.push(RuleViolation::new(&rule(2), format!("VAT ID of {} is required for VAT category {}", buyer.name, code)));
+    }
+
+    let rate = invoice.vat_rate();
+    let rate_violation = match category {
+        VatCategory::Standard => (!rate.is_positive()).then_some("VAT rate must be greater than zero"),
+        VatCategory::OutsideScope => (!rate.is_zero()).then_some("VAT rate must not be given"),
+        _ => (!rate.is_zero()).then_some("VAT rate must be zero")
+    };
+    if let Some(message) = rate_violation {
+        violations.push(RuleViolation::new(&rule(5), format!("{} for VAT category {}", message, code)));
+    }
+
+    let has_reason = !is_blank(&invoice.vat_exemption_reason) || category.exemption_reason_code().is_some();
+    if category.requires_exemption_reason() && !has_reason {
+        violations.push(RuleViolation::new(&rule(10), format!("VAT exemption reason is required for VAT category {}", code)));
+    }
+    if !category.requires_exemption_reason() && has_reason {
+        violations.push(RuleViolation::new(&rule(10), format!("VAT exemption reason must not be given for VAT category {}", code)));
+    }
+}
+
+pub(crate) fn is_blank(value: &Option<String>) -> bool {
+    value.as_deref().is_none_or(|value| value.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+    use crate::test_fixtures::INVOICE;
+    use crate::invoice::parse_invoice_json;
+    use std::str::FromStr;
+
+    fn rules(violations: &[RuleViolation]) -> Vec<&str> {
+        violations.iter().map(|violation| violation.rule.as_str()).collect()
+    }
+
+    #[test]
+    fn should_accept_valid_invoice() {
+        assert_eq!(validate_en16931(&parse_invoice_json(INVOICE).unwrap()), Vec::new());
+    }
+
+    #[test]
+    fn should_check_required_data() {
+        let mut invoice = parse_invoice_json(INVOICE).unwrap();
+        invoice.invoice_number = String::new();
+        invoice.currency = "eur".to_owned();
+        invoice.billed_to.country_code = None;
+        invoice.billed_by.vat_id = Some("12345678".to_owned());
+        invoice.bank_details.account_number = "FI21 1234 5600 0007 86".to_owned();
+        invoice.due_date = "31.02.2025".to_owned();
+        assert_eq!(rules(&validate_en16931(&invoice)), vec!["BR-2", "BR-5", "BR-11", "BR-61", "BR-CO-9", "BR-CO-25"]);
+    }
+
+    #[test]
+    fn should_check_vat_category_rules() {
+        let mut invoice = parse_invoice_json(INVOICE).unwrap();
+        invoice.billed_by.vat_id = None;
+        assert_eq!(rules(&validate_en16931(&invoice)), vec!["BR-S-2"]);
+
+        let mut invoice = parse_invoice_json(INVOICE).unwrap();
+        invoice.vat_category = Some(VatCategory::ReverseCharge);
+        invoice.billed_to.vat_id = None;
+        assert_eq!(rules(&validate_en16931(&invoice)), vec!["BR-AE-2", "BR-AE-5"]);
+
+        let mut invoice = parse_invoice_json(INVOICE).unwrap();
+        invoice.vat_percent = 0.0;
+        invoice.vat_category = Some(VatCategory::Exempt);
+        assert_eq!(rules(&validate_en16931(&invoice)), vec!["BR-E-10"]);
+        invoice.vat_category = Some(VatCategory::ZeroRated);
+        invoice.vat_exemption_reason = Some("Exempt".to_owned());
+        assert_eq!(rules(&validate_en16931(&invoice)), vec!["BR-Z-10"]);
+    }
+
+    #[test]
+    fn should_check_decimals_of_the_total() {
+        let mut invoice = parse_invoice_json(INVOICE).unwrap();
+        invoice.invoice_lines[0].price = BigDecimal::from_str("125.505").unwrap();
+        assert_eq!(rules(&validate_en16931(&invoice)), vec!["BR-DEC-14"]);
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::Error;
+use crate::export::cii::{cii_xml, CiiProfile};
+use crate::export::en16931::Endpoint;
+use crate::export::ubl::{ubl_xml, UblProfile};
+use crate::export::validation::{is_blank, validate_en16931, RuleViolation, ValidationErrors};
+use crate::invoice::{BillingInformation, Invoice, VatCategory};
+use crate::payment::iban::mod_97;
+
+/// Specification identifier (BT-24) of XRechnung 3.0
+pub const XRECHNUNG_SPECIFICATION_ID: &str = "urn:cen.eu:en16931:2017#compliant#urn:xeinkauf.de:kosit:xrechnung_3.0";
+
+pub const XRECHNUNG_UBL: UblProfile = UblProfile {
+    customization_id: XRECHNUNG_SPECIFICATION_ID,
+    profile_id: "urn:fdc:peppol.eu:2017:poacc:billing:01:1.0"
+};
+
+pub const XRECHNUNG_CII: CiiProfile = CiiProfile {
+    guideline_id: XRECHNUNG_SPECIFICATION_ID
+};
+
+/// XRechnung can be delivered in either of the EN 16931 syntaxes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum XRechnungSyntax {
+    Ubl,
+    Cii
+}
+
+/// Maps the invoice to an XRechnung 3.0 document, failing with `ValidationErrors` listing
+/// every XRechnung business rule the invoice breaks
+pub fn xrechnung_xml(invoice: &Invoice, syntax: XRechnungSyntax) -> Result<String, Error> {
+    ValidationErrors::check(validate_xrechnung(invoice))?;
+    match syntax {
+        XRechnungSyntax::Ubl => ubl_xml(invoice, &XRECHNUNG_UBL),
+        XRechnungSyntax::Cii => cii_xml(invoice, &XRECHNUNG_CII)
+    }
+}
+
+/// Checks the EN 16931 rules and the data XRechnung requires on top of them (the BR-DE rules and
+/// the electronic addresses of the parties, which XRechnung checks with the PEPPOL rules)
+pub fn validate_xrechnung(invoice: &Invoice) -> Vec<RuleViolation> {
+    let mut violations = validate_en16931(invoice);
+    let seller = &invoice.billed_by;
+    let buyer = &invoice.billed_to;
+    match invoice.buyer_reference.as_deref().map(str::trim) {
+        None | Some("") => violations.push(RuleViolation::new("BR-DE-15", "Buyer reference (Leitweg-ID) is missing")),
+        Some(buyer_reference) => if looks_like_leitweg_id(buyer_reference) && !is_valid_leitweg_id(buyer_reference) {
+            violations.push(RuleViolation::new("BR-DE-15", format!("Leitweg-ID {} has invalid check digits", buyer_reference)));
+        }
+    }
+    if is_blank(&seller.contact_name) {
+        violations.push(RuleViolation::new("BR-DE-5", format!("Contact name of {} is missing", seller.name)));
+    }
+    if is_blank(&seller.phone_number) {
+        violations.push(RuleViolation::new("BR-DE-6", format!("Phone number of {} is missing", seller.name)));
+    }
+    if is_blank(&seller.email) {
+        violations.push(RuleViolation::new("BR-DE-7", format!("Email of {} is missing", seller.name)));
+    }
+    check_address(&mut violations, seller, "BR-DE-3", "BR-DE-4");
+    check_address(&mut violations, buyer, "BR-DE-8", "BR-DE-9");
+    if invoice.vat_category() != VatCategory::OutsideScope && is_blank(&seller.vat_id) {
+        violations.push(RuleViolation::new("BR-DE-16", format!("VAT ID of {} is missing", seller.name)));
+    }
+    for (party, rule) in [(seller, "PEPPOL-EN16931-R020"), (buyer, "PEPPOL-EN16931-R010")] {
+        if let Err(e) = Endpoint::of_party(party) {
+            violations.push(RuleViolation::new(rule, format!("Electronic address is required: {}", e)));
+        }
+    }
+    violations
+}
+
+fn check_address(violations: &mut Vec<RuleViolation>, party: &BillingInformation, town_rule: &str, postal_code_rule: &str) {
+    if is_blank(&party.town) {
+        violations.push(RuleViolation::new(town_rule, format!("Town of {} is missing", party.name)));
+    }
+    if is_blank(&party.postal_code) {
+        violations.push(RuleViolation::new(postal_code_rule, format!("Postal code of {} is missing", party.name)));
+    }
+}
+
+/// Leitweg-IDs are written as "coarse address-fine address-check digits", e.g. "991-33333TEST-33",
+/// other buyer references such as purchase order numbers are not checked
+fn looks_like_leitweg_id(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('-').collect();
+    (2..=3).contains(&parts.len())
+        && (2..=12).contains(&parts[0].len()) && parts[0].chars().all(|c| c.is_ascii_digit())
+        && parts[parts.len() - 1].len() == 2 && parts[parts.len() - 1].chars().all(|c| c.is_ascii_digit())
+}
+
+/// The check digits are calculated with ISO/IEC 7064 MOD 97-10 over the coarse and fine addresses
+fn is_valid_leitweg_id(value: &str) -> bool {
+    let (address, check_digits) = value.rsplit_once('-').unwrap_or((value, ""));
+    let address = address.replace('-', "");
+    if address.len() > 42 || !address.chars().all(|c| c.is_ascii_digit() || c.is_ascii_uppercase()) {
+        return false;
+    }
+    let expected = 98 - mod_97(&format!("{}00", address));
+    check_digits.parse::<u32>() == Ok(expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::INVOICE;
+    use crate::invoice::parse_invoice_json;
+
+    fn xrechnung_invoice() -> Invoice {
+        let mut invoice = parse_invoice_json(INVOICE).unwrap();
+        invoice.buyer_reference = Some("991-33333TEST-33".to_owned());
+        invoice.billed_by.contact_name = Some("Maija Meikäläinen".to_owned());
+        invoice.billed_by.phone_number = Some("+358 40 123 4567".to_owned());
+        invoice.billed_to.endpoint_id = Some("EM:invoices@client.de".to_owned());
+        invoice
+    }
+
+    #[test]
+    fn should_validate_leitweg_ids() {
+        assert!(is_valid_leitweg_id("991-33333TEST-33"));
+        assert!(is_valid_leitweg_id("04011000-1234512345-06"));
+        assert!(!is_valid_leitweg_id("991-33333TEST-34"));
+        assert!(looks_like_leitweg_id("991-33333TEST-33"));
+        assert!(!looks_like_leitweg_id("PO-2025-17"));
+    }
+
+    #[test]
+    fn should_write_xrechnung_in_both_syntaxes() {
+        let invoice = xrechnung_invoice();
+        let ubl = xrechnung_xml(&invoice, XRechnungSyntax::Ubl).unwrap();
+        assert!(ubl.contains("<cbc:CustomizationID>urn:cen.eu:en16931:2017#compliant#urn:xeinkauf.de:kosit:xrechnung_3.0</cbc:CustomizationID>"));
+        assert!(ubl.contains("<cbc:BuyerReference>991-33333TEST-33</cbc:BuyerReference>"));
+        assert!(ubl.contains("<cbc:Name>Maija Meikäläinen</cbc:Name>"));
+        let cii = xrechnung_xml(&invoice, XRechnungSyntax::Cii).unwrap();
+        assert!(cii.contains("<ram:ID>urn:cen.eu:en16931:2017#compliant#urn:xeinkauf.de:kosit:xrechnung_3.0</ram:ID>"));
+        assert!(cii.contains("<ram:PersonName>Maija Meikäläinen</ram:PersonName>"));
+    }
+
+    #[test]
+    fn should_list_broken_rules() {
+        let mut invoice = xrechnung_invoice();
+        invoice.buyer_reference = None;
+        invoice.billed_by.contact_name = None;
+        invoice.billed_to.town = None;
+        let error = xrechnung_xml(&invoice, XRechnungSyntax::Ubl).unwrap_err();
+        let rules: Vec<&str> = error.downcast_ref::<ValidationErrors>().unwrap().violations.iter()
+            .map(|violation| violation.rule.as_str())
+            .collect();
+        assert_eq!(rules, vec!["BR-DE-15", "BR-DE-5", "BR-DE-8"]);
+
+        invoice.buyer_reference = Some("991-33333TEST-34".to_owned());
+        let violations = validate_xrechnung(&invoice);
+        assert_eq!(violations[0].message, "Leitweg-ID 991-33333TEST-34 has invalid check digits");
+
+        let mut invoice = xrechnung_invoice();
+        invoice.billed_to.endpoint_id = None;
+        assert_eq!(validate_xrechnung(&invoice)[0].rule, "PEPPOL-EN16931-R010");
+    }
+}
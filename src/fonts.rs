@@ -1,60 +1,132 @@
 use anyhow::Error;
 use printpdf::*;
+use font_kit::family_name::FamilyName;
+use font_kit::handle::Handle;
+use font_kit::properties::{Properties, Weight};
 use font_kit::source::SystemSource;
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::drawing::{Font, FontFamily, FontWeight};
 
-pub struct FontManager {
-    regular_font: FontId,
-    bold_font: FontId,
+const MM_PER_PT: f32 = 25.4 / 72.0;
+
+/// Regular and bold system fonts of a font family, loaded once per thread and shared by the layout
+/// and the PDF backend
+pub struct SystemFonts {
+    regular: ParsedFont,
+    bold: ParsedFont
 }
 
-impl FontManager {
-    pub fn initialize(doc: &mut PdfDocument) -> Result<Self, Error> {
-        let font_names: Vec<String> = vec![
+/// Fonts loaded for a font family, `None` being the default fonts, or the error loading them
+type LoadedFonts = (Option<FontFamily>, Result<Rc<SystemFonts>, String>);
+
+thread_local! {
+    static SYSTEM_FONTS: RefCell<Vec<LoadedFonts>> = const { RefCell::new(Vec::new()) };
+}
+
+impl SystemFonts {
+    /// Fonts of the family, `None` for the first of the default fonts that is installed, loaded on
+    /// first use
+    pub fn get(family: Option<FontFamily>) -> Result<Rc<SystemFonts>, Error> {
+        SYSTEM_FONTS.with(|loaded| {
+            let mut loaded = loaded.borrow_mut();
+            if let Some((_, fonts)) = loaded.iter().find(|(loaded_family, _)| *loaded_family == family) {
+                return fonts.clone().map_err(Error::msg);
+            }
+            let fonts = match family {
+                Some(family) => Self::load_family(family.name()),
+                None => Self::load()
+            }.map(Rc::new).map_err(|e| e.to_string());
+            loaded.push((family, fonts.clone()));
+            fonts.map_err(Error::msg)
+        })
+    }
+
+    fn load_family(family: &str) -> Result<SystemFonts, Error> {
+        let source = SystemSource::new();
+        let load = |properties: &Properties| source.select_best_match(&[FamilyName::Title(family.to_owned())], properties).ok()
+            .and_then(|handle| Self::parse_font(&handle));
+        Ok(SystemFonts {
+            regular: load(&Properties::new()).ok_or_else(|| Error::msg(format!("Could not load font family: {}", family)))?,
+            bold: load(Properties::new().weight(Weight::BOLD)).ok_or_else(|| Error::msg(format!("Could not load the bold font of font family: {}", family)))?
+        })
+    }
+
+    fn load() -> Result<SystemFonts, Error> {
+        let font_names: Vec<String> = [
             "Helvetica",
             "Arial",
             "DejaVu Sans",
-            "Liberation Sans", 
+            "Liberation Sans",
             "FreeSans",
             "Ubuntu",
             "Noto Sans",
         ].iter().map(|name| name.to_string()).collect();
         let bold_font_names: Vec<String> = font_names.iter().map(|name| format!("{}-Bold", name)).collect();
-
-        let regular_font = Self::load_system_font(doc, &font_names)?;
-        let bold_font = Self::load_system_font(doc, &bold_font_names)?;
-        
-        Ok(FontManager {
-            regular_font,
-            bold_font,
+        Ok(SystemFonts {
+            regular: Self::load_system_font(&font_names)?,
+            bold: Self::load_system_font(&bold_font_names)?
         })
     }
 
-    fn load_system_font(doc: &mut PdfDocument, font_names: &[String]) -> Result<FontId, Error> {
+    fn load_system_font(font_names: &[String]) -> Result<ParsedFont, Error> {
         let source = SystemSource::new();
         for name in font_names {
             if let Ok(font) = source.select_by_postscript_name(name) {
-                if let Ok(font_data) = font.load() {
-                    if let Some(font_bytes) = font_data.copy_font_data() {
-                        let font_index = 0;
-                        let mut warnings = Vec::new();
-                        
-                        if let Some(parsed_font) = ParsedFont::from_bytes(&font_bytes, font_index, &mut warnings) {
-                            return Ok(doc.add_font(&parsed_font));
-                        }
-                    }
-                } else {
-                    println!("Could not load font: {}", name);
+                if let Some(parsed_font) = Self::parse_font(&font) {
+                    return Ok(parsed_font);
                 }
             }
         }
         Err(Error::msg(format!("Could not load any appropriate system fonts. Are fonts installed? Tried: {:?}", font_names)))
     }
 
-    pub fn regular_font(&self) -> FontId {
-        self.regular_font.clone()
+    fn parse_font(handle: &Handle) -> Option<ParsedFont> {
+        let Ok(font_data) = handle.load() else {
+            println!("Could not load font: {:?}", handle);
+            return None;
+        };
+        let font_bytes = font_data.copy_font_data()?;
+        let font_index = 0;
+        let mut warnings = Vec::new();
+        ParsedFont::from_bytes(&font_bytes, font_index, &mut warnings)
     }
 
-    pub fn bold_font(&self) -> FontId {
-        self.bold_font.clone()
+    pub fn font(&self, weight: FontWeight) -> &ParsedFont {
+        match weight {
+            FontWeight::Regular => &self.regular,
+            FontWeight::Bold => &self.bold
+        }
     }
-}
\ No newline at end of file
+}
+
+/// Width of a single line of text in mm from the advance widths of the glyphs, approximated as
+/// half of the font size per character when the system fonts cannot be loaded
+pub fn text_width(text: &str, font_size: f32, font: Font) -> f32 {
+    let Ok(fonts) = SystemFonts::get(font.family) else {
+        return text.chars().count() as f32 * font_size * 0.5 * MM_PER_PT;
+    };
+    let parsed_font = fonts.font(font.weight);
+    let units: u32 = text.chars()
+        .map(|c| parsed_font.lookup_glyph_index(c as u32).map(|glyph| parsed_font.get_horizontal_advance(glyph) as u32).unwrap_or(0))
+        .sum();
+    units as f32 / parsed_font.font_metrics.units_per_em as f32 * font_size * MM_PER_PT
+}
+
+/// Fonts added to a PDF document, every font family and weight once
+#[derive(Default)]
+pub struct FontManager {
+    fonts: Vec<(Font, FontId)>
+}
+
+impl FontManager {
+    /// Id of the font in the document, adding the font on first use
+    pub fn font_id(&mut self, doc: &mut PdfDocument, font: Font) -> Result<FontId, Error> {
+        if let Some((_, font_id)) = self.fonts.iter().find(|(added, _)| *added == font) {
+            return Ok(font_id.clone());
+        }
+        let font_id = doc.add_font(SystemFonts::get(font.family)?.font(font.weight));
+        self.fonts.push((font, font_id.clone()));
+        Ok(font_id)
+    }
+}
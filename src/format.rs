@@ -5,7 +5,11 @@ pub fn format_vat(value: &f32) -> String {
 }
 
 pub fn format_price(value: &BigDecimal, currency: &str, locale: &str) -> String {
-    let formatted = format!("{:.2} {}", value, currency);
+    format!("{} {}", format_amount(value, locale), currency)
+}
+
+pub fn format_amount(value: &BigDecimal, locale: &str) -> String {
+    let formatted = format!("{:.2}", value);
     if locale != "en-GB" {
         formatted.replace(".", ",")
     } else {
@@ -30,6 +34,12 @@ mod tests {
         assert_eq!(format_price(&BigDecimal::from_f32(1.212423).unwrap(), "EUR", "en-GB"), "1.21 EUR");
     }
 
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(&BigDecimal::from_f32(7500.0).unwrap(), "fi-FI"), "7500,00");
+        assert_eq!(format_amount(&BigDecimal::from_f32(2.3450).unwrap(), "en-GB"), "2.35");
+    }
+
     #[test]
     fn test_format_vat() {
         assert_eq!(format_vat(&25.5), "25,5");
@@ -2,7 +2,33 @@
 use anyhow::Error;
 use printpdf::*;
 
-pub(crate) fn load_image(image_path: &str) -> Result<RawImage, Error> {
+/// Image file contents with the decoded pixels, the PDF backend embeds the pixels and the SVG
+/// backend the original file
+pub struct Image {
+    pub data: Vec<u8>,
+    pub raw: RawImage
+}
+
+impl Image {
+    /// MIME type of the image file, recognized from its signature
+    pub fn mime_type(&self) -> &'static str {
+        match self.data.as_slice() {
+            [0x89, b'P', b'N', b'G', ..] => "image/png",
+            [0xFF, 0xD8, ..] => "image/jpeg",
+            [b'G', b'I', b'F', ..] => "image/gif",
+            [b'B', b'M', ..] => "image/bmp",
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+            _ => "application/octet-stream"
+        }
+    }
+
+    /// The image file as a `data:` URI, to embed it into SVG and HTML
+    pub fn to_data_uri(&self) -> String {
+        format!("data:{};base64,{}", self.mime_type(), base64(&self.data))
+    }
+}
+
+pub(crate) fn load_image(image_path: &str) -> Result<Image, Error> {
     let image_bytes = if image_path.starts_with("http://") || image_path.starts_with("https://") {
         let response = reqwest::blocking::get(image_path)
             .map_err(|e| Error::msg(format!("Failed to download image from URL: {}", e)))?;
@@ -22,5 +48,38 @@ pub(crate) fn load_image(image_path: &str) -> Result<RawImage, Error> {
     let mut warnings = Vec::new();
     let image = RawImage::decode_from_bytes(&image_bytes, &mut warnings)
         .map_err(|e| Error::msg(format!("Failed to decode image: {}", e)))?;
-    Ok(image)
-}
\ No newline at end of file
+    Ok(Image {
+        data: image_bytes,
+        raw: image
+    })
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let value = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(value >> (18 - 6 * index) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_encode_base64() {
+        assert_eq!(base64(b"Invoicely"), "SW52b2ljZWx5");
+        assert_eq!(base64(b"PNG"), "UE5H");
+        assert_eq!(base64(b"PN"), "UE4=");
+        assert_eq!(base64(b"P"), "UA==");
+    }
+}
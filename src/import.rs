@@ -0,0 +1,299 @@
+use anyhow::Error;
+use bigdecimal::{BigDecimal, RoundingMode, ToPrimitive, Zero};
+use std::str::FromStr;
+use crate::date::Date;
+use crate::import::xml_tree::Element;
+use crate::invoice::{BankDetails, BillingInformation, Invoice, InvoiceLine, VatCategory};
+
+pub mod cii;
+pub mod finvoice;
+pub mod ubl;
+mod xml_tree;
+
+/// Locale of the imported invoices, the documents don't tell in which language to render them
+const IMPORT_LOCALE: &str = "en-GB";
+
+pub struct ImportedInvoice {
+    pub invoice: Invoice,
+    /// Data of the document which the invoice couldn't hold or which had to be changed
+    pub losses: Vec<String>
+}
+
+/// Reads a UBL 2.1 Invoice or CreditNote, an UN/CEFACT Cross Industry Invoice or a Finvoice document,
+/// recognized by the root element
+pub fn import_xml(xml: &str) -> Result<ImportedInvoice, Error> {
+    let root = Element::parse(xml)?;
+    let data = match root.name.as_str() {
+        "Invoice" | "CreditNote" => ubl::read_ubl(&root)?,
+        "CrossIndustryInvoice" => cii::read_cii(&root)?,
+        "Finvoice" => finvoice::read_finvoice(&root)?,
+        name => return Err(Error::msg(format!("Unsupported XML document {}, expected a UBL, CII or Finvoice invoice", name)))
+    };
+    build_invoice(data, &root)
+}
+
+/// Invoice data read from a document by one of the format specific readers
+#[derive(Default)]
+pub(crate) struct DocumentData {
+    pub invoice_number: Option<String>,
+    pub issue_date: Option<String>,
+    pub due_date: Option<String>,
+    /// Credit note amounts are written as positive numbers and have to be negated
+    pub credit_note: bool,
+    pub currency: Option<String>,
+    pub notes: Vec<String>,
+    pub buyer_reference: Option<String>,
+    pub payment_reference: Option<String>,
+    pub iban: Option<String>,
+    pub bic: Option<String>,
+    pub seller: BillingInformation,
+    pub buyer: BillingInformation,
+    pub vat_breakdown: Vec<VatBreakdown>,
+    pub lines: Vec<DocumentLine>,
+    /// Total amount including VAT
+    pub total_price: Option<BigDecimal>
+}
+
+pub(crate) struct VatBreakdown {
+    pub category: Option<String>,
+    pub rate: Option<BigDecimal>,
+    pub exemption_reason: Option<String>
+}
+
+pub(crate) struct DocumentLine {
+    pub name: String,
+    pub quantity: Option<BigDecimal>,
+    pub amount_without_vat: Option<BigDecimal>,
+    pub amount: Option<BigDecimal>,
+    pub vat_rate: Option<BigDecimal>
+}
+
+/// Parses an amount written with a decimal point or a decimal comma
+pub(crate) fn parse_decimal(value: Option<&str>) -> Result<Option<BigDecimal>, Error> {
+    value.map(|value| {
+        let normalized: String = value.chars().filter(|c| !c.is_whitespace()).collect::<String>().replace(',', ".");
+        BigDecimal::from_str(&normalized).map_err(|_| Error::msg(format!("Invalid number: {}", value)))
+    }).transpose()
+}
+
+/// Joins the non-empty texts, e.g. street name lines
+pub(crate) fn join_texts<'a>(texts: impl IntoIterator<Item = Option<&'a str>>, separator: &str) -> Option<String> {
+    let texts: Vec<&str> = texts.into_iter().flatten().collect();
+    (!texts.is_empty()).then(|| texts.join(separator))
+}
+
+fn build_invoice(data: DocumentData, root: &Element) -> Result<ImportedInvoice, Error> {
+    let mut losses = Vec::new();
+    let invoice_number = data.invoice_number.ok_or_else(|| Error::msg("Invoice number is missing"))?;
+    let issue_date = Date::parse(data.issue_date.as_deref().ok_or_else(|| Error::msg("Issue date is missing"))?)?;
+    let due_date = match data.due_date.as_deref() {
+        Some(due_date) => Date::parse(due_date)?,
+        None => {
+            losses.push("Due date is missing, the issue date is used instead".to_owned());
+            issue_date
+        }
+    };
+    let currency = data.currency.unwrap_or_else(|| {
+        losses.push("Currency is missing, EUR is used instead".to_owned());
+        "EUR".to_owned()
+    });
+
+    if data.vat_breakdown.len() > 1 {
+        let rates: Vec<String> = data.vat_breakdown.iter()
+            .map(|vat| format!("{} {}%", vat.category.as_deref().unwrap_or("?"), vat.rate.as_ref().map(|rate| rate.normalized().to_string()).unwrap_or_default()))
+            .collect();
+        losses.push(format!("Invoice can have only one VAT rate, the first one of {} is used", rates.join(", ")));
+    }
+    let vat = data.vat_breakdown.into_iter().next();
+    let vat_rate = vat.as_ref().and_then(|vat| vat.rate.clone())
+        .or_else(|| data.lines.iter().find_map(|line| line.vat_rate.clone()))
+        .unwrap_or_default();
+    let vat_category = match vat.as_ref().and_then(|vat| vat.category.as_deref()) {
+        Some(code) => {
+            let vat_category = VatCategory::from_code(code);
+            if vat_category.is_none() {
+                losses.push(format!("VAT category {} is not supported", code));
+            }
+            vat_category
+        },
+        None => None
+    };
+
+    let sign = if data.credit_note { BigDecimal::from(-1) } else { BigDecimal::from(1) };
+    let vat_multiplier = BigDecimal::from(1) + &vat_rate / BigDecimal::from(100);
+    let mut invoice_lines = Vec::new();
+    for line in data.lines {
+        if line.vat_rate.as_ref().is_some_and(|rate| rate != &vat_rate) {
+            losses.push(format!("VAT rate of line \"{}\" is changed to {}%", line.name, vat_rate.normalized()));
+        }
+        let price = match (line.amount, line.amount_without_vat) {
+            (Some(amount), _) => amount,
+            (None, Some(amount_without_vat)) => (amount_without_vat * &vat_multiplier).with_scale_round(2, RoundingMode::HalfUp),
+            (None, None) => return Err(Error::msg(format!("Amount of line \"{}\" is missing", line.name)))
+        };
+        let quantity = line.quantity.map(|quantity| quantity.abs()).unwrap_or_else(|| BigDecimal::from(1));
+        let count = if !quantity.is_integer() {
+            losses.push(format!("Quantity {} of line \"{}\" is not a whole number, the line price is kept", quantity.normalized(), line.name));
+            1
+        } else if let Some(count) = quantity.normalized().to_usize() {
+            count
+        } else {
+            losses.push(format!("Quantity {} of line \"{}\" is too large, the line price is kept", quantity.normalized(), line.name));
+            1
+        };
+        invoice_lines.push(InvoiceLine {
+            name: line.name,
+            count,
+            price: price * &sign
+        });
+    }
+    if let Some(total_price) = data.total_price.map(|total| total * &sign) {
+        adjust_to_total(&mut invoice_lines, &total_price, &mut losses);
+    }
+
+    let iban = data.iban.unwrap_or_else(|| {
+        losses.push("Bank account is missing".to_owned());
+        String::new()
+    });
+    let vat_exemption_reason = vat.and_then(|vat| vat.exemption_reason);
+    let note = (!data.notes.is_empty()).then(|| data.notes.join("\n"));
+    losses.extend(root.unused_values().into_iter().map(|value| format!("Not imported: {}", value)));
+
+    let invoice = Invoice {
+        billed_to: data.buyer,
+        billed_by: data.seller,
+        invoice_description: None,
+        currency,
+        vat_percent: vat_rate.to_string().parse().unwrap_or_default(),
+        vat_category,
+        vat_exemption_reason,
+        billed_at: issue_date.to_dd_mm_yyyy(),
+        due_date: due_date.to_dd_mm_yyyy(),
+        invoice_number,
+        reference_id: data.payment_reference,
+        buyer_reference: data.buyer_reference,
+        note,
+        payment_url: None,
+        bank_details: BankDetails {
+            account_number: iban,
+            bic_code: data.bic.unwrap_or_default()
+        },
+        invoice_lines,
+        locale: IMPORT_LOCALE.to_owned()
+    };
+    Ok(ImportedInvoice { invoice, losses })
+}
+
+/// Line prices computed from amounts without VAT can be a few cents off from the total of the document,
+/// the difference is added to the last line like `Invoice::totals` does for the amounts without VAT
+fn adjust_to_total(invoice_lines: &mut [InvoiceLine], total_price: &BigDecimal, losses: &mut Vec<String>) {
+    let difference = total_price - invoice_lines.iter().map(|line| &line.price).sum::<BigDecimal>();
+    if difference.is_zero() {
+        return;
+    }
+    let rounding_tolerance = BigDecimal::from(invoice_lines.len() as u64) / BigDecimal::from(100);
+    match invoice_lines.last_mut() {
+        Some(last_line) if difference.abs() <= rounding_tolerance => last_line.price += difference,
+        _ => losses.push(format!("Total price of the lines differs from the total {} of the document by {}", total_price, difference))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::INVOICE;
+    use crate::export::cii::{cii_xml, EN16931_PROFILE};
+    use crate::export::finvoice::finvoice_xml;
+    use crate::export::ubl::peppol_xml;
+    use crate::invoice::parse_invoice_json;
+
+    fn original() -> Invoice {
+        let mut invoice = parse_invoice_json(INVOICE).unwrap();
+        invoice.buyer_reference = Some("PO-17".to_owned());
+        invoice.billed_to.endpoint_id = Some("9930:DE123456789".to_owned());
+        invoice.invoice_lines.push(InvoiceLine { name: "Travel".to_owned(), count: 1, price: BigDecimal::from_str("99.99").unwrap() });
+        invoice
+    }
+
+    fn assert_same_invoice(imported: &Invoice, original: &Invoice) {
+        assert_eq!(imported.invoice_number, original.invoice_number);
+        assert_eq!(imported.billed_at, "04.02.2025");
+        assert_eq!(imported.due_date, "28.02.2025");
+        assert_eq!(imported.currency, original.currency);
+        assert_eq!(imported.vat_percent, original.vat_percent);
+        assert_eq!(imported.billed_by.name, original.billed_by.name);
+        assert_eq!(imported.billed_by.vat_id, original.billed_by.vat_id);
+        assert_eq!(imported.billed_to.town, original.billed_to.town);
+        assert_eq!(imported.bank_details.account_number, "FI2112345600000785");
+        assert_eq!(imported.reference_id.as_deref(), Some("RF18539007547034"));
+        let lines: Vec<(&str, usize, String)> = imported.invoice_lines.iter()
+            .map(|line| (line.name.as_str(), line.count, line.price.to_string()))
+            .collect();
+        assert_eq!(lines, vec![("Consulting", 2, "251.00".to_owned()), ("Travel", 1, "99.99".to_owned())]);
+    }
+
+    #[test]
+    fn should_import_exported_documents() {
+        let original = original();
+        let peppol = peppol_xml(&original).unwrap();
+        let cii = cii_xml(&original, &EN16931_PROFILE).unwrap();
+        // Whole quantities are often written with decimals
+        let peppol_with_decimals = peppol.replace(r#"unitCode="C62">2<"#, r#"unitCode="C62">2.00<"#);
+        let cii_with_decimals = cii.replace(r#"unitCode="C62">2<"#, r#"unitCode="C62">2.00<"#);
+        assert_ne!(peppol_with_decimals, peppol);
+        assert_ne!(cii_with_decimals, cii);
+        for xml in [peppol, cii, finvoice_xml(&original).unwrap(), peppol_with_decimals, cii_with_decimals] {
+            let imported = import_xml(&xml).unwrap();
+            assert_same_invoice(&imported.invoice, &original);
+            assert!(imported.losses.is_empty(), "{:?}", imported.losses);
+        }
+    }
+
+    #[test]
+    fn should_import_credit_notes_with_negative_prices() {
+        let mut original = original();
+        original.invoice_lines.truncate(1);
+        original.invoice_lines[0].price = BigDecimal::from(-251);
+        for xml in [peppol_xml(&original).unwrap(), cii_xml(&original, &EN16931_PROFILE).unwrap()] {
+            let imported = import_xml(&xml).unwrap().invoice;
+            assert_eq!(imported.invoice_lines[0].price, BigDecimal::from(-251));
+            assert_eq!(imported.invoice_lines[0].count, 2);
+        }
+    }
+
+    #[test]
+    fn should_report_lost_data() {
+        let xml = r#"<Invoice xmlns="urn:oasis:names:specification:ubl:schema:xsd:Invoice-2"
+                xmlns:cac="urn:oasis:names:specification:ubl:schema:xsd:CommonAggregateComponents-2"
+                xmlns:cbc="urn:oasis:names:specification:ubl:schema:xsd:CommonBasicComponents-2">
+            <cbc:ID>17</cbc:ID>
+            <cbc:IssueDate>2025-02-04</cbc:IssueDate>
+            <cbc:DocumentCurrencyCode>EUR</cbc:DocumentCurrencyCode>
+            <cac:Delivery><cbc:ActualDeliveryDate>2025-02-01</cbc:ActualDeliveryDate></cac:Delivery>
+            <cac:TaxTotal>
+                <cac:TaxSubtotal><cac:TaxCategory><cbc:ID>S</cbc:ID><cbc:Percent>24</cbc:Percent></cac:TaxCategory></cac:TaxSubtotal>
+                <cac:TaxSubtotal><cac:TaxCategory><cbc:ID>S</cbc:ID><cbc:Percent>14</cbc:Percent></cac:TaxCategory></cac:TaxSubtotal>
+            </cac:TaxTotal>
+            <cac:InvoiceLine>
+                <cbc:InvoicedQuantity unitCode="HUR">1.5</cbc:InvoicedQuantity>
+                <cbc:LineExtensionAmount currencyID="EUR">150.00</cbc:LineExtensionAmount>
+                <cac:Item><cbc:Name>Consulting</cbc:Name></cac:Item>
+            </cac:InvoiceLine>
+        </Invoice>"#;
+        let imported = import_xml(xml).unwrap();
+        assert_eq!(imported.invoice.invoice_lines[0].price, BigDecimal::from(186));
+        assert_eq!(imported.losses, vec![
+            "Due date is missing, the issue date is used instead",
+            "Invoice can have only one VAT rate, the first one of S 24%, S 14% is used",
+            "Quantity 1.5 of line \"Consulting\" is not a whole number, the line price is kept",
+            "Bank account is missing",
+            "Not imported: Delivery/ActualDeliveryDate = 2025-02-01"
+        ]);
+    }
+
+    #[test]
+    fn should_reject_unknown_documents() {
+        assert!(import_xml("<Order/>").is_err());
+        assert!(import_xml("<Invoice>").is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use anyhow::Error;
+use crate::import::xml_tree::Element;
+use crate::import::{parse_decimal, DocumentData, DocumentLine, VatBreakdown};
+use crate::invoice::BillingInformation;
+
+/// Reads an UN/CEFACT Cross Industry Invoice, e.g. the XML of a Factur-X or ZUGFeRD invoice
+pub(crate) fn read_cii(root: &Element) -> Result<DocumentData, Error> {
+    root.ignore("ExchangedDocumentContext");
+    let document = root.child("ExchangedDocument");
+    let text = |element: Option<&Element>, path: &str| element.and_then(|element| element.text(path)).map(str::to_owned);
+    let transaction = root.child("SupplyChainTradeTransaction");
+    let agreement = transaction.and_then(|transaction| transaction.child("ApplicableHeaderTradeAgreement"));
+    let settlement = transaction.and_then(|transaction| transaction.child("ApplicableHeaderTradeSettlement"));
+    let payment_means = settlement.and_then(|settlement| settlement.child("SpecifiedTradeSettlementPaymentMeans"));
+    let summation = settlement.and_then(|settlement| settlement.child("SpecifiedTradeSettlementHeaderMonetarySummation"));
+
+    let mut data = DocumentData {
+        invoice_number: text(document, "ID"),
+        issue_date: text(document, "IssueDateTime/DateTimeString"),
+        due_date: text(settlement, "SpecifiedTradePaymentTerms/DueDateDateTime/DateTimeString"),
+        credit_note: text(document, "TypeCode").as_deref() == Some("381"),
+        currency: text(settlement, "InvoiceCurrencyCode"),
+        notes: document.map(|document| document.children("IncludedNote/Content")).unwrap_or_default()
+            .into_iter().filter_map(Element::own_text).map(str::to_owned).collect(),
+        buyer_reference: text(agreement, "BuyerReference"),
+        payment_reference: text(settlement, "PaymentReference"),
+        iban: text(payment_means, "PayeePartyCreditorFinancialAccount/IBANID"),
+        bic: text(payment_means, "PayeeSpecifiedCreditorFinancialInstitution/BICID"),
+        seller: agreement.and_then(|agreement| agreement.child("SellerTradeParty")).map(read_trade_party).unwrap_or_default(),
+        buyer: agreement.and_then(|agreement| agreement.child("BuyerTradeParty")).map(read_trade_party).unwrap_or_default(),
+        total_price: parse_decimal(summation.and_then(|summation| summation.text("GrandTotalAmount")))?,
+        ..DocumentData::default()
+    };
+    if let Some(payment_means) = payment_means {
+        payment_means.ignore("TypeCode");
+    }
+    if let Some(summation) = summation {
+        for path in ["LineTotalAmount", "TaxBasisTotalAmount", "TaxTotalAmount", "DuePayableAmount"] {
+            summation.ignore(path);
+        }
+    }
+
+    for tax in settlement.map(|settlement| settlement.children("ApplicableTradeTax")).unwrap_or_default() {
+        for path in ["CalculatedAmount", "TypeCode", "BasisAmount", "ExemptionReasonCode"] {
+            tax.ignore(path);
+        }
+        data.vat_breakdown.push(VatBreakdown {
+            category: tax.text("CategoryCode").map(str::to_owned),
+            rate: parse_decimal(tax.text("RateApplicablePercent"))?,
+            exemption_reason: tax.text("ExemptionReason").map(str::to_owned)
+        });
+    }
+
+    for line in transaction.map(|transaction| transaction.children("IncludedSupplyChainTradeLineItem")).unwrap_or_default() {
+        line.ignore("AssociatedDocumentLineDocument/LineID");
+        line.ignore("SpecifiedLineTradeAgreement/NetPriceProductTradePrice/ChargeAmount");
+        line.ignore("SpecifiedLineTradeSettlement/ApplicableTradeTax/TypeCode");
+        line.ignore("SpecifiedLineTradeSettlement/ApplicableTradeTax/CategoryCode");
+        data.lines.push(DocumentLine {
+            name: line.text("SpecifiedTradeProduct/Name").unwrap_or_default().to_owned(),
+            quantity: parse_decimal(line.text("SpecifiedLineTradeDelivery/BilledQuantity"))?,
+            amount_without_vat: parse_decimal(line.text("SpecifiedLineTradeSettlement/SpecifiedTradeSettlementLineMonetarySummation/LineTotalAmount"))?,
+            amount: None,
+            vat_rate: parse_decimal(line.text("SpecifiedLineTradeSettlement/ApplicableTradeTax/RateApplicablePercent"))?
+        });
+    }
+    Ok(data)
+}
+
+fn read_trade_party(party: &Element) -> BillingInformation {
+    let endpoint_id = party.child("URIUniversalCommunication/URIID").and_then(|endpoint| {
+        let identifier = endpoint.own_text()?;
+        Some(match endpoint.attribute("schemeID") {
+            Some(scheme) => format!("{}:{}", scheme, identifier),
+            None => identifier.to_owned()
+        })
+    });
+    let vat_id = party.children("SpecifiedTaxRegistration/ID").into_iter()
+        .find(|id| id.attribute("schemeID") == Some("VA"))
+        .and_then(Element::own_text);
+    BillingInformation {
+        name: party.text("Name").unwrap_or_default().to_owned(),
+        email: party.text("DefinedTradeContact/EmailURIUniversalCommunication/URIID").map(str::to_owned),
+        company_id: party.text("SpecifiedLegalOrganization/ID").map(str::to_owned),
+        vat_id: vat_id.map(str::to_owned),
+        phone_number: party.text("DefinedTradeContact/TelephoneUniversalCommunication/CompleteNumber").map(str::to_owned),
+        contact_name: party.text("DefinedTradeContact/PersonName").map(str::to_owned),
+        address_line_1: party.text("PostalTradeAddress/LineOne").unwrap_or_default().to_owned(),
+        address_line_2: party.text("PostalTradeAddress/LineTwo").map(str::to_owned),
+        address_line_3: party.text("PostalTradeAddress/LineThree").map(str::to_owned),
+        postal_code: party.text("PostalTradeAddress/PostcodeCode").map(str::to_owned),
+        town: party.text("PostalTradeAddress/CityName").map(str::to_owned),
+        country_code: party.text("PostalTradeAddress/CountryID").map(str::to_owned),
+        endpoint_id,
+        ..BillingInformation::default()
+    }
+}
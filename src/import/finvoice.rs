@@ -0,0 +1,101 @@
+use anyhow::Error;
+use crate::import::xml_tree::Element;
+use crate::import::{join_texts, parse_decimal, DocumentData, DocumentLine, VatBreakdown};
+use crate::invoice::BillingInformation;
+
+/// Reads a Finvoice 3.0 document. Finvoice amounts are signed, so credit notes already have negative amounts.
+pub(crate) fn read_finvoice(root: &Element) -> Result<DocumentData, Error> {
+    let details = root.child("InvoiceDetails");
+    let text = |element: Option<&Element>, path: &str| element.and_then(|element| element.text(path)).map(str::to_owned);
+    let epi = root.child("EpiDetails");
+    if let Some(details) = details {
+        for path in ["InvoiceTypeCode", "InvoiceTypeCodeUN", "InvoiceTypeText", "OriginCode", "InvoiceTotalVatExcludedAmount", "InvoiceTotalVatAmount"] {
+            details.ignore(path);
+        }
+    }
+    if let Some(epi) = epi {
+        for path in ["EpiIdentificationDetails/EpiDate", "EpiPartyDetails/EpiBeneficiaryPartyDetails/EpiNameAddressDetails",
+            "EpiPaymentInstructionDetails/EpiInstructedAmount", "EpiPaymentInstructionDetails/EpiCharge"] {
+            epi.ignore(path);
+        }
+    }
+    let total = details.and_then(|details| details.child("InvoiceTotalVatIncludedAmount"));
+    // The payment details are written both for the buyer and for the bank, either one is enough
+    let due_date = text(details, "PaymentTermsDetails/InvoiceDueDate")
+        .or(text(epi, "EpiPaymentInstructionDetails/EpiDateOptionDate"));
+    let iban = text(epi, "EpiPartyDetails/EpiBeneficiaryPartyDetails/EpiAccountID")
+        .or(text(root.child("SellerInformationDetails"), "SellerAccountDetails/SellerAccountID"));
+    let bic = text(epi, "EpiPartyDetails/EpiBfiPartyDetails/EpiBfiIdentifier")
+        .or(text(root.child("SellerInformationDetails"), "SellerAccountDetails/SellerBic"));
+
+    let mut data = DocumentData {
+        invoice_number: text(details, "InvoiceNumber"),
+        issue_date: text(details, "InvoiceDate"),
+        due_date,
+        credit_note: false,
+        currency: total.and_then(|total| total.attribute("AmountCurrencyIdentifier")).map(str::to_owned),
+        notes: details.map(|details| details.children("InvoiceFreeText")).unwrap_or_default()
+            .into_iter().filter_map(Element::own_text).map(str::to_owned).collect(),
+        buyer_reference: text(details, "BuyerReferenceIdentifier").or_else(|| text(details, "OrderIdentifier")),
+        payment_reference: text(epi, "EpiPaymentInstructionDetails/EpiRemittanceInfoIdentifier"),
+        iban,
+        bic,
+        seller: read_party(root, "Seller"),
+        buyer: read_party(root, "Buyer"),
+        total_price: parse_decimal(total.and_then(Element::own_text))?,
+        ..DocumentData::default()
+    };
+
+    for vat in details.map(|details| details.children("VatSpecificationDetails")).unwrap_or_default() {
+        vat.ignore("VatBaseAmount");
+        vat.ignore("VatRateAmount");
+        data.vat_breakdown.push(VatBreakdown {
+            category: vat.text("VatCode").map(str::to_owned),
+            rate: parse_decimal(vat.text("VatRatePercent"))?,
+            exemption_reason: vat.text("VatFreeText").map(str::to_owned)
+        });
+    }
+
+    for row in root.children("InvoiceRow") {
+        for path in ["UnitPriceAmount", "RowVatCode", "RowVatAmount"] {
+            row.ignore(path);
+        }
+        data.lines.push(DocumentLine {
+            name: row.text("ArticleName").unwrap_or_default().to_owned(),
+            quantity: parse_decimal(row.text("DeliveredQuantity"))?,
+            amount_without_vat: parse_decimal(row.text("RowVatExcludedAmount"))?,
+            amount: parse_decimal(row.text("RowAmount"))?,
+            vat_rate: parse_decimal(row.text("RowVatRatePercent"))?
+        });
+    }
+    Ok(data)
+}
+
+/// Seller and buyer details only differ by the prefix of the element names
+fn read_party(root: &Element, prefix: &str) -> BillingInformation {
+    let text = |path: String| root.text(&path).map(str::to_owned);
+    let party_details = format!("{}PartyDetails", prefix);
+    let address = format!("{}/{}PostalAddressDetails", party_details, prefix);
+    let communication = format!("{}CommunicationDetails", prefix);
+    root.ignore(&format!("{}/CountryName", address));
+    let names = root.children(&format!("{}/{}OrganisationName", party_details, prefix));
+    let streets = root.children(&format!("{}/{}StreetName", address, prefix));
+    let endpoint_id = text(format!("{}OrganisationUnitNumber", prefix)).map(|ovt_code| format!("0216:{}", ovt_code));
+    let website = (prefix == "Seller").then(|| text("SellerInformationDetails/SellerWebaddressIdentifier".to_owned())).flatten();
+    BillingInformation {
+        name: join_texts(names.into_iter().map(Element::own_text), " ").unwrap_or_default(),
+        email: text(format!("{}/{}EmailaddressIdentifier", communication, prefix)),
+        company_id: text(format!("{}/{}PartyIdentifier", party_details, prefix)),
+        vat_id: text(format!("{}/{}OrganisationTaxCode", party_details, prefix)),
+        phone_number: text(format!("{}/{}PhoneNumberIdentifier", communication, prefix)),
+        contact_name: text(format!("{}ContactPersonName", prefix)),
+        website,
+        address_line_1: streets.first().and_then(|street| street.own_text()).unwrap_or_default().to_owned(),
+        address_line_2: join_texts(streets.iter().skip(1).map(|street| street.own_text()), ", "),
+        postal_code: text(format!("{}/{}PostCodeIdentifier", address, prefix)),
+        town: text(format!("{}/{}TownName", address, prefix)),
+        country_code: text(format!("{}/CountryCode", address)),
+        endpoint_id,
+        ..BillingInformation::default()
+    }
+}
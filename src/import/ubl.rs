@@ -0,0 +1,94 @@
+use anyhow::Error;
+use crate::import::xml_tree::Element;
+use crate::import::{join_texts, parse_decimal, DocumentData, DocumentLine, VatBreakdown};
+use crate::invoice::BillingInformation;
+
+/// Reads a UBL 2.1 Invoice or CreditNote
+pub(crate) fn read_ubl(root: &Element) -> Result<DocumentData, Error> {
+    root.ignore("UBLVersionID");
+    root.ignore("CustomizationID");
+    root.ignore("ProfileID");
+    let credit_note = root.name == "CreditNote" || root.text("InvoiceTypeCode") == Some("381");
+    root.ignore("CreditNoteTypeCode");
+    let mut data = DocumentData {
+        invoice_number: root.text("ID").map(str::to_owned),
+        issue_date: root.text("IssueDate").map(str::to_owned),
+        due_date: root.text("DueDate")
+            .or_else(|| root.text("PaymentMeans/PaymentDueDate"))
+            .map(str::to_owned),
+        credit_note,
+        currency: root.text("DocumentCurrencyCode").map(str::to_owned),
+        notes: root.children("Note").into_iter().filter_map(Element::own_text).map(str::to_owned).collect(),
+        buyer_reference: root.text("BuyerReference").map(str::to_owned),
+        payment_reference: root.text("PaymentMeans/PaymentID").map(str::to_owned),
+        iban: root.text("PaymentMeans/PayeeFinancialAccount/ID").map(str::to_owned),
+        bic: root.text("PaymentMeans/PayeeFinancialAccount/FinancialInstitutionBranch/ID").map(str::to_owned),
+        seller: root.child("AccountingSupplierParty/Party").map(read_party).unwrap_or_default(),
+        buyer: root.child("AccountingCustomerParty/Party").map(read_party).unwrap_or_default(),
+        total_price: parse_decimal(root.text("LegalMonetaryTotal/TaxInclusiveAmount"))?,
+        ..DocumentData::default()
+    };
+    root.ignore("PaymentMeans/PaymentMeansCode");
+    for path in ["LineExtensionAmount", "TaxExclusiveAmount", "PayableAmount"] {
+        root.ignore(&format!("LegalMonetaryTotal/{}", path));
+    }
+
+    for tax_total in root.children("TaxTotal") {
+        tax_total.ignore("TaxAmount");
+        for subtotal in tax_total.children("TaxSubtotal") {
+            subtotal.ignore("TaxableAmount");
+            subtotal.ignore("TaxAmount");
+            subtotal.ignore("TaxCategory/TaxScheme");
+            subtotal.ignore("TaxCategory/TaxExemptionReasonCode");
+            data.vat_breakdown.push(VatBreakdown {
+                category: subtotal.text("TaxCategory/ID").map(str::to_owned),
+                rate: parse_decimal(subtotal.text("TaxCategory/Percent"))?,
+                exemption_reason: subtotal.text("TaxCategory/TaxExemptionReason").map(str::to_owned)
+            });
+        }
+    }
+
+    let (line_name, quantity_name) = if root.name == "CreditNote" { ("CreditNoteLine", "CreditedQuantity") } else { ("InvoiceLine", "InvoicedQuantity") };
+    for line in root.children(line_name) {
+        line.ignore("ID");
+        line.ignore("Price/PriceAmount");
+        line.ignore("Item/ClassifiedTaxCategory/ID");
+        line.ignore("Item/ClassifiedTaxCategory/TaxScheme");
+        data.lines.push(DocumentLine {
+            name: line.text("Item/Name").or_else(|| line.text("Item/Description")).unwrap_or_default().to_owned(),
+            quantity: parse_decimal(line.text(quantity_name))?,
+            amount_without_vat: parse_decimal(line.text("LineExtensionAmount"))?,
+            amount: None,
+            vat_rate: parse_decimal(line.text("Item/ClassifiedTaxCategory/Percent"))?
+        });
+    }
+    Ok(data)
+}
+
+fn read_party(party: &Element) -> BillingInformation {
+    let endpoint_id = party.child("EndpointID").and_then(|endpoint| {
+        let identifier = endpoint.own_text()?;
+        Some(match endpoint.attribute("schemeID") {
+            Some(scheme) => format!("{}:{}", scheme, identifier),
+            None => identifier.to_owned()
+        })
+    });
+    party.ignore("PartyTaxScheme/TaxScheme");
+    let registration_name = party.text("PartyLegalEntity/RegistrationName");
+    BillingInformation {
+        name: party.text("PartyName/Name").or(registration_name).unwrap_or_default().to_owned(),
+        email: party.text("Contact/ElectronicMail").map(str::to_owned),
+        company_id: party.text("PartyLegalEntity/CompanyID").map(str::to_owned),
+        vat_id: party.text("PartyTaxScheme/CompanyID").map(str::to_owned),
+        phone_number: party.text("Contact/Telephone").map(str::to_owned),
+        contact_name: party.text("Contact/Name").map(str::to_owned),
+        address_line_1: party.text("PostalAddress/StreetName").unwrap_or_default().to_owned(),
+        address_line_2: party.text("PostalAddress/AdditionalStreetName").map(str::to_owned),
+        address_line_3: join_texts(party.children("PostalAddress/AddressLine").into_iter().map(|line| line.text("Line")), ", "),
+        postal_code: party.text("PostalAddress/PostalZone").map(str::to_owned),
+        town: party.text("PostalAddress/CityName").map(str::to_owned),
+        country_code: party.text("PostalAddress/Country/IdentificationCode").map(str::to_owned),
+        endpoint_id,
+        ..BillingInformation::default()
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::{Context, Error};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::cell::Cell;
+
+/// XML element with the namespace prefixes dropped from the names. Reading the text of an element
+/// marks it as used, so that the data the importer didn't read can be listed afterwards.
+#[derive(Debug)]
+pub(crate) struct Element {
+    pub name: String,
+    attributes: Vec<(String, String)>,
+    text: String,
+    children: Vec<Element>,
+    used: Cell<bool>
+}
+
+impl Element {
+    pub fn parse(xml: &str) -> Result<Element, Error> {
+        let mut reader = Reader::from_str(xml);
+        reader.config_mut().trim_text(true);
+        let mut open_elements: Vec<Element> = Vec::new();
+        loop {
+            let event = reader.read_event()
+                .with_context(|| format!("Invalid XML at position {}", reader.buffer_position()))?;
+            match event {
+                Event::Start(start) => open_elements.push(Element::new(&start)?),
+                Event::Empty(start) => {
+                    let element = Element::new(&start)?;
+                    match open_elements.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element)
+                    }
+                },
+                Event::Text(text) => if let Some(element) = open_elements.last_mut() {
+                    element.text.push_str(&text.unescape()?);
+                },
+                Event::CData(data) => if let Some(element) = open_elements.last_mut() {
+                    element.text.push_str(&String::from_utf8_lossy(&data));
+                },
+                Event::End(_) => {
+                    let element = open_elements.pop().context("Unexpected closing tag")?;
+                    match open_elements.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element)
+                    }
+                },
+                Event::Eof => return Err(Error::msg("XML document has no root element or it is not closed")),
+                _ => {}
+            }
+        }
+    }
+
+    fn new(start: &BytesStart) -> Result<Element, Error> {
+        let mut attributes = Vec::new();
+        for attribute in start.attributes() {
+            let attribute = attribute?;
+            attributes.push((
+                String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned(),
+                attribute.unescape_value()?.into_owned()
+            ));
+        }
+        Ok(Element {
+            name: String::from_utf8_lossy(start.local_name().as_ref()).into_owned(),
+            attributes,
+            text: String::new(),
+            children: Vec::new(),
+            used: Cell::new(false)
+        })
+    }
+
+    /// First element at the path of child names separated by "/", e.g. "Party/PartyName/Name"
+    pub fn child(&self, path: &str) -> Option<&Element> {
+        path.split('/').try_fold(self, |element, name| element.children.iter().find(|child| child.name == name))
+    }
+
+    /// All the elements at the path, e.g. all "InvoiceLine" elements
+    pub fn children<'a>(&'a self, path: &str) -> Vec<&'a Element> {
+        let (parent, name) = match path.rsplit_once('/') {
+            Some((parent_path, name)) => (self.child(parent_path), name),
+            None => (Some(self), path)
+        };
+        parent.map(|parent| parent.children.iter().filter(|child| child.name == name).collect())
+            .unwrap_or_default()
+    }
+
+    /// Trimmed text of the element at the path, `None` when there is no such element or it is empty
+    pub fn text(&self, path: &str) -> Option<&str> {
+        self.child(path).and_then(Element::own_text)
+    }
+
+    pub fn own_text(&self) -> Option<&str> {
+        self.used.set(true);
+        let text = self.text.trim();
+        (!text.is_empty()).then_some(text)
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    /// Marks everything under the path as used, for data which is implied by the imported invoice
+    pub fn ignore(&self, path: &str) {
+        for element in self.children(path) {
+            element.mark_used();
+        }
+    }
+
+    fn mark_used(&self) {
+        self.used.set(true);
+        self.children.iter().for_each(Element::mark_used);
+    }
+
+    /// "path = value" of every element with text that wasn't read
+    pub fn unused_values(&self) -> Vec<String> {
+        let mut unused = Vec::new();
+        for child in &self.children {
+            child.collect_unused("", &mut unused);
+        }
+        unused
+    }
+
+    fn collect_unused(&self, parent_path: &str, unused: &mut Vec<String>) {
+        let path = if parent_path.is_empty() { self.name.clone() } else { format!("{}/{}", parent_path, self.name) };
+        let text = self.text.trim();
+        if !self.used.get() && !text.is_empty() {
+            unused.push(format!("{} = {}", path, text));
+        }
+        for child in &self.children {
+            child.collect_unused(&path, unused);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_list_values_which_were_not_read() {
+        let root = Element::parse(r#"<?xml version="1.0"?>
+            <ubl:Invoice xmlns:ubl="urn:ubl" xmlns:cbc="urn:cbc">
+                <cbc:ID schemeID="0088">A &amp; B</cbc:ID>
+                <cbc:Note>First</cbc:Note>
+                <cbc:Note>Second</cbc:Note>
+                <Delivery><Date>2025-02-01</Date><Empty/></Delivery>
+            </ubl:Invoice>"#).unwrap();
+        assert_eq!(root.name, "Invoice");
+        assert_eq!(root.text("ID"), Some("A & B"));
+        assert_eq!(root.child("ID").unwrap().attribute("schemeID"), Some("0088"));
+        assert_eq!(root.children("Note").len(), 2);
+        assert_eq!(root.children("Note")[0].own_text(), Some("First"));
+        assert_eq!(root.unused_values(), vec!["Note = Second", "Delivery/Date = 2025-02-01"]);
+        root.ignore("Delivery");
+        assert_eq!(root.unused_values(), vec!["Note = Second"]);
+    }
+}
@@ -1,35 +1,115 @@
 use anyhow::{Context, Error};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json;
-use bigdecimal::BigDecimal;
+use bigdecimal::{BigDecimal, RoundingMode};
+use std::str::FromStr;
+use crate::theme::Theme;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct BillingInformation {
     pub name: String,
     pub email: Option<String>,
     pub company_id: Option<String>,
     pub vat_id: Option<String>,
     pub phone_number: Option<String>,
+    /// Contact person, required by XRechnung for the seller
+    pub contact_name: Option<String>,
+    pub website: Option<String>,
     pub address_line_1: String,
     pub address_line_2: Option<String>,
     pub address_line_3: Option<String>,
+    pub postal_code: Option<String>,
+    pub town: Option<String>,
+    /// ISO 3166-1 alpha-2 country code, e.g. "FI"
+    pub country_code: Option<String>,
+    /// Electronic address for e-invoicing as "scheme:identifier", e.g. "0216:003712345678"
+    pub endpoint_id: Option<String>,
     pub detail: Option<String>,
-    pub logo: Option<String>
+    pub logo: Option<String>,
+    /// Brand colors and fonts of the seller's invoices
+    pub theme: Option<Theme>
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct InvoiceLine {
     pub name: String,
     pub count: usize,
+    #[serde(deserialize_with = "deserialize_decimal")]
     pub price: BigDecimal
 }
 
+/// JSON numbers are read as written, e.g. 89.99 and not as the nearest binary float 89.9899999999999948...
+fn deserialize_decimal<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigDecimal, D::Error> {
+    let value = serde_json::Value::deserialize(deserializer)?;
+    let text = match &value {
+        serde_json::Value::Number(number) => number.to_string(),
+        serde_json::Value::String(text) => text.clone(),
+        _ => return Err(serde::de::Error::custom(format!("Expected a number, got {}", value)))
+    };
+    BigDecimal::from_str(text.trim()).map_err(|_| serde::de::Error::custom(format!("Invalid number: {}", text)))
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BankDetails {
     pub account_number: String,
     pub bic_code: String
 }
 
+/// VAT category codes of the UNCL5305 code list used by EN 16931
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VatCategory {
+    #[serde(rename = "S")]
+    Standard,
+    #[serde(rename = "Z")]
+    ZeroRated,
+    #[serde(rename = "E")]
+    Exempt,
+    #[serde(rename = "AE")]
+    ReverseCharge,
+    #[serde(rename = "K")]
+    IntraCommunitySupply,
+    #[serde(rename = "G")]
+    Export,
+    #[serde(rename = "O")]
+    OutsideScope
+}
+
+impl VatCategory {
+    pub fn from_code(code: &str) -> Option<VatCategory> {
+        [
+            VatCategory::Standard, VatCategory::ZeroRated, VatCategory::Exempt, VatCategory::ReverseCharge,
+            VatCategory::IntraCommunitySupply, VatCategory::Export, VatCategory::OutsideScope
+        ].into_iter().find(|category| category.code() == code)
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            VatCategory::Standard => "S",
+            VatCategory::ZeroRated => "Z",
+            VatCategory::Exempt => "E",
+            VatCategory::ReverseCharge => "AE",
+            VatCategory::IntraCommunitySupply => "K",
+            VatCategory::Export => "G",
+            VatCategory::OutsideScope => "O"
+        }
+    }
+
+    /// VATEX exemption reason code, for the categories that have a single generic one
+    pub fn exemption_reason_code(&self) -> Option<&'static str> {
+        match self {
+            VatCategory::ReverseCharge => Some("VATEX-EU-AE"),
+            VatCategory::IntraCommunitySupply => Some("VATEX-EU-IC"),
+            VatCategory::Export => Some("VATEX-EU-G"),
+            VatCategory::OutsideScope => Some("VATEX-EU-O"),
+            _ => None
+        }
+    }
+
+    pub fn requires_exemption_reason(&self) -> bool {
+        !matches!(self, VatCategory::Standard | VatCategory::ZeroRated)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Invoice {
     pub billed_to: BillingInformation,
@@ -37,17 +117,123 @@ pub struct Invoice {
     pub invoice_description: Option<String>,
     pub currency: String,
     pub vat_percent: f32,
+    /// Defaults to "S" (standard rate) when VAT is charged and to "Z" (zero rated) otherwise
+    pub vat_category: Option<VatCategory>,
+    pub vat_exemption_reason: Option<String>,
     pub billed_at: String,
     pub due_date: String,
     pub invoice_number: String,
     pub reference_id: Option<String>,
+    /// Reference given by the buyer, e.g. a purchase order number or a Leitweg-ID
+    pub buyer_reference: Option<String>,
     pub note: Option<String>,
+    /// Address of an online payment page for the invoice
+    pub payment_url: Option<String>,
     pub bank_details: BankDetails,
     pub invoice_lines: Vec<InvoiceLine>,
     pub locale: String
 }
 
+pub struct InvoiceTotals {
+    pub total_price: BigDecimal,
+    pub total_vat: BigDecimal,
+    pub total_price_without_vat: BigDecimal,
+    /// Prices of the invoice lines without VAT, in the same order as the lines,
+    /// rounded to cents so that they add up to `total_price_without_vat`
+    pub line_prices_without_vat: Vec<BigDecimal>
+}
+
+impl Invoice {
+    /// VAT percentage as a decimal, e.g. 25.5
+    pub fn vat_rate(&self) -> BigDecimal {
+        BigDecimal::from_str(&self.vat_percent.to_string()).unwrap_or_default()
+    }
+
+    pub fn vat_category(&self) -> VatCategory {
+        match self.vat_category {
+            Some(vat_category) => vat_category,
+            None if self.vat_percent > 0.0 => VatCategory::Standard,
+            None => VatCategory::ZeroRated
+        }
+    }
+
+    /// Line prices include VAT, the VAT is the difference between the total price and
+    /// the total price without VAT rounded to cents
+    pub fn totals(&self) -> InvoiceTotals {
+        let vat_multiplier = BigDecimal::from(1) + self.vat_rate() / BigDecimal::from(100);
+        let without_vat = |price: &BigDecimal| (price / &vat_multiplier).with_scale_round(2, RoundingMode::HalfUp);
+
+        let total_price: BigDecimal = self.invoice_lines.iter().map(|line| &line.price).sum();
+        let total_price_without_vat = without_vat(&total_price);
+        let total_vat = &total_price - &total_price_without_vat;
+
+        let mut line_prices_without_vat: Vec<BigDecimal> = self.invoice_lines.iter().map(|line| without_vat(&line.price)).collect();
+        let rounding_difference = &total_price_without_vat - line_prices_without_vat.iter().sum::<BigDecimal>();
+        if let Some(last_line_price) = line_prices_without_vat.last_mut() {
+            *last_line_price += rounding_difference;
+        }
+
+        InvoiceTotals {
+            total_price,
+            total_vat,
+            total_price_without_vat,
+            line_prices_without_vat
+        }
+    }
+}
+
 pub fn parse_invoice_json(raw_invoice: &str) -> Result<Invoice, Error> {
     let translations: Invoice = serde_json::from_str(raw_invoice).context("Could not load translations")?;
     Ok(translations)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::test_fixtures::invoice_with;
+
+    /// Invoice with lines of the prices, written as in the invoice JSON
+    fn invoice(vat_percent: f32, prices: &[&str]) -> Invoice {
+        let lines: Vec<serde_json::Value> = prices.iter()
+            .map(|price| serde_json::from_str(&format!(r#"{{ "name": "Work", "count": 1, "price": {} }}"#, price)).unwrap())
+            .collect();
+        invoice_with(json!({ "vat_percent": vat_percent, "invoice_lines": lines }))
+    }
+
+    #[test]
+    fn should_compute_totals_from_prices_including_vat() {
+        let totals = invoice(25.5, &["7500"]).totals();
+        assert_eq!(totals.total_price, BigDecimal::from(7500));
+        assert_eq!(totals.total_price_without_vat, BigDecimal::from_str("5976.10").unwrap());
+        assert_eq!(totals.total_vat, BigDecimal::from_str("1523.90").unwrap());
+    }
+
+    #[test]
+    fn should_read_prices_exactly() {
+        let totals = invoice(25.5, &["89.99", "\"35.00\"", "12"]).totals();
+        assert_eq!(totals.total_price, BigDecimal::from_str("136.99").unwrap());
+        assert_eq!(totals.total_vat, BigDecimal::from_str("27.83").unwrap());
+    }
+
+    #[test]
+    fn should_make_line_prices_add_up_to_total() {
+        let totals = invoice(24.0, &["10", "10", "10"]).totals();
+        assert_eq!(totals.total_price_without_vat, BigDecimal::from_str("24.19").unwrap());
+        assert_eq!(totals.line_prices_without_vat, vec![
+            BigDecimal::from_str("8.06").unwrap(),
+            BigDecimal::from_str("8.06").unwrap(),
+            BigDecimal::from_str("8.07").unwrap()
+        ]);
+        assert_eq!(totals.total_vat, BigDecimal::from_str("5.81").unwrap());
+    }
+
+    #[test]
+    fn should_default_vat_category_from_vat_percent() {
+        assert_eq!(invoice(24.0, &["10"]).vat_category(), VatCategory::Standard);
+        assert_eq!(invoice(0.0, &["10"]).vat_category(), VatCategory::ZeroRated);
+        let mut reverse_charge = invoice(0.0, &["10"]);
+        reverse_charge.vat_category = Some(VatCategory::ReverseCharge);
+        assert_eq!(reverse_charge.vat_category(), VatCategory::ReverseCharge);
+    }
+}
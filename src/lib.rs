@@ -1,7 +1,17 @@
 pub mod format;
 pub mod components;
+pub mod date;
+pub mod drawing;
+pub mod export;
 pub mod invoice;
 pub mod locale;
+pub mod payment;
+pub mod pdf;
 pub mod renderer;
+pub mod theme;
 pub mod fonts;
-pub mod image;
\ No newline at end of file
+pub mod image;
+pub mod import;
+
+#[cfg(test)]
+mod test_fixtures;
@@ -2,6 +2,7 @@ use anyhow::Error;
 
 pub mod en_gb;
 pub mod fi_fi;
+pub mod hyphenation;
 pub mod translations;
 
 use en_gb::EN_GB;
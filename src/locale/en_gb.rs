@@ -1,4 +1,4 @@
-use crate::locale::translations::{Translations, InvoiceTranslations, AccountTranslations, LineTranslations};
+use crate::locale::translations::{Translations, InvoiceTranslations, AccountTranslations, LineTranslations, PaymentTranslations, SwissQrBillTranslations};
 
 pub const EN_GB: Translations = Translations {
     invoice: InvoiceTranslations {
@@ -10,6 +10,10 @@ pub const EN_GB: Translations = Translations {
         total_price_without_tax: "Total price without tax",
         total_price: "Total price",
         vat: "VAT",
+        carried_forward: "Carried forward",
+        brought_forward: "Brought forward",
+        page: "Page",
+        page_of: "of",
         line: LineTranslations {
             item: "Item",
             quantity: "Quantity",
@@ -23,5 +27,22 @@ pub const EN_GB: Translations = Translations {
     account: AccountTranslations {
         number: "Account number",
         bic: "BIC"
+    },
+    payment: PaymentTranslations {
+        epc_qr_code: "Scan to pay",
+        virtual_barcode: "Virtual barcode",
+        swiss_qr_bill: SwissQrBillTranslations {
+            receipt: "Receipt",
+            payment_part: "Payment part",
+            account_payable_to: "Account / Payable to",
+            reference: "Reference",
+            additional_information: "Additional information",
+            payable_by: "Payable by",
+            payable_by_name_address: "Payable by (name/address)",
+            currency: "Currency",
+            amount: "Amount",
+            acceptance_point: "Acceptance point",
+            separate_before_paying_in: "Separate before paying in"
+        }
     }
 };
\ No newline at end of file
@@ -1,4 +1,4 @@
-use crate::locale::translations::{Translations, InvoiceTranslations, AccountTranslations, LineTranslations};
+use crate::locale::translations::{Translations, InvoiceTranslations, AccountTranslations, LineTranslations, PaymentTranslations, SwissQrBillTranslations};
 
 pub const FI_FI: Translations = Translations {
     invoice: InvoiceTranslations {
@@ -10,6 +10,10 @@ pub const FI_FI: Translations = Translations {
         total_price_without_tax: "Veroton hinta yhteensä",
         total_price: "Summa yhteensä",
         vat: "Alv",
+        carried_forward: "Siirto seuraavalle sivulle",
+        brought_forward: "Siirto edelliseltä sivulta",
+        page: "Sivu",
+        page_of: "/",
         line: LineTranslations {
             item: "Tuote",
             quantity: "Määrä",
@@ -23,5 +27,23 @@ pub const FI_FI: Translations = Translations {
     account: AccountTranslations {
         number: "Tilinumero",
         bic: "BIC-koodi"
+    },
+    payment: PaymentTranslations {
+        epc_qr_code: "Skannaa ja maksa",
+        virtual_barcode: "Virtuaaliviivakoodi",
+        // The QR-bill standard only allows German, French, Italian and English headings
+        swiss_qr_bill: SwissQrBillTranslations {
+            receipt: "Receipt",
+            payment_part: "Payment part",
+            account_payable_to: "Account / Payable to",
+            reference: "Reference",
+            additional_information: "Additional information",
+            payable_by: "Payable by",
+            payable_by_name_address: "Payable by (name/address)",
+            currency: "Currency",
+            amount: "Amount",
+            acceptance_point: "Acceptance point",
+            separate_before_paying_in: "Separate before paying in"
+        }
     }
 };
\ No newline at end of file
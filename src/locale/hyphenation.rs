@@ -0,0 +1,126 @@
+/// Where long words may be broken when they do not fit on a line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hyphenation {
+    /// Words are only broken after hyphens they already contain, or between any characters as a last resort
+    None,
+    /// Finnish syllable rules, which also split long compound words
+    Finnish
+}
+
+impl Hyphenation {
+    pub fn for_locale(locale: &str) -> Hyphenation {
+        match locale {
+            "fi-FI" => Hyphenation::Finnish,
+            _ => Hyphenation::None
+        }
+    }
+
+    /// Character indexes the word may be broken before, a hyphen is added at the break
+    pub fn break_points(&self, word: &str) -> Vec<usize> {
+        match self {
+            Hyphenation::None => Vec::new(),
+            Hyphenation::Finnish => finnish_break_points(word)
+        }
+    }
+}
+
+const MIN_FRAGMENT: usize = 2;
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y' | 'ä' | 'ö' | 'å')
+}
+
+fn is_consonant(c: char) -> bool {
+    c.is_alphabetic() && !is_vowel(c)
+}
+
+/// Vowel pairs that belong to the same syllable, "ie", "uo" and "yö" only in the first syllable
+fn is_diphthong(first: char, second: char, first_syllable: bool) -> bool {
+    first == second || matches!(
+        (first, second),
+        ('a' | 'e' | 'o' | 'u' | 'y' | 'ä' | 'ö', 'i') | ('a' | 'e' | 'i' | 'o', 'u') | ('e' | 'i' | 'ä' | 'ö', 'y')
+    ) || (first_syllable && matches!((first, second), ('i', 'e') | ('u', 'o') | ('y', 'ö')))
+}
+
+/// Breaks the runs of letters of the word into syllables, leaving at least two letters on both sides
+fn finnish_break_points(word: &str) -> Vec<usize> {
+    let chars: Vec<char> = word.chars().flat_map(char::to_lowercase).collect();
+    if chars.len() != word.chars().count() {
+        return Vec::new();
+    }
+    let mut points = Vec::new();
+    let mut run_start = 0;
+    while run_start < chars.len() {
+        let run_length = chars[run_start..].iter().take_while(|c| c.is_alphabetic()).count();
+        points.extend(
+            finnish_syllables(&chars[run_start..run_start + run_length]).into_iter()
+                .filter(|&point| point >= MIN_FRAGMENT && run_length - point >= MIN_FRAGMENT)
+                .map(|point| run_start + point)
+        );
+        run_start += run_length.max(1);
+    }
+    points
+}
+
+/// A syllable starts at a consonant followed by a vowel, and between two vowels that do not form
+/// a long vowel or a diphthong, e.g. "tie-to-ko-ne-pal-ve-lut" and "ra-di-os-sa"
+fn finnish_syllables(chars: &[char]) -> Vec<usize> {
+    let mut points = Vec::new();
+    let mut syllable_start = 0;
+    let mut seen_vowel = false;
+    for index in 1..chars.len() {
+        let (previous, current) = (chars[index - 1], chars[index]);
+        let next = chars.get(index + 1).copied();
+        let syllable_vowels = chars[syllable_start..index].iter().rev().take_while(|c| is_vowel(**c)).count();
+        seen_vowel |= is_vowel(previous);
+        let starts_syllable = if is_consonant(current) {
+            seen_vowel && next.is_some_and(is_vowel)
+        } else if is_vowel(current) && is_vowel(previous) {
+            syllable_vowels >= 2 || !is_diphthong(previous, current, points.is_empty())
+        } else {
+            false
+        };
+        if starts_syllable && chars[syllable_start..index].iter().any(|c| is_vowel(*c)) {
+            points.push(index);
+            syllable_start = index;
+            seen_vowel = false;
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hyphenated(word: &str) -> String {
+        let points = Hyphenation::Finnish.break_points(word);
+        word.chars().enumerate()
+            .flat_map(|(index, c)| if points.contains(&index) { vec!['-', c] } else { vec![c] })
+            .collect()
+    }
+
+    #[test]
+    fn should_hyphenate_finnish_words() {
+        assert_eq!(hyphenated("Tietokonepalvelut"), "Tie-to-ko-ne-pal-ve-lut");
+        assert_eq!(hyphenated("kissa"), "kis-sa");
+        assert_eq!(hyphenated("radiossa"), "ra-di-os-sa");
+        assert_eq!(hyphenated("kauan"), "kau-an");
+        assert_eq!(hyphenated("strategia"), "stra-te-gia");
+        assert_eq!(hyphenated("Päivitys"), "Päi-vi-tys");
+        assert_eq!(hyphenated("ohjelmistokehitys"), "oh-jel-mis-to-ke-hi-tys");
+    }
+
+    #[test]
+    fn should_keep_fragments_of_two_characters() {
+        assert_eq!(hyphenated("aika"), "ai-ka");
+        assert_eq!(hyphenated("ilo"), "ilo");
+        assert_eq!(hyphenated("CD-levyt"), "CD-le-vyt");
+    }
+
+    #[test]
+    fn should_not_hyphenate_without_rules() {
+        assert!(Hyphenation::None.break_points("Tietokonepalvelut").is_empty());
+        assert_eq!(Hyphenation::for_locale("en-GB"), Hyphenation::None);
+    }
+}
@@ -3,7 +3,8 @@ pub struct Translations {
     pub invoice: InvoiceTranslations,
     pub company_id: &'static str,
     pub vat_id: &'static str,
-    pub account: AccountTranslations
+    pub account: AccountTranslations,
+    pub payment: PaymentTranslations
 }
 
 #[derive(Debug, Clone)]
@@ -16,6 +17,13 @@ pub struct InvoiceTranslations {
     pub total_price_without_tax: &'static str,
     pub total_price: &'static str,
     pub vat: &'static str,
+    /// Subtotal row at the bottom of a page when the invoice lines continue on the next page
+    pub carried_forward: &'static str,
+    /// Subtotal row at the top of a page continuing the invoice lines of the previous page
+    pub brought_forward: &'static str,
+    /// Page numbering, written as "{page} 1 {page_of} 2"
+    pub page: &'static str,
+    pub page_of: &'static str,
     pub line: LineTranslations
 }
 
@@ -32,4 +40,26 @@ pub struct LineTranslations {
     pub price: &'static str,
     pub price_without_tax: &'static str,
     pub vat: &'static str
+}
+
+#[derive(Debug, Clone)]
+pub struct PaymentTranslations {
+    pub epc_qr_code: &'static str,
+    pub virtual_barcode: &'static str,
+    pub swiss_qr_bill: SwissQrBillTranslations
+}
+
+#[derive(Debug, Clone)]
+pub struct SwissQrBillTranslations {
+    pub receipt: &'static str,
+    pub payment_part: &'static str,
+    pub account_payable_to: &'static str,
+    pub reference: &'static str,
+    pub additional_information: &'static str,
+    pub payable_by: &'static str,
+    pub payable_by_name_address: &'static str,
+    pub currency: &'static str,
+    pub amount: &'static str,
+    pub acceptance_point: &'static str,
+    pub separate_before_paying_in: &'static str
 }
\ No newline at end of file
@@ -1,26 +1,156 @@
 use anyhow::{ Context, Error };
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use std::fs;
-use std::fs::File;
-use std::io::BufWriter;
-use std::path::PathBuf;
-use invoicely::renderer::render;
+use std::path::{Path, PathBuf};
+use invoicely::export::finvoice::finvoice_xml;
+use invoicely::export::ubl::peppol_xml;
+use invoicely::export::xrechnung::{xrechnung_xml, XRechnungSyntax};
+use invoicely::export::factur_x::FacturXProfile;
+use invoicely::renderer::{extract_invoice_json, render_pdf, render_svg};
+use invoicely::renderer::html::render_html;
+use invoicely::renderer::text::{render_text, TextFormat};
+use invoicely::renderer::options::{Margins, Orientation, PageSize, PaymentSlip, QrCodePosition, RenderOptions};
+use invoicely::renderer::template::Template;
+use invoicely::theme::Theme;
+use invoicely::import::import_xml;
 use invoicely::invoice::Invoice;
-use printpdf::PdfSaveOptions;
 
 #[derive(Parser)]
 #[command(name = "invoice-generator")]
-#[command(about = "Generate PDF invoices from JSON data")]
+#[command(about = "Generate PDF invoices and e-invoices from JSON data")]
+#[command(subcommand_negates_reqs = true)]
 struct Args {
-    /// Input JSON file containing invoice data
-    #[arg(short, long)]
-    input: PathBuf,
-    
-    /// Output PDF file path
-    #[arg(short, long)]
-    output: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Input JSON file containing invoice data, or a UBL, CII or Finvoice e-invoice with an .xml extension
+    #[arg(short, long, required = true)]
+    input: Option<PathBuf>,
     
+    /// Output file path
+    #[arg(short, long, required = true)]
+    output: Option<PathBuf>,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "pdf")]
+    format: OutputFormat,
+
+    /// Add an EPC (SEPA credit transfer) QR code at the given position, only for invoices in EUR
+    #[arg(long, value_enum)]
+    epc_qr_code: Option<QrCodePositionArg>,
+
+    /// Add the Finnish bank barcode, requires a Finnish IBAN and a reference number
+    #[arg(long)]
+    bank_barcode: bool,
+
+    /// Print a payment slip at the bottom of the page
+    #[arg(long, value_enum)]
+    payment_slip: Option<PaymentSlipArg>,
+
+    /// Also write a PEPPOL BIS Billing 3.0 (UBL) XML invoice next to the PDF, with the same name and an .xml extension
+    #[arg(long)]
+    peppol_xml: bool,
+
+    /// Write a Factur-X / ZUGFeRD (EN 16931 profile) PDF/A-3 with the XML invoice embedded into the PDF
+    #[arg(long)]
+    factur_x: bool,
+
+    /// Page size of the PDF and SVG: a4, a5, letter or WIDTHxHEIGHT in mm, e.g. 210x99
+    #[arg(long, value_parser = parse_page_size, default_value = "a4")]
+    page_size: PageSize,
+
+    #[arg(long, value_enum, default_value = "portrait")]
+    orientation: OrientationArg,
+
+    /// Page margins in mm as TOP,RIGHT,BOTTOM,LEFT, the footer is printed inside the bottom margin [default: 20,10,25,15]
+    #[arg(long, value_parser = parse_margins)]
+    margins: Option<Margins>,
+
+    /// JSON template describing the blocks, regions and fonts of the PDF and SVG layout
+    #[arg(long)]
+    template: Option<PathBuf>,
+
+    /// JSON theme with the brand colors and fonts, overrides the theme of the seller in the invoice
+    #[arg(long)]
+    theme: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Write out the invoice JSON embedded into a PDF rendered by Invoicely, to edit and render it again
+    Extract {
+        /// PDF invoice rendered by Invoicely
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output JSON file path
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Pdf,
+    /// HTML page for browsers and email bodies, prints on A4 close to the PDF
+    Html,
+    /// SVG preview of the PDF layout, the second and following pages are written next to the output as name-2.svg, ...
+    Svg,
+    /// Plain text summary for email bodies
+    Text,
+    /// Markdown summary for email bodies and chat messages
+    Markdown,
+    /// Finvoice 3.0 XML e-invoice
+    Finvoice,
+    /// XRechnung 3.0 in the UBL syntax, for German public authorities
+    XrechnungUbl,
+    /// XRechnung 3.0 in the CII syntax, for German public authorities
+    XrechnungCii,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum QrCodePositionArg {
+    BottomLeft,
+    BottomRight,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OrientationArg {
+    Portrait,
+    Landscape,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PaymentSlipArg {
+    SwissQrBill,
+    FinnishTransferSlip,
+}
+
+impl From<PaymentSlipArg> for PaymentSlip {
+    fn from(payment_slip: PaymentSlipArg) -> Self {
+        match payment_slip {
+            PaymentSlipArg::SwissQrBill => PaymentSlip::SwissQrBill,
+            PaymentSlipArg::FinnishTransferSlip => PaymentSlip::FinnishTransferSlip,
+        }
+    }
+}
+
+impl From<OrientationArg> for Orientation {
+    fn from(orientation: OrientationArg) -> Self {
+        match orientation {
+            OrientationArg::Portrait => Orientation::Portrait,
+            OrientationArg::Landscape => Orientation::Landscape,
+        }
+    }
+}
 
+impl From<QrCodePositionArg> for QrCodePosition {
+    fn from(position: QrCodePositionArg) -> Self {
+        match position {
+            QrCodePositionArg::BottomLeft => QrCodePosition::BottomLeft,
+            QrCodePositionArg::BottomRight => QrCodePosition::BottomRight,
+        }
+    }
 }
 
 fn main() -> Result<(), Error> {
@@ -31,24 +161,154 @@ fn main() -> Result<(), Error> {
     };
     */
     
-    let raw_invoice = fs::read_to_string(&args.input)
-        .with_context(|| format!("Could not read input file: {}", args.input.display()))?;
-    
-    let invoice: Invoice = serde_json::from_str(&raw_invoice)
-        .context("Could not parse invoice JSON data")?;
-    
-    let doc = render(&invoice)?;
-    
-    let output_file = File::create(&args.output)
-        .with_context(|| format!("Could not create output file: {}", args.output.display()))?;
-    let mut writer = BufWriter::new(output_file);
-    
-    let mut save_warnings = &mut Vec::new();
-    doc.save_writer(&mut writer, &PdfSaveOptions::default(), &mut save_warnings);
-    if save_warnings.len() > 0 {
-        //println!("Warnings: {:?}", save_warnings);
-        //println!("There might had been problems saving PDF to: {}", args.output.display());
+    if let Some(Command::Extract { input, output }) = &args.command {
+        let pdf = fs::read(input)
+            .with_context(|| format!("Could not read input file: {}", input.display()))?;
+        write_output(output, extract_invoice_json(&pdf)?.as_bytes())?;
+        println!("Invoice JSON extracted successfully: {}", output.display());
+        return Ok(());
+    }
+    let input = args.input.as_deref().context("Input file is required")?;
+    let output = args.output.as_deref().context("Output file is required")?;
+    let (invoice, source_json) = read_invoice(input)?;
+
+    let template = match &args.template {
+        Some(path) => read_template(path)?,
+        None => Template::default()
+    };
+    let theme = args.theme.as_deref().map(read_theme).transpose()?;
+    let options = RenderOptions {
+        epc_qr_code: args.epc_qr_code.map(QrCodePosition::from),
+        bank_barcode: args.bank_barcode,
+        payment_slip: args.payment_slip.map(PaymentSlip::from),
+        page_size: args.page_size,
+        orientation: args.orientation.into(),
+        margins: args.margins.unwrap_or_default(),
+        template,
+        theme,
+        factur_x: args.factur_x.then_some(FacturXProfile::En16931),
+        source_json,
+    };
+    if matches!(args.format, OutputFormat::Pdf | OutputFormat::Html | OutputFormat::Svg) {
+        for skipped in options.skipped_payment_codes(&invoice) {
+            eprintln!("Warning: {}", skipped);
+        }
+    }
+    match args.format {
+        OutputFormat::Pdf => {
+            let pdf = render_pdf(&invoice, &options)?;
+            write_output(output, &pdf)?;
+            println!("Invoice PDF generated successfully: {}", output.display());
+            if args.peppol_xml {
+                let xml_output = output.with_extension("xml");
+                write_output(&xml_output, peppol_xml(&invoice)?.as_bytes())?;
+                println!("PEPPOL XML generated successfully: {}", xml_output.display());
+            }
+        }
+        OutputFormat::Html => {
+            let html = render_html(&invoice, &options)?;
+            write_output(output, html.as_bytes())?;
+            println!("Invoice HTML generated successfully: {}", output.display());
+        }
+        OutputFormat::Svg => {
+            for (index, svg) in render_svg(&invoice, &options)?.iter().enumerate() {
+                let page_output = page_path(output, index);
+                write_output(&page_output, svg.as_bytes())?;
+                println!("Invoice SVG generated successfully: {}", page_output.display());
+            }
+        }
+        OutputFormat::Text | OutputFormat::Markdown => {
+            let format = if matches!(args.format, OutputFormat::Text) { TextFormat::Plain } else { TextFormat::Markdown };
+            let text = render_text(&invoice, format)?;
+            write_output(output, text.as_bytes())?;
+            println!("Invoice summary generated successfully: {}", output.display());
+        }
+        OutputFormat::Finvoice => {
+            let xml = finvoice_xml(&invoice)?;
+            write_output(output, xml.as_bytes())?;
+            println!("Finvoice XML generated successfully: {}", output.display());
+        }
+        OutputFormat::XrechnungUbl | OutputFormat::XrechnungCii => {
+            let syntax = if matches!(args.format, OutputFormat::XrechnungUbl) { XRechnungSyntax::Ubl } else { XRechnungSyntax::Cii };
+            let xml = xrechnung_xml(&invoice, syntax)?;
+            write_output(output, xml.as_bytes())?;
+            println!("XRechnung XML generated successfully: {}", output.display());
+        }
     }
-    println!("Invoice PDF generated successfully: {}", args.output.display());
     Ok(())
-}
\ No newline at end of file
+}
+
+fn parse_page_size(value: &str) -> Result<PageSize, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "a4" => Ok(PageSize::A4),
+        "a5" => Ok(PageSize::A5),
+        "letter" => Ok(PageSize::Letter),
+        size => {
+            let dimensions = size.split_once('x')
+                .and_then(|(width, height)| width.trim().parse::<f32>().ok().zip(height.trim().parse::<f32>().ok()))
+                .filter(|&(width, height)| width > 0.0 && height > 0.0);
+            match dimensions {
+                Some((width, height)) => Ok(PageSize::Custom { width, height }),
+                None => Err("expected a4, a5, letter or WIDTHxHEIGHT in mm".to_owned())
+            }
+        }
+    }
+}
+
+fn parse_margins(value: &str) -> Result<Margins, String> {
+    let margins: Vec<f32> = value.split(',').map(|margin| margin.trim().parse::<f32>())
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    match margins[..] {
+        [top, right, bottom, left] if margins.iter().all(|margin| *margin >= 0.0) => Ok(Margins { top, right, bottom, left }),
+        _ => Err("expected four non-negative margins in mm as TOP,RIGHT,BOTTOM,LEFT".to_owned())
+    }
+}
+
+/// The invoice and, for JSON input, the JSON as it was read to embed it into the PDF
+fn read_invoice(input: &Path) -> Result<(Invoice, Option<Vec<u8>>), Error> {
+    let raw_invoice = fs::read_to_string(input)
+        .with_context(|| format!("Could not read input file: {}", input.display()))?;
+    let is_xml = input.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("xml"));
+    if !is_xml {
+        let invoice = serde_json::from_str(&raw_invoice).context("Could not parse invoice JSON data")?;
+        return Ok((invoice, Some(raw_invoice.into_bytes())));
+    }
+    let imported = import_xml(&raw_invoice).context("Could not import the XML invoice")?;
+    for loss in &imported.losses {
+        eprintln!("Warning: {}", loss);
+    }
+    Ok((imported.invoice, None))
+}
+
+fn read_template(path: &Path) -> Result<Template, Error> {
+    let raw_template = fs::read_to_string(path)
+        .with_context(|| format!("Could not read template file: {}", path.display()))?;
+    Template::from_json(&raw_template)
+        .with_context(|| format!("Could not use template file: {}", path.display()))
+}
+
+fn read_theme(path: &Path) -> Result<Theme, Error> {
+    let raw_theme = fs::read_to_string(path)
+        .with_context(|| format!("Could not read theme file: {}", path.display()))?;
+    Theme::from_json(&raw_theme)
+        .with_context(|| format!("Could not use theme file: {}", path.display()))
+}
+
+fn write_output(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    fs::write(path, contents)
+        .with_context(|| format!("Could not write output file: {}", path.display()))
+}
+
+/// Output path of the page with the given index, the first page is written to the output path itself
+fn page_path(output: &Path, index: usize) -> PathBuf {
+    if index == 0 {
+        return output.to_path_buf();
+    }
+    let stem = output.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let file_name = match output.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, index + 1, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, index + 1)
+    };
+    output.with_file_name(file_name)
+}
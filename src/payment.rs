@@ -0,0 +1,5 @@
+pub mod bank_barcode;
+pub mod epc;
+pub mod iban;
+pub mod reference;
+pub mod swiss_qr_bill;
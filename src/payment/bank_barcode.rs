@@ -0,0 +1,115 @@
+use anyhow::Error;
+use bigdecimal::{BigDecimal, RoundingMode};
+use crate::date::Date;
+use crate::invoice::Invoice;
+use crate::payment::iban::validate_iban;
+use crate::payment::reference::{is_rf_reference, validate_finnish_reference, validate_rf_reference};
+
+/// Builds the 54 digit Finnish bank barcode ("virtuaaliviivakoodi") as specified by Finanssiala:
+/// version 4 for national reference numbers and version 5 for RF creditor references.
+/// Amounts over 999 999,99 and missing due dates are encoded as zeros.
+pub fn bank_barcode(iban: &str, amount: &BigDecimal, reference: &str, due_date: Option<&Date>) -> Result<String, Error> {
+    let iban = validate_iban(iban)?;
+    if !iban.starts_with("FI") {
+        return Err(Error::msg(format!("Bank barcode can only be created for Finnish IBANs: {}", iban)));
+    }
+    let account = &iban[2..];
+    let amount = amount.with_scale_round(2, RoundingMode::HalfUp);
+    if amount < BigDecimal::from(0) {
+        return Err(Error::msg(format!("Bank barcode amount cannot be negative: {}", amount)));
+    }
+    let (euros, cents) = if amount >= BigDecimal::from(1_000_000) {
+        ("000000".to_owned(), "00".to_owned())
+    } else {
+        let digits = amount.as_bigint_and_exponent().0.to_string();
+        let digits = format!("{:0>8}", digits);
+        (digits[..6].to_owned(), digits[6..].to_owned())
+    };
+    let due_date = due_date.map(|date| date.to_yymmdd()).unwrap_or_else(|| "000000".to_owned());
+    let barcode = if is_rf_reference(reference) {
+        let reference = validate_rf_reference(reference)?;
+        let (check_digits, body) = (&reference[2..4], &reference[4..]);
+        if !body.chars().all(|c| c.is_ascii_digit()) {
+            return Err(Error::msg(format!("Bank barcode supports only numeric RF creditor references: {}", reference)));
+        }
+        format!("5{}{}{}{}{:0>21}{}", account, euros, cents, check_digits, body, due_date)
+    } else {
+        let reference = validate_finnish_reference(reference)?;
+        format!("4{}{}{}000{:0>20}{}", account, euros, cents, reference, due_date)
+    };
+    Ok(barcode)
+}
+
+pub fn bank_barcode_from_invoice(invoice: &Invoice) -> Result<String, Error> {
+    if invoice.currency != "EUR" {
+        return Err(Error::msg(format!("Bank barcode only supports EUR payments, got: {}", invoice.currency)));
+    }
+    let reference = invoice.reference_id.as_ref()
+        .ok_or_else(|| Error::msg("Bank barcode requires a reference number"))?;
+    let due_date = Date::parse(&invoice.due_date)?;
+    bank_barcode(&invoice.bank_details.account_number, &invoice.totals().total_price, reference, Some(&due_date))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn barcode(iban: &str, amount: &str, reference: &str, due_date: Option<&str>) -> String {
+        let due_date = due_date.map(|date| Date::parse(date).unwrap());
+        bank_barcode(iban, &BigDecimal::from_str(amount).unwrap(), reference, due_date.as_ref()).unwrap()
+    }
+
+    // Test vectors from the Finanssiala "Pankkiviivakoodi-opas" specification
+
+    #[test]
+    fn should_build_version_4_barcodes() {
+        assert_eq!(
+            barcode("FI79 4405 2020 0360 82", "4883.15", "86851 62596 19897", Some("12.6.2010")),
+            "479440520200360820048831500000000868516259619897100612"
+        );
+        assert_eq!(
+            barcode("FI58 1017 1000 0001 22", "482.99", "55958 22432 94671", Some("31.1.2012")),
+            "458101710000001220004829900000000559582243294671120131"
+        );
+        assert_eq!(
+            barcode("FI73 3131 3001 0000 58", "0.00", "86851 62596 19897", None),
+            "473313130010000580000000000000000868516259619897000000"
+        );
+    }
+
+    #[test]
+    fn should_build_version_5_barcodes() {
+        assert_eq!(
+            barcode("FI79 4405 2020 0360 82", "4883.15", "RF09 8685 1625 9619 897", Some("12.6.2010")),
+            "579440520200360820048831509000000868516259619897100612"
+        );
+        assert_eq!(
+            barcode("FI58 1017 1000 0001 22", "482.99", "RF06 5595 8224 3294 671", Some("31.1.2012")),
+            "558101710000001220004829906000000559582243294671120131"
+        );
+    }
+
+    #[test]
+    fn should_encode_too_large_amounts_as_zeros() {
+        assert_eq!(
+            barcode("FI79 4405 2020 0360 82", "1000000.00", "86851 62596 19897", Some("12.6.2010")),
+            "479440520200360820000000000000000868516259619897100612"
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_input() {
+        let amount = BigDecimal::from(10);
+        assert!(bank_barcode("DE89 3704 0044 0532 0130 00", &amount, "86851 62596 19897", None).is_err());
+        assert!(bank_barcode("FI79 4405 2020 0360 82", &amount, "86851 62596 19898", None).is_err());
+        assert!(bank_barcode("FI79 4405 2020 0360 82", &amount, "RF10 8685 1625 9619 897", None).is_err());
+    }
+
+    #[test]
+    fn should_always_be_54_digits_long() {
+        let barcode = barcode("FI58 1017 1000 0001 22", "1.5", "1232", Some("01.01.2025"));
+        assert_eq!(barcode.len(), 54);
+        assert!(barcode.chars().all(|c| c.is_ascii_digit()));
+    }
+}
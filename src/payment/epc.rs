@@ -0,0 +1,110 @@
+use anyhow::Error;
+use bigdecimal::{BigDecimal, RoundingMode};
+use crate::invoice::Invoice;
+use crate::payment::iban::validate_iban;
+
+const MAX_BENEFICIARY_NAME_LENGTH: usize = 70;
+const MAX_STRUCTURED_REFERENCE_LENGTH: usize = 35;
+const MAX_UNSTRUCTURED_REMITTANCE_LENGTH: usize = 140;
+
+#[derive(Debug, PartialEq)]
+pub enum Remittance {
+    Structured(String),
+    Unstructured(String)
+}
+
+/// SEPA credit transfer data encoded into an EPC069-12 ("GiroCode") QR code
+#[derive(Debug)]
+pub struct EpcPayment {
+    pub bic: String,
+    pub beneficiary_name: String,
+    pub iban: String,
+    pub amount: BigDecimal,
+    pub remittance: Remittance
+}
+
+impl EpcPayment {
+    pub fn from_invoice(invoice: &Invoice) -> Result<EpcPayment, Error> {
+        if invoice.currency != "EUR" {
+            return Err(Error::msg(format!("EPC QR code only supports EUR payments, got: {}", invoice.currency)));
+        }
+        let beneficiary_name = invoice.billed_by.name.trim().to_owned();
+        if beneficiary_name.is_empty() || beneficiary_name.chars().count() > MAX_BENEFICIARY_NAME_LENGTH {
+            return Err(Error::msg(format!("EPC QR code beneficiary name should be 1-{} characters long: {}", MAX_BENEFICIARY_NAME_LENGTH, beneficiary_name)));
+        }
+        let bic = invoice.bank_details.bic_code.trim().to_ascii_uppercase();
+        if !bic.is_empty() && bic.len() != 8 && bic.len() != 11 {
+            return Err(Error::msg(format!("Invalid BIC: {}", bic)));
+        }
+        let amount = invoice.totals().total_price.with_scale_round(2, RoundingMode::HalfUp);
+        if amount <= BigDecimal::from(0) || amount >= BigDecimal::from(1_000_000_000) {
+            return Err(Error::msg(format!("EPC QR code amount should be between 0.01 and 999999999.99, got: {}", amount)));
+        }
+        let remittance = match invoice.reference_id.as_ref() {
+            Some(reference_id) => {
+                let reference: String = reference_id.chars().filter(|c| !c.is_whitespace()).collect();
+                if reference.len() > MAX_STRUCTURED_REFERENCE_LENGTH {
+                    return Err(Error::msg(format!("EPC QR code reference should be at most {} characters long: {}", MAX_STRUCTURED_REFERENCE_LENGTH, reference)));
+                }
+                Remittance::Structured(reference)
+            },
+            None => Remittance::Unstructured(invoice.invoice_number.chars().take(MAX_UNSTRUCTURED_REMITTANCE_LENGTH).collect())
+        };
+        Ok(EpcPayment {
+            bic,
+            beneficiary_name,
+            iban: validate_iban(&invoice.bank_details.account_number)?,
+            amount,
+            remittance
+        })
+    }
+
+    /// Serializes the payment as version 002 of the EPC069-12 payload, UTF-8 encoded
+    pub fn to_payload(&self) -> String {
+        let (structured, unstructured) = match &self.remittance {
+            Remittance::Structured(reference) => (reference.as_str(), ""),
+            Remittance::Unstructured(text) => ("", text.as_str())
+        };
+        [
+            "BCD",
+            "002",
+            "1",
+            "SCT",
+            self.bic.as_str(),
+            self.beneficiary_name.as_str(),
+            self.iban.as_str(),
+            &format!("EUR{}", self.amount),
+            "",
+            structured,
+            unstructured
+        ].join("\n").trim_end().to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::test_fixtures::invoice_with;
+
+    #[test]
+    fn should_build_payload_with_structured_reference() {
+        let invoice = invoice_with(json!({ "invoice_lines": [{ "name": "Work", "count": 1, "price": 7500 }, { "name": "Travel", "count": 1, "price": "120.5" }] }));
+        let payment = EpcPayment::from_invoice(&invoice).unwrap();
+        assert_eq!(payment.to_payload(), "BCD\n002\n1\nSCT\nNDEAFIHH\nExample Company Ltd\nFI2112345600000785\nEUR7620.50\n\nRF18539007547034");
+    }
+
+    #[test]
+    fn should_use_invoice_number_as_unstructured_remittance_without_reference() {
+        let invoice = invoice_with(json!({ "reference_id": null, "invoice_lines": [{ "name": "Work", "count": 1, "price": 7500 }, { "name": "Travel", "count": 1, "price": "120.5" }] }));
+        let payment = EpcPayment::from_invoice(&invoice).unwrap();
+        assert_eq!(payment.remittance, Remittance::Unstructured("2025-0001".to_owned()));
+        assert_eq!(payment.to_payload(), "BCD\n002\n1\nSCT\nNDEAFIHH\nExample Company Ltd\nFI2112345600000785\nEUR7620.50\n\n\n2025-0001");
+    }
+
+    #[test]
+    fn should_reject_non_euro_invoices() {
+        let invoice = invoice_with(json!({ "currency": "USD" }));
+        assert!(EpcPayment::from_invoice(&invoice).is_err());
+    }
+}
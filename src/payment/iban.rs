@@ -0,0 +1,63 @@
+use anyhow::Error;
+
+pub fn normalize_iban(iban: &str) -> String {
+    iban.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+pub fn validate_iban(iban: &str) -> Result<String, Error> {
+    let iban = normalize_iban(iban);
+    if iban.len() < 15 || iban.len() > 34 {
+        return Err(Error::msg(format!("Invalid IBAN length: {}", iban)));
+    }
+    if !iban.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(Error::msg(format!("IBAN contains invalid characters: {}", iban)));
+    }
+    if !iban[0..2].chars().all(|c| c.is_ascii_alphabetic()) || !iban[2..4].chars().all(|c| c.is_ascii_digit()) {
+        return Err(Error::msg(format!("IBAN should start with a country code and check digits: {}", iban)));
+    }
+    let rearranged = format!("{}{}", &iban[4..], &iban[0..4]);
+    if mod_97(&rearranged) != 1 {
+        return Err(Error::msg(format!("Invalid IBAN checksum: {}", iban)));
+    }
+    Ok(iban)
+}
+
+/// Computes the ISO 7064 MOD 97-10 remainder, letters count as 10 (A) to 35 (Z)
+pub(crate) fn mod_97(value: &str) -> u32 {
+    value.chars().fold(0, |remainder, c| {
+        let digit = c.to_digit(36).unwrap_or(0);
+        if digit >= 10 {
+            (remainder * 100 + digit) % 97
+        } else {
+            (remainder * 10 + digit) % 97
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_normalize_iban() {
+        assert_eq!(normalize_iban("fi21 1234 5600 0007 85"), "FI2112345600000785");
+    }
+
+    #[test]
+    fn should_accept_valid_ibans() {
+        assert_eq!(validate_iban("FI21 1234 5600 0007 85").unwrap(), "FI2112345600000785");
+        assert_eq!(validate_iban("DE89 3704 0044 0532 0130 00").unwrap(), "DE89370400440532013000");
+        assert_eq!(validate_iban("CH9300762011623852957").unwrap(), "CH9300762011623852957");
+    }
+
+    #[test]
+    fn should_reject_invalid_ibans() {
+        assert!(validate_iban("FI12 3456 7890 1234 56").is_err());
+        assert!(validate_iban("FI21").is_err());
+        assert!(validate_iban("2112345600000785FI").is_err());
+        assert!(validate_iban("FI21 1234 5600 0007 8!").is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::Error;
+use crate::payment::iban::mod_97;
+
+pub fn normalize_reference(reference: &str) -> String {
+    reference.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+pub fn is_rf_reference(reference: &str) -> bool {
+    normalize_reference(reference).starts_with("RF")
+}
+
+/// Validates a Finnish national reference number (4-20 digits, last one is a 7-3-1 check digit)
+pub fn validate_finnish_reference(reference: &str) -> Result<String, Error> {
+    let reference = normalize_reference(reference);
+    if reference.len() < 4 || reference.len() > 20 || !reference.chars().all(|c| c.is_ascii_digit()) {
+        return Err(Error::msg(format!("Finnish reference number should consist of 4-20 digits: {}", reference)));
+    }
+    let (body, check_digit) = reference.split_at(reference.len() - 1);
+    let weighted_sum: u32 = body.chars().rev()
+        .zip([7, 3, 1].iter().cycle())
+        .map(|(c, weight)| c.to_digit(10).unwrap() * weight)
+        .sum();
+    if (10 - weighted_sum % 10) % 10 != check_digit.parse::<u32>().unwrap() {
+        return Err(Error::msg(format!("Invalid Finnish reference number check digit: {}", reference)));
+    }
+    Ok(reference)
+}
+
+/// Validates an ISO 11649 creditor reference ("RF" + 2 check digits + up to 21 characters)
+pub fn validate_rf_reference(reference: &str) -> Result<String, Error> {
+    let reference = normalize_reference(reference);
+    if !reference.starts_with("RF") || reference.len() < 5 || reference.len() > 25 || !reference.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(Error::msg(format!("Invalid RF creditor reference: {}", reference)));
+    }
+    let rearranged = format!("{}{}", &reference[4..], &reference[0..4]);
+    if mod_97(&rearranged) != 1 {
+        return Err(Error::msg(format!("Invalid RF creditor reference checksum: {}", reference)));
+    }
+    Ok(reference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_validate_finnish_references() {
+        assert_eq!(validate_finnish_reference("86851 62596 19897").unwrap(), "868516259619897");
+        assert_eq!(validate_finnish_reference("1232").unwrap(), "1232");
+        assert!(validate_finnish_reference("86851 62596 19898").is_err());
+        assert!(validate_finnish_reference("123").is_err());
+        assert!(validate_finnish_reference("RF09 8685 1625 9619 897").is_err());
+    }
+
+    #[test]
+    fn should_validate_rf_references() {
+        assert_eq!(validate_rf_reference("RF09 8685 1625 9619 897").unwrap(), "RF09868516259619897");
+        assert_eq!(validate_rf_reference("rf18 5390 0754 7034").unwrap(), "RF18539007547034");
+        assert!(validate_rf_reference("RF10 8685 1625 9619 897").is_err());
+        assert!(validate_rf_reference("868516259619897").is_err());
+    }
+}
@@ -0,0 +1,213 @@
+use anyhow::Error;
+use bigdecimal::{BigDecimal, RoundingMode};
+use crate::invoice::{BillingInformation, Invoice};
+use crate::payment::iban::validate_iban;
+use crate::payment::reference::{normalize_reference, validate_rf_reference};
+
+const MAX_NAME_LENGTH: usize = 70;
+const MAX_UNSTRUCTURED_MESSAGE_LENGTH: usize = 140;
+
+#[derive(Debug, PartialEq)]
+pub enum SwissReference {
+    /// QR reference, 27 digits, only allowed with a QR-IBAN
+    Qrr(String),
+    /// ISO 11649 creditor reference, only allowed with a regular IBAN
+    Scor(String),
+    None
+}
+
+/// Structured ("S") address as required by the Swiss Payment Standards
+#[derive(Debug, PartialEq)]
+pub struct SwissAddress {
+    pub name: String,
+    pub street: String,
+    pub postal_code: String,
+    pub town: String,
+    pub country_code: String
+}
+
+/// Payment data of the Swiss QR-bill, see "Swiss Implementation Guidelines for the QR-bill"
+#[derive(Debug)]
+pub struct SwissQrBill {
+    pub iban: String,
+    pub creditor: SwissAddress,
+    pub amount: BigDecimal,
+    pub currency: String,
+    pub debtor: Option<SwissAddress>,
+    pub reference: SwissReference,
+    pub message: String
+}
+
+impl SwissQrBill {
+    pub fn from_invoice(invoice: &Invoice) -> Result<SwissQrBill, Error> {
+        if invoice.currency != "CHF" && invoice.currency != "EUR" {
+            return Err(Error::msg(format!("Swiss QR-bill only supports CHF and EUR payments, got: {}", invoice.currency)));
+        }
+        let iban = validate_iban(&invoice.bank_details.account_number)?;
+        if !iban.starts_with("CH") && !iban.starts_with("LI") {
+            return Err(Error::msg(format!("Swiss QR-bill requires a Swiss or Liechtenstein IBAN: {}", iban)));
+        }
+        let reference = match invoice.reference_id.as_ref() {
+            Some(reference) if is_qr_iban(&iban) => SwissReference::Qrr(validate_qr_reference(reference)?),
+            Some(reference) => SwissReference::Scor(validate_rf_reference(reference)
+                .map_err(|e| e.context("Swiss QR-bill with a regular IBAN requires an RF creditor reference"))?),
+            None if is_qr_iban(&iban) => return Err(Error::msg(format!("Swiss QR-bill with a QR-IBAN requires a QR reference: {}", iban))),
+            None => SwissReference::None
+        };
+        let amount = invoice.totals().total_price.with_scale_round(2, RoundingMode::HalfUp);
+        if amount <= BigDecimal::from(0) || amount >= BigDecimal::from(1_000_000_000) {
+            return Err(Error::msg(format!("Swiss QR-bill amount should be between 0.01 and 999999999.99, got: {}", amount)));
+        }
+        Ok(SwissQrBill {
+            iban,
+            creditor: SwissAddress::from_billing_information(&invoice.billed_by)
+                .ok_or_else(|| Error::msg("Swiss QR-bill requires the postal code, town and country code of the seller"))?,
+            amount,
+            currency: invoice.currency.clone(),
+            debtor: SwissAddress::from_billing_information(&invoice.billed_to),
+            reference,
+            message: invoice.invoice_number.chars().take(MAX_UNSTRUCTURED_MESSAGE_LENGTH).collect()
+        })
+    }
+
+    /// Serializes the payment as the version 2.0 QR code payload
+    pub fn to_payload(&self) -> String {
+        let mut lines: Vec<String> = vec![
+            "SPC".to_owned(),
+            "0200".to_owned(),
+            "1".to_owned(),
+            self.iban.clone()
+        ];
+        lines.extend(address_lines(Some(&self.creditor)));
+        // Ultimate creditor, reserved for future use
+        lines.extend(address_lines(None));
+        lines.push(self.amount.to_string());
+        lines.push(self.currency.clone());
+        lines.extend(address_lines(self.debtor.as_ref()));
+        let (reference_type, reference) = match &self.reference {
+            SwissReference::Qrr(reference) => ("QRR", reference.as_str()),
+            SwissReference::Scor(reference) => ("SCOR", reference.as_str()),
+            SwissReference::None => ("NON", "")
+        };
+        lines.push(reference_type.to_owned());
+        lines.push(reference.to_owned());
+        lines.push(self.message.clone());
+        lines.push("EPD".to_owned());
+        lines.join("\n")
+    }
+}
+
+impl SwissAddress {
+    fn from_billing_information(billing_information: &BillingInformation) -> Option<SwissAddress> {
+        Some(SwissAddress {
+            name: billing_information.name.chars().take(MAX_NAME_LENGTH).collect(),
+            street: billing_information.address_line_1.clone(),
+            postal_code: billing_information.postal_code.clone()?,
+            town: billing_information.town.clone()?,
+            country_code: billing_information.country_code.clone()?.to_ascii_uppercase()
+        })
+    }
+}
+
+fn address_lines(address: Option<&SwissAddress>) -> Vec<String> {
+    match address {
+        Some(address) => vec![
+            "S".to_owned(),
+            address.name.clone(),
+            address.street.clone(),
+            "".to_owned(),
+            address.postal_code.clone(),
+            address.town.clone(),
+            address.country_code.clone()
+        ],
+        None => vec!["".to_owned(); 7]
+    }
+}
+
+/// QR-IBANs have an institution identification (QR-IID) in the range 30000-31999
+pub fn is_qr_iban(iban: &str) -> bool {
+    iban.get(4..9)
+        .and_then(|iid| iid.parse::<u32>().ok())
+        .map(|iid| (30000..=31999).contains(&iid))
+        .unwrap_or(false)
+}
+
+/// Validates a QR reference: 27 digits, the last one is a recursive modulo 10 check digit
+pub fn validate_qr_reference(reference: &str) -> Result<String, Error> {
+    let reference = normalize_reference(reference);
+    if reference.len() != 27 || !reference.chars().all(|c| c.is_ascii_digit()) {
+        return Err(Error::msg(format!("QR reference should consist of 27 digits: {}", reference)));
+    }
+    let (body, check_digit) = reference.split_at(26);
+    if modulo_10_recursive(body) != check_digit.parse::<u32>().unwrap() {
+        return Err(Error::msg(format!("Invalid QR reference check digit: {}", reference)));
+    }
+    Ok(reference)
+}
+
+fn modulo_10_recursive(digits: &str) -> u32 {
+    const TABLE: [u32; 10] = [0, 9, 4, 6, 8, 2, 7, 1, 3, 5];
+    let carry = digits.chars().fold(0, |carry, c| TABLE[((carry + c.to_digit(10).unwrap()) % 10) as usize]);
+    (10 - carry) % 10
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::test_fixtures::invoice_with;
+
+    fn swiss_invoice(account_number: &str, reference_id: Option<&str>) -> Invoice {
+        invoice_with(json!({ "currency": "CHF", "bank_details": { "account_number": account_number }, "reference_id": reference_id }))
+    }
+
+    #[test]
+    fn should_detect_qr_ibans() {
+        assert!(is_qr_iban("CH4431999123000889012"));
+        assert!(!is_qr_iban("CH9300762011623852957"));
+    }
+
+    #[test]
+    fn should_validate_qr_references() {
+        assert_eq!(validate_qr_reference("21 00000 00003 13947 14300 09017").unwrap(), "210000000003139471430009017");
+        assert!(validate_qr_reference("21 00000 00003 13947 14300 09018").is_err());
+        assert!(validate_qr_reference("RF18 5390 0754 7034").is_err());
+    }
+
+    #[test]
+    fn should_build_payload_with_qr_reference() {
+        let invoice = swiss_invoice("CH44 3199 9123 0008 8901 2", Some("210000000003139471430009017"));
+        let bill = SwissQrBill::from_invoice(&invoice).unwrap();
+        assert_eq!(bill.to_payload(), [
+            "SPC", "0200", "1", "CH4431999123000889012",
+            "S", "Example Company Ltd", "123 Business Street", "", "00100", "Helsinki", "FI",
+            "", "", "", "", "", "", "",
+            "251.00", "CHF",
+            "S", "Client GmbH", "Hauptstrasse 1", "", "10115", "Berlin", "DE",
+            "QRR", "210000000003139471430009017", "2025-0001", "EPD"
+        ].join("\n"));
+    }
+
+    #[test]
+    fn should_use_creditor_reference_with_regular_iban() {
+        let invoice = swiss_invoice("CH93 0076 2011 6238 5295 7", Some("RF18 5390 0754 7034"));
+        let bill = SwissQrBill::from_invoice(&invoice).unwrap();
+        assert_eq!(bill.reference, SwissReference::Scor("RF18539007547034".to_owned()));
+    }
+
+    #[test]
+    fn should_reject_mismatching_iban_and_reference() {
+        let qr_iban_without_reference = swiss_invoice("CH44 3199 9123 0008 8901 2", None);
+        assert!(SwissQrBill::from_invoice(&qr_iban_without_reference).is_err());
+        let qr_iban_with_creditor_reference = swiss_invoice("CH44 3199 9123 0008 8901 2", Some("RF18 5390 0754 7034"));
+        assert!(SwissQrBill::from_invoice(&qr_iban_with_creditor_reference).is_err());
+        let iban_with_qr_reference = swiss_invoice("CH93 0076 2011 6238 5295 7", Some("210000000003139471430009017"));
+        assert!(SwissQrBill::from_invoice(&iban_with_qr_reference).is_err());
+    }
+
+    #[test]
+    fn should_reject_non_swiss_ibans() {
+        let invoice = swiss_invoice("FI21 1234 5600 0007 85", None);
+        assert!(SwissQrBill::from_invoice(&invoice).is_err());
+    }
+}
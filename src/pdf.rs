@@ -0,0 +1,265 @@
+use anyhow::Error;
+use lopdf::{decode_text_string, text_string, Dictionary, Document, Object, Stream};
+use printpdf::{PdfDocument, PdfSaveOptions};
+
+mod pdf_a;
+
+/// How an embedded file relates to the PDF document (the /AFRelationship of PDF/A-3)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttachmentRelationship {
+    /// The file the document was created from
+    Source,
+    /// Data used to derive the document content, e.g. a table of values
+    Data,
+    /// An equivalent representation of the document content, e.g. a structured invoice
+    Alternative
+}
+
+impl AttachmentRelationship {
+    fn name(&self) -> &'static str {
+        match self {
+            AttachmentRelationship::Source => "Source",
+            AttachmentRelationship::Data => "Data",
+            AttachmentRelationship::Alternative => "Alternative"
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PdfAttachment {
+    pub file_name: String,
+    pub mime_type: String,
+    pub description: String,
+    pub relationship: AttachmentRelationship,
+    pub content: Vec<u8>
+}
+
+/// Custom XMP properties, described with a PDF/A extension schema so that PDF/A validators accept them
+#[derive(Debug, Clone)]
+pub struct XmpExtension {
+    pub schema: String,
+    pub namespace_uri: String,
+    pub prefix: String,
+    pub properties: Vec<XmpProperty>
+}
+
+#[derive(Debug, Clone)]
+pub struct XmpProperty {
+    pub name: String,
+    pub description: String,
+    pub value: String
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PdfOutputOptions {
+    /// Writes the document as PDF/A-3b: sRGB output intent, XMP metadata and print flags on annotations
+    pub pdf_a3: bool,
+    pub attachments: Vec<PdfAttachment>,
+    /// Written into the XMP metadata of PDF/A documents
+    pub xmp_extensions: Vec<XmpExtension>
+}
+
+/// Saves the document into bytes, moving the link annotations which printpdf writes into
+/// the page resources to the page dictionary where PDF viewers look for them
+pub fn save_pdf(doc: &PdfDocument) -> Result<Vec<u8>, Error> {
+    save_pdf_with_options(doc, &PdfOutputOptions::default())
+}
+
+pub fn save_pdf_with_options(doc: &PdfDocument, options: &PdfOutputOptions) -> Result<Vec<u8>, Error> {
+    let mut warnings = Vec::new();
+    let bytes = doc.save(&PdfSaveOptions::default(), &mut warnings);
+    let mut pdf = Document::load_mem(&bytes)
+        .map_err(|e| Error::msg(format!("Could not read the generated PDF: {}", e)))?;
+    move_annotations_to_pages(&mut pdf)?;
+    embed_attachments(&mut pdf, &options.attachments)?;
+    if options.pdf_a3 {
+        pdf_a::convert_to_pdf_a3(&mut pdf, &options.xmp_extensions)?;
+    }
+    let mut output = Vec::new();
+    pdf.save_to(&mut output)?;
+    Ok(output)
+}
+
+fn move_annotations_to_pages(pdf: &mut Document) -> Result<(), Error> {
+    for page_id in pdf.get_pages().into_values() {
+        let resources_id = match pdf.get_dictionary(page_id)?.get(b"Resources").and_then(Object::as_reference) {
+            Ok(resources_id) => resources_id,
+            Err(_) => continue
+        };
+        match pdf.get_dictionary_mut(resources_id)?.remove(b"Annots") {
+            Some(Object::Array(annotations)) if !annotations.is_empty() => {
+                pdf.get_dictionary_mut(page_id)?.set("Annots", Object::Array(annotations));
+            },
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// The document information dictionary referenced from the trailer
+pub(crate) fn info_dictionary(pdf: &Document) -> Option<&Dictionary> {
+    let info = pdf.trailer.get(b"Info").ok()?;
+    match info {
+        Object::Reference(info_id) => pdf.get_dictionary(*info_id).ok(),
+        Object::Dictionary(info) => Some(info),
+        _ => None
+    }
+}
+
+/// Adds the files to the embedded files name tree and to the associated files (/AF) of the document
+fn embed_attachments(pdf: &mut Document, attachments: &[PdfAttachment]) -> Result<(), Error> {
+    if attachments.is_empty() {
+        return Ok(());
+    }
+    let modification_date = info_dictionary(pdf).and_then(|info| info.get(b"ModDate").ok()).cloned();
+    let mut attachments: Vec<&PdfAttachment> = attachments.iter().collect();
+    attachments.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    let mut names = Vec::new();
+    let mut file_specs = Vec::new();
+    for attachment in attachments {
+        let mut params = Dictionary::from_iter(vec![("Size", Object::Integer(attachment.content.len() as i64))]);
+        if let Some(modification_date) = modification_date.as_ref() {
+            params.set("ModDate", modification_date.clone());
+        }
+        let file_stream = Stream::new(
+            Dictionary::from_iter(vec![
+                ("Type", Object::Name(b"EmbeddedFile".to_vec())),
+                ("Subtype", Object::Name(attachment.mime_type.as_bytes().to_vec())),
+                ("Params", Object::Dictionary(params))
+            ]),
+            attachment.content.clone()
+        );
+        let file_stream_id = pdf.add_object(file_stream);
+        let file_spec_id = pdf.add_object(Dictionary::from_iter(vec![
+            ("Type", Object::Name(b"Filespec".to_vec())),
+            ("F", Object::string_literal(attachment.file_name.as_str())),
+            ("UF", text_string(&attachment.file_name)),
+            ("Desc", text_string(&attachment.description)),
+            ("AFRelationship", Object::Name(attachment.relationship.name().as_bytes().to_vec())),
+            ("EF", Object::Dictionary(Dictionary::from_iter(vec![
+                ("F", Object::Reference(file_stream_id)),
+                ("UF", Object::Reference(file_stream_id))
+            ])))
+        ]));
+        names.push(Object::string_literal(attachment.file_name.as_str()));
+        names.push(Object::Reference(file_spec_id));
+        file_specs.push(Object::Reference(file_spec_id));
+    }
+    let catalog = pdf.catalog_mut()?;
+    catalog.set("Names", Dictionary::from_iter(vec![
+        ("EmbeddedFiles", Object::Dictionary(Dictionary::from_iter(vec![("Names", Object::Array(names))])))
+    ]));
+    catalog.set("AF", Object::Array(file_specs));
+    Ok(())
+}
+
+/// Content of the embedded file with the given name, `None` when the PDF has no such attachment
+pub fn extract_attachment(pdf: &[u8], file_name: &str) -> Result<Option<Vec<u8>>, Error> {
+    let pdf = Document::load_mem(pdf).map_err(|e| Error::msg(format!("Could not read the PDF: {}", e)))?;
+    let embedded_files = pdf.catalog()?.get(b"Names").ok()
+        .and_then(|names| resolve(&pdf, names).as_dict().ok())
+        .and_then(|names| names.get(b"EmbeddedFiles").ok())
+        .and_then(|embedded_files| resolve(&pdf, embedded_files).as_dict().ok());
+    let file_spec = match embedded_files {
+        Some(embedded_files) => find_in_name_tree(&pdf, embedded_files, file_name),
+        None => return Ok(None)
+    };
+    let file_stream = file_spec
+        .and_then(|file_spec| file_spec.get(b"EF").ok())
+        .and_then(|files| resolve(&pdf, files).as_dict().ok())
+        .and_then(|files| files.get(b"UF").or_else(|_| files.get(b"F")).ok())
+        .and_then(|file| resolve(&pdf, file).as_stream().ok());
+    Ok(file_stream.map(|stream| stream.decompressed_content().unwrap_or_else(|_| stream.content.clone())))
+}
+
+fn resolve<'a>(pdf: &'a Document, object: &'a Object) -> &'a Object {
+    match object {
+        Object::Reference(id) => pdf.get_object(*id).unwrap_or(object),
+        _ => object
+    }
+}
+
+/// Looks up a file specification from a name tree node and its kids
+fn find_in_name_tree<'a>(pdf: &'a Document, node: &'a Dictionary, file_name: &str) -> Option<&'a Dictionary> {
+    if let Ok(names) = node.get(b"Names").and_then(Object::as_array) {
+        let found = names.chunks(2).find(|pair| decode_text_string(resolve(pdf, &pair[0])).ok().as_deref() == Some(file_name));
+        if let Some([_, file_spec]) = found {
+            return resolve(pdf, file_spec).as_dict().ok();
+        }
+    }
+    node.get(b"Kids").and_then(Object::as_array).ok()?.iter()
+        .filter_map(|kid| resolve(pdf, kid).as_dict().ok())
+        .find_map(|kid| find_in_name_tree(pdf, kid, file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use printpdf::*;
+
+    fn document_with_link() -> PdfDocument {
+        let mut doc = PdfDocument::new("Links");
+        let link = LinkAnnotation::new(
+            Rect { x: Pt(10.0), y: Pt(10.0), width: Pt(50.0), height: Pt(10.0) },
+            Actions::Uri("https://example.com".to_owned()),
+            None,
+            None,
+            None
+        );
+        doc.with_pages(vec![PdfPage::new(Mm(210.0), Mm(297.0), vec![Op::LinkAnnotation { link }])]);
+        doc
+    }
+
+    #[test]
+    fn should_move_link_annotations_to_page() {
+        let pdf = Document::load_mem(&save_pdf(&document_with_link()).unwrap()).unwrap();
+        let page_id = *pdf.get_pages().values().next().unwrap();
+        let page = pdf.get_dictionary(page_id).unwrap();
+        assert_eq!(page.get(b"Annots").unwrap().as_array().unwrap().len(), 1);
+        let resources_id = page.get(b"Resources").unwrap().as_reference().unwrap();
+        assert!(pdf.get_dictionary(resources_id).unwrap().get(b"Annots").is_err());
+    }
+
+    #[test]
+    fn should_embed_attachments() {
+        let options = PdfOutputOptions {
+            attachments: vec![PdfAttachment {
+                file_name: "invoice.xml".to_owned(),
+                mime_type: "text/xml".to_owned(),
+                description: "Invoice".to_owned(),
+                relationship: AttachmentRelationship::Alternative,
+                content: b"<Invoice/>".to_vec()
+            }],
+            ..PdfOutputOptions::default()
+        };
+        let pdf = Document::load_mem(&save_pdf_with_options(&document_with_link(), &options).unwrap()).unwrap();
+        let catalog = pdf.catalog().unwrap();
+        let names = catalog.get(b"Names").unwrap().as_dict().unwrap()
+            .get(b"EmbeddedFiles").unwrap().as_dict().unwrap()
+            .get(b"Names").unwrap().as_array().unwrap();
+        assert_eq!(names[0].as_str().unwrap(), b"invoice.xml");
+        let file_spec = pdf.get_dictionary(names[1].as_reference().unwrap()).unwrap();
+        assert_eq!(file_spec.get(b"AFRelationship").unwrap().as_name().unwrap(), b"Alternative");
+        let file_id = file_spec.get(b"EF").unwrap().as_dict().unwrap().get(b"F").unwrap().as_reference().unwrap();
+        assert_eq!(pdf.get_object(file_id).unwrap().as_stream().unwrap().content, b"<Invoice/>");
+        assert_eq!(catalog.get(b"AF").unwrap().as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn should_extract_attachments() {
+        let options = PdfOutputOptions {
+            attachments: ["a.json", "b.json"].iter().map(|file_name| PdfAttachment {
+                file_name: file_name.to_string(),
+                mime_type: "application/json".to_owned(),
+                description: "Invoice".to_owned(),
+                relationship: AttachmentRelationship::Source,
+                content: format!("{{\"file\": \"{}\"}}", file_name).into_bytes()
+            }).collect(),
+            ..PdfOutputOptions::default()
+        };
+        let pdf = save_pdf_with_options(&document_with_link(), &options).unwrap();
+        assert_eq!(extract_attachment(&pdf, "b.json").unwrap().unwrap(), br#"{"file": "b.json"}"#);
+        assert_eq!(extract_attachment(&pdf, "c.json").unwrap(), None);
+        assert_eq!(extract_attachment(&save_pdf(&document_with_link()).unwrap(), "a.json").unwrap(), None);
+    }
+}
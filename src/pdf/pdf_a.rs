@@ -0,0 +1,227 @@
+use anyhow::Error;
+use lopdf::{decode_text_string, text_string, Dictionary, Document, Object, Stream};
+use quick_xml::escape::escape;
+use crate::pdf::{info_dictionary, XmpExtension};
+
+/// Compact sRGB v4 profile from the Compact ICC Profiles project (CC0)
+const SRGB_ICC_PROFILE: &[u8] = include_bytes!("sRGB-v4.icc");
+
+/// Document information entries and the XMP properties they have to match in PDF/A
+const INFO_ENTRIES: [&str; 6] = ["Title", "Author", "Subject", "Creator", "Producer", "Keywords"];
+
+/// Turns the document into PDF/A-3b: PDF 1.7 with an sRGB output intent, XMP metadata matching
+/// the document information dictionary and annotations flagged to be printed
+pub(crate) fn convert_to_pdf_a3(pdf: &mut Document, xmp_extensions: &[XmpExtension]) -> Result<(), Error> {
+    pdf.version = "1.7".to_owned();
+    let info = document_info(pdf);
+    let info_id = pdf.add_object(Dictionary::from_iter(
+        info.iter().map(|(key, value)| (key.as_str(), text_string(value))).collect::<Vec<_>>()
+    ));
+    pdf.trailer.set("Info", Object::Reference(info_id));
+
+    let metadata = Stream::new(
+        Dictionary::from_iter(vec![
+            ("Type", Object::Name(b"Metadata".to_vec())),
+            ("Subtype", Object::Name(b"XML".to_vec()))
+        ]),
+        xmp_metadata(&info, xmp_extensions).into_bytes()
+    );
+    let metadata_id = pdf.add_object(metadata);
+
+    let icc_profile_id = pdf.add_object(Stream::new(
+        Dictionary::from_iter(vec![("N", Object::Integer(3))]),
+        SRGB_ICC_PROFILE.to_vec()
+    ));
+    let output_intent = Dictionary::from_iter(vec![
+        ("Type", Object::Name(b"OutputIntent".to_vec())),
+        ("S", Object::Name(b"GTS_PDFA1".to_vec())),
+        ("OutputConditionIdentifier", Object::string_literal("sRGB IEC61966-2.1")),
+        ("Info", Object::string_literal("sRGB IEC61966-2.1")),
+        ("DestOutputProfile", Object::Reference(icc_profile_id))
+    ]);
+
+    let catalog = pdf.catalog_mut()?;
+    catalog.set("Metadata", Object::Reference(metadata_id));
+    catalog.set("OutputIntents", Object::Array(vec![Object::Dictionary(output_intent)]));
+    set_print_flag_on_annotations(pdf)
+}
+
+/// Entries of the document information dictionary which have an XMP counterpart, empty ones left out
+fn document_info(pdf: &Document) -> Vec<(String, String)> {
+    let info = match info_dictionary(pdf) {
+        Some(info) => info,
+        None => return Vec::new()
+    };
+    INFO_ENTRIES.iter().chain(["CreationDate", "ModDate"].iter())
+        .filter_map(|key| {
+            let value = info.get(key.as_bytes()).ok().and_then(|value| decode_text_string(value).ok())?;
+            (!value.trim().is_empty()).then(|| (key.to_string(), value))
+        })
+        .collect()
+}
+
+fn xmp_metadata(info: &[(String, String)], xmp_extensions: &[XmpExtension]) -> String {
+    let entry = |key: &str| info.iter().find(|(k, _)| k == key).map(|(_, value)| escape(value.as_str()).into_owned());
+    let mut descriptions = vec![
+        r#"<rdf:Description rdf:about="" xmlns:pdfaid="http://www.aiim.org/pdfa/ns/id/"><pdfaid:part>3</pdfaid:part><pdfaid:conformance>B</pdfaid:conformance></rdf:Description>"#.to_owned()
+    ];
+
+    let mut dublin_core = String::new();
+    if let Some(title) = entry("Title") {
+        dublin_core.push_str(&format!(r#"<dc:title><rdf:Alt><rdf:li xml:lang="x-default">{}</rdf:li></rdf:Alt></dc:title>"#, title));
+    }
+    if let Some(author) = entry("Author") {
+        dublin_core.push_str(&format!("<dc:creator><rdf:Seq><rdf:li>{}</rdf:li></rdf:Seq></dc:creator>", author));
+    }
+    if let Some(subject) = entry("Subject") {
+        dublin_core.push_str(&format!(r#"<dc:description><rdf:Alt><rdf:li xml:lang="x-default">{}</rdf:li></rdf:Alt></dc:description>"#, subject));
+    }
+    if !dublin_core.is_empty() {
+        descriptions.push(format!(r#"<rdf:Description rdf:about="" xmlns:dc="http://purl.org/dc/elements/1.1/">{}</rdf:Description>"#, dublin_core));
+    }
+
+    let mut pdf_properties = String::new();
+    if let Some(producer) = entry("Producer") {
+        pdf_properties.push_str(&format!("<pdf:Producer>{}</pdf:Producer>", producer));
+    }
+    if let Some(keywords) = entry("Keywords") {
+        pdf_properties.push_str(&format!("<pdf:Keywords>{}</pdf:Keywords>", keywords));
+    }
+    if !pdf_properties.is_empty() {
+        descriptions.push(format!(r#"<rdf:Description rdf:about="" xmlns:pdf="http://ns.adobe.com/pdf/1.3/">{}</rdf:Description>"#, pdf_properties));
+    }
+
+    let mut xmp_properties = String::new();
+    if let Some(creator) = entry("Creator") {
+        xmp_properties.push_str(&format!("<xmp:CreatorTool>{}</xmp:CreatorTool>", creator));
+    }
+    if let Some(creation_date) = entry("CreationDate").and_then(|date| xmp_date(&date)) {
+        xmp_properties.push_str(&format!("<xmp:CreateDate>{}</xmp:CreateDate>", creation_date));
+    }
+    if let Some(modification_date) = entry("ModDate").and_then(|date| xmp_date(&date)) {
+        xmp_properties.push_str(&format!("<xmp:ModifyDate>{}</xmp:ModifyDate>", modification_date));
+    }
+    if !xmp_properties.is_empty() {
+        descriptions.push(format!(r#"<rdf:Description rdf:about="" xmlns:xmp="http://ns.adobe.com/xap/1.0/">{}</rdf:Description>"#, xmp_properties));
+    }
+
+    for extension in xmp_extensions {
+        let values: String = extension.properties.iter()
+            .map(|property| format!("<{0}:{1}>{2}</{0}:{1}>", extension.prefix, property.name, escape(property.value.as_str())))
+            .collect();
+        descriptions.push(format!(r#"<rdf:Description rdf:about="" xmlns:{}="{}">{}</rdf:Description>"#, extension.prefix, escape(extension.namespace_uri.as_str()), values));
+    }
+    if !xmp_extensions.is_empty() {
+        descriptions.push(extension_schemas(xmp_extensions));
+    }
+
+    format!(
+        "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"><rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">{}</rdf:RDF></x:xmpmeta>\n<?xpacket end=\"w\"?>",
+        descriptions.join("")
+    )
+}
+
+/// The pdfaExtension description of the custom properties
+fn extension_schemas(xmp_extensions: &[XmpExtension]) -> String {
+    let schemas: String = xmp_extensions.iter().map(|extension| {
+        let properties: String = extension.properties.iter().map(|property| format!(
+            r#"<rdf:li rdf:parseType="Resource"><pdfaProperty:name>{}</pdfaProperty:name><pdfaProperty:valueType>Text</pdfaProperty:valueType><pdfaProperty:category>external</pdfaProperty:category><pdfaProperty:description>{}</pdfaProperty:description></rdf:li>"#,
+            property.name, escape(property.description.as_str())
+        )).collect();
+        format!(
+            r#"<rdf:li rdf:parseType="Resource"><pdfaSchema:schema>{}</pdfaSchema:schema><pdfaSchema:namespaceURI>{}</pdfaSchema:namespaceURI><pdfaSchema:prefix>{}</pdfaSchema:prefix><pdfaSchema:property><rdf:Seq>{}</rdf:Seq></pdfaSchema:property></rdf:li>"#,
+            escape(extension.schema.as_str()), escape(extension.namespace_uri.as_str()), extension.prefix, properties
+        )
+    }).collect();
+    format!(
+        r#"<rdf:Description rdf:about="" xmlns:pdfaExtension="http://www.aiim.org/pdfa/ns/extension/" xmlns:pdfaSchema="http://www.aiim.org/pdfa/ns/schema#" xmlns:pdfaProperty="http://www.aiim.org/pdfa/ns/property#"><pdfaExtension:schemas><rdf:Bag>{}</rdf:Bag></pdfaExtension:schemas></rdf:Description>"#,
+        schemas
+    )
+}
+
+/// Converts a PDF date "D:20250204101500+00'00'" into the XMP form "2025-02-04T10:15:00+00:00"
+fn xmp_date(pdf_date: &str) -> Option<String> {
+    let value = pdf_date.strip_prefix("D:").unwrap_or(pdf_date);
+    let digits: String = value.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if digits.len() < 14 {
+        return None;
+    }
+    let time_zone = match value[14..].chars().next() {
+        Some(sign @ ('+' | '-')) => {
+            let offset: String = value[15..].chars().filter(|c| c.is_ascii_digit()).collect();
+            if offset.len() < 4 {
+                return None;
+            }
+            format!("{}{}:{}", sign, &offset[0..2], &offset[2..4])
+        },
+        _ => "Z".to_owned()
+    };
+    Some(format!("{}-{}-{}T{}:{}:{}{}", &digits[0..4], &digits[4..6], &digits[6..8], &digits[8..10], &digits[10..12], &digits[12..14], time_zone))
+}
+
+/// PDF/A requires annotations to be printed along with the page (the Print flag, bit 3)
+fn set_print_flag_on_annotations(pdf: &mut Document) -> Result<(), Error> {
+    let mut annotation_ids = Vec::new();
+    for page_id in pdf.get_pages().into_values() {
+        let page = pdf.get_dictionary_mut(page_id)?;
+        if let Ok(Object::Array(annotations)) = page.get_mut(b"Annots") {
+            for annotation in annotations.iter_mut() {
+                match annotation {
+                    Object::Dictionary(annotation) => annotation.set("F", Object::Integer(4)),
+                    Object::Reference(annotation_id) => annotation_ids.push(*annotation_id),
+                    _ => {}
+                }
+            }
+        }
+    }
+    for annotation_id in annotation_ids {
+        pdf.get_dictionary_mut(annotation_id)?.set("F", Object::Integer(4));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::{save_pdf_with_options, PdfOutputOptions, XmpProperty};
+    use printpdf::*;
+
+    #[test]
+    fn should_convert_pdf_dates_to_xmp() {
+        assert_eq!(xmp_date("D:20250204101500+00'00'").unwrap(), "2025-02-04T10:15:00+00:00");
+        assert_eq!(xmp_date("D:20250204101500Z").unwrap(), "2025-02-04T10:15:00Z");
+        assert!(xmp_date("D:2025").is_none());
+    }
+
+    #[test]
+    fn should_write_pdf_a3_metadata() {
+        let mut doc = PdfDocument::new("Invoice 2025-0001");
+        doc.metadata.info.creator = "Invoicely".to_owned();
+        doc.with_pages(vec![PdfPage::new(Mm(210.0), Mm(297.0), Vec::new())]);
+        let options = PdfOutputOptions {
+            pdf_a3: true,
+            xmp_extensions: vec![XmpExtension {
+                schema: "Example Schema".to_owned(),
+                namespace_uri: "urn:example#".to_owned(),
+                prefix: "ex".to_owned(),
+                properties: vec![XmpProperty { name: "Kind".to_owned(), description: "Kind of the document".to_owned(), value: "R&D".to_owned() }]
+            }],
+            ..PdfOutputOptions::default()
+        };
+        let pdf = Document::load_mem(&save_pdf_with_options(&doc, &options).unwrap()).unwrap();
+        assert_eq!(pdf.version, "1.7");
+        let catalog = pdf.catalog().unwrap();
+        let metadata_id = catalog.get(b"Metadata").unwrap().as_reference().unwrap();
+        let metadata = String::from_utf8(pdf.get_object(metadata_id).unwrap().as_stream().unwrap().content.clone()).unwrap();
+        assert!(metadata.contains("<pdfaid:part>3</pdfaid:part>"));
+        assert!(metadata.contains(r#"<rdf:li xml:lang="x-default">Invoice 2025-0001</rdf:li>"#));
+        assert!(metadata.contains("<xmp:CreatorTool>Invoicely</xmp:CreatorTool>"));
+        assert!(metadata.contains("<ex:Kind>R&amp;D</ex:Kind>"));
+        assert!(metadata.contains("<pdfaSchema:prefix>ex</pdfaSchema:prefix>"));
+        let output_intent = &catalog.get(b"OutputIntents").unwrap().as_array().unwrap()[0];
+        assert_eq!(output_intent.as_dict().unwrap().get(b"S").unwrap().as_name().unwrap(), b"GTS_PDFA1");
+        let info = info_dictionary(&pdf).unwrap();
+        assert!(info.get(b"GTS_PDFXVersion").is_err());
+        assert!(info.get(b"Author").is_err());
+    }
+}
@@ -1,227 +1,747 @@
-use anyhow::Error;
+use anyhow::{Context, Error};
 use printpdf::*;
-use crate::components::Component;
-use crate::invoice::Invoice;
-use bigdecimal::{BigDecimal, FromPrimitive};
+use bigdecimal::BigDecimal;
+use std::ops::Range;
+use std::rc::Rc;
+use crate::components::{render_top, stroke_line, Component};
+use crate::components::layout::{Aligned, Alignment, Grid, Stack};
+use crate::components::picture::Picture;
+use crate::drawing::{DrawOp, Page};
+use crate::drawing::pdf::to_pdf_pages;
+use crate::drawing::svg::to_svg;
+use crate::invoice::{BillingInformation, Invoice, InvoiceTotals};
 use crate::format::{format_price, format_vat};
-use crate::components::table::Table;
+use crate::components::table::{ColumnAlignment, Table, TableStyle};
+use crate::components::code128::Code128;
 use crate::components::label::Label;
+use crate::components::link::Link;
+use crate::components::qr_code::QrCode;
+use crate::payment::bank_barcode::bank_barcode_from_invoice;
+use crate::payment::epc::EpcPayment;
+use crate::export::factur_x::factur_x_output_options;
+use crate::pdf::{extract_attachment, save_pdf_with_options, AttachmentRelationship, PdfAttachment, PdfOutputOptions};
+use crate::renderer::options::{PaymentSlip, QrCodePosition, RenderOptions};
 use crate::locale::get_translations;
+use crate::locale::hyphenation::Hyphenation;
+use crate::fonts::SystemFonts;
 use crate::image::load_image;
 use crate::renderer::rendering_context::{init_rendering_context, RenderingContext};
+use crate::renderer::finnish_transfer_slip::{finnish_transfer_slip, FINNISH_TRANSFER_SLIP_HEIGHT};
+use crate::renderer::swiss_qr_bill::{swiss_qr_bill, SWISS_QR_BILL_HEIGHT};
+use crate::renderer::pagination::{paginate, PageSpace};
+use crate::renderer::template::{Block, BlockKind, Footer, Region};
+use crate::theme::{Color, Theme};
 
+mod finnish_transfer_slip;
+pub mod html;
+pub mod options;
+mod pagination;
 mod rendering_context;
+mod swiss_qr_bill;
+pub mod template;
+pub mod text;
 
-pub fn render(invoice: &Invoice) -> Result<PdfDocument, Error> {
+/// Width and height of the EPC QR code including its quiet zone, in mm
+const EPC_QR_CODE_SIZE: f32 = 30.0;
+/// The item is left-aligned, the quantity, prices and VAT percentage right-aligned
+const INVOICE_LINE_COLUMN_ALIGNMENTS: [ColumnAlignment; 5] = [
+    ColumnAlignment::Left, ColumnAlignment::Right, ColumnAlignment::Right, ColumnAlignment::Right, ColumnAlignment::Right
+];
+/// Bottom edge of the EPC QR code and the bank barcode above the bottom margin of the last page
+const PAYMENT_CODES_BOTTOM: f32 = 5.0;
+/// Space between the EPC QR code and the bank barcode when the QR code is stacked above it
+const PAYMENT_CODE_SPACING: f32 = 4.0;
+/// Space between a payment code and its caption below it
+const CAPTION_SPACING: f32 = 1.0;
+/// Width of the Swiss QR-bill and the Finnish bank transfer slip, centred on wider pages
+const PAYMENT_SLIP_WIDTH: f32 = 210.0;
+/// Space between the footer line and the seller details below it
+const FOOTER_SPACING: f32 = 2.0;
+/// Vertical space between the blocks of the page
+const SECTION_SPACING: f32 = 8.0;
+/// Space between the last line of a page, the border below it and the carried forward row
+const CARRIED_FORWARD_GAP: f32 = 1.0;
+/// Space kept between the content and the footer or payment codes below it
+const CONTENT_GAP: f32 = 2.0;
+
+pub fn render(invoice: &Invoice, options: &RenderOptions) -> Result<PdfDocument, Error> {
     let translations = get_translations(&invoice.locale)?;
     let mut doc: PdfDocument = PdfDocument::new(&format!("{} {}", translations.invoice.invoice, invoice.invoice_number));
-    let rendering_context= init_rendering_context(&mut doc, invoice, translations, &invoice.locale)?;
+    let now = OffsetDateTime::now_utc();
+    doc.metadata.info.creation_date = now;
+    doc.metadata.info.modification_date = now;
+    doc.metadata.info.metadata_date = now;
+    doc.metadata.info.author = invoice.billed_by.name.clone();
+    doc.metadata.info.creator = "Invoicely".to_owned();
+    let pages = to_pdf_pages(&mut doc, &draw_pages(invoice, options)?)?;
+    doc.with_pages(pages);
+    Ok(doc)
+}
 
-    let invoice_parts = vec![
-        if let Some(logo_url) = &invoice.billed_by.logo {
-            let image = load_image(logo_url)?;
-            logo(&mut doc, &image)?
-        } else {
-            Vec::new()
-        },
-        Label::new(rendering_context.translations.invoice.invoice, 22.0, &rendering_context.bold_font_id).render_at(110.0, 270.0),
-        invoice_info(invoice, &rendering_context).render_at(110.0, 260.0),
-        billed_to(invoice, &rendering_context).render_at(15.0, 260.0),
-        invoice_lines(invoice, &rendering_context).render_at(15.0, 200.0),
-        summary(invoice, &rendering_context).render_at(125.0, 164.0),
-        if let Some(note) = invoice.note.as_ref() {
-            Label::new(note, 10.0, &rendering_context.regular_font_id).render_at(15.0, 140.0)
+/// Renders the invoice as standalone SVG images, one per page, e.g. for previews
+pub fn render_svg(invoice: &Invoice, options: &RenderOptions) -> Result<Vec<String>, Error> {
+    Ok(draw_pages(invoice, options)?.iter().map(to_svg).collect())
+}
+
+/// Lays out the invoice into pages of backend independent drawing operations. The blocks flow
+/// from the top margin down, invoice lines continue on the following pages when needed and the
+/// summary and footer go to the last page.
+pub fn draw_pages(invoice: &Invoice, options: &RenderOptions) -> Result<Vec<Page>, Error> {
+    let translations = get_translations(&invoice.locale)?;
+    let rendering_context = init_rendering_context(invoice, translations, &invoice.locale, options)?;
+    let page = rendering_context.page;
+    let (margin_left, content_width) = (page.margins.left, page.content_width());
+    if options.payment_slip.is_some() && page.width < PAYMENT_SLIP_WIDTH {
+        return Err(Error::msg(format!("Payment slips need a page at least {} mm wide, the page is {} mm wide", PAYMENT_SLIP_WIDTH, page.width)));
+    }
+    let theme = &rendering_context.theme;
+    if let Some(family) = rendering_context.regular_font.family {
+        SystemFonts::get(Some(family))?;
+    }
+    let footer_y = footer_y(invoice, &rendering_context, options);
+    let payment_codes = payment_codes(invoice, &rendering_context, options, footer_y)?;
+    let content_top = page.content_top();
+    let header = first_page_header(invoice, &rendering_context)?;
+    let heading = Label {
+        color: theme.primary_color,
+        ..Label::new(&format!("{} {}", translations.invoice.invoice, invoice.invoice_number), 11.0, rendering_context.bold_font)
+    };
+    let tail = region(invoice, &rendering_context, &rendering_context.template.tail, content_width)?;
+    let all_lines = invoice_lines(invoice, &rendering_context, 0..invoice.invoice_lines.len());
+    let row_heights = all_lines.row_heights();
+    let page_space = PageSpace {
+        first_page_top: content_top - header.measure(Some(content_width)).height - SECTION_SPACING,
+        next_page_top: content_top - heading.measure(None).height - SECTION_SPACING,
+        header_height: all_lines.header_height(),
+        brought_forward_height: subtotal_table(invoice, &rendering_context, "", 0).measure(None).height,
+        page_break_bottom: page.margins.bottom + carried_forward_height(invoice, &rendering_context),
+        tail_height: SECTION_SPACING + tail.measure(Some(content_width)).height,
+        content_bottom: content_bottom(&payment_codes, footer_y)
+    };
+    let line_pages = paginate(&row_heights, &page_space);
+    let page_count = line_pages.len();
+
+    let mut pages = Vec::new();
+    for (page_index, lines) in line_pages.into_iter().enumerate() {
+        let first_page = page_index == 0;
+        let last_page = page_index == page_count - 1;
+        let mut invoice_parts = Vec::new();
+        let table_top = if first_page {
+            invoice_parts.push(render_top(&header, margin_left, content_top, Some(content_width)));
+            page_space.first_page_top
         } else {
-            Vec::new()
+            invoice_parts.push(render_top(&heading, margin_left, content_top, None));
+            page_space.next_page_top
+        };
+        let table = invoice_lines(invoice, &rendering_context, lines.clone());
+        invoice_parts.push(render_top(&table, margin_left, table_top, None));
+        let table_bottom = table_top - table.measure(None).height;
+        if page_count > 1 {
+            let page_number = Aligned {
+                alignment: Alignment::Right,
+                child: Box::new(Label {
+                    color: theme.text_color,
+                    ..Label::new(&format!("{} {} {} {}", translations.invoice.page, page_index + 1, translations.invoice.page_of, page_count), 8.0, rendering_context.regular_font)
+                })
+            };
+            invoice_parts.push(render_top(&page_number, margin_left, page.height - page.margins.top / 2.0, Some(content_width)));
+        }
+        if !last_page {
+            invoice_parts.push(carried_forward(invoice, &rendering_context, lines.end, table_bottom));
+            pages.push(Page {
+                width: page.width,
+                height: page.height,
+                ops: invoice_parts.into_iter().flatten().collect()
+            });
+            continue;
+        }
+
+        invoice_parts.push(render_top(&tail, margin_left, table_bottom - SECTION_SPACING, Some(content_width)));
+        if let Some(footer) = &rendering_context.template.footer {
+            invoice_parts.push(rule_line(theme, 0.8, &[(margin_left, footer_y), (margin_left + content_width, footer_y)]));
+            invoice_parts.push(render_top(&billed_by(invoice, &rendering_context, footer), margin_left, footer_y - FOOTER_SPACING, Some(content_width)));
+        }
+        for code in payment_codes.iter() {
+            invoice_parts.push(render_top(&code.component, code.x, code.top, None));
+        }
+        let slip = match options.payment_slip {
+            Some(PaymentSlip::SwissQrBill) => swiss_qr_bill(invoice, &rendering_context)?,
+            Some(PaymentSlip::FinnishTransferSlip) => finnish_transfer_slip(invoice, &rendering_context, options.bank_barcode_for(invoice))?,
+            None => Vec::new()
+        };
+        let slip_x = (page.width - PAYMENT_SLIP_WIDTH) / 2.0;
+        invoice_parts.push(slip.into_iter().map(|op| op.translated(slip_x, 0.0)).collect());
+        pages.push(Page {
+            width: page.width,
+            height: page.height,
+            ops: invoice_parts.into_iter().flatten().collect()
+        });
+    }
+    Ok(pages)
+}
+
+/// Separator line in the rule color of the theme
+fn rule_line(theme: &Theme, width: f32, points: &[(f32, f32)]) -> Vec<DrawOp> {
+    let mut ops = vec![DrawOp::SetLineWidth(width)];
+    ops.extend(theme.rule_color.map(DrawOp::SetStrokeColor));
+    ops.push(stroke_line(points));
+    if theme.rule_color.is_some() {
+        ops.push(DrawOp::SetStrokeColor(Color::BLACK));
+    }
+    ops
+}
+
+/// Line above the seller details at the bottom of the last page, the payment codes stand on it.
+/// The seller details are printed inside the bottom margin, and above the payment slip drawn from
+/// the bottom edge of the page when one is printed.
+fn footer_y(invoice: &Invoice, rendering_context: &RenderingContext, options: &RenderOptions) -> f32 {
+    let margin_bottom = rendering_context.page.margins.bottom;
+    let slip_height = match options.payment_slip {
+        Some(PaymentSlip::SwissQrBill) => SWISS_QR_BILL_HEIGHT,
+        Some(PaymentSlip::FinnishTransferSlip) => FINNISH_TRANSFER_SLIP_HEIGHT,
+        None => return margin_bottom
+    };
+    let footer_height = rendering_context.template.footer.as_ref()
+        .map(|footer| FOOTER_SPACING + billed_by(invoice, rendering_context, footer).measure(Some(rendering_context.page.content_width())).height)
+        .unwrap_or(0.0);
+    margin_bottom.max(slip_height + CONTENT_GAP + footer_height)
+}
+
+/// Lowest bottom edge of the content on the last page, above the footer and payment codes
+fn content_bottom(payment_codes: &[PaymentCode], footer_y: f32) -> f32 {
+    payment_codes.iter()
+        .filter(|code| code.reserves_space)
+        .map(|code| code.top + CONTENT_GAP)
+        .fold(footer_y + CONTENT_GAP, f32::max)
+}
+
+/// Name of the attachment holding the invoice JSON the PDF was rendered from
+pub const SOURCE_JSON_FILE_NAME: &str = "invoice.json";
+
+/// Renders and saves the invoice with its JSON embedded, as a Factur-X PDF/A-3 with the embedded
+/// XML invoice when requested
+pub fn render_pdf(invoice: &Invoice, options: &RenderOptions) -> Result<Vec<u8>, Error> {
+    let doc = render(invoice, options)?;
+    let mut output_options = match options.factur_x {
+        Some(profile) => factur_x_output_options(invoice, profile)?,
+        None => PdfOutputOptions::default()
+    };
+    output_options.attachments.push(PdfAttachment {
+        file_name: SOURCE_JSON_FILE_NAME.to_owned(),
+        mime_type: "application/json".to_owned(),
+        description: format!("Source data of invoice {}", invoice.invoice_number),
+        relationship: AttachmentRelationship::Source,
+        content: match &options.source_json {
+            Some(source_json) => source_json.clone(),
+            None => serde_json::to_vec_pretty(invoice)?
+        }
+    });
+    save_pdf_with_options(&doc, &output_options)
+}
+
+/// Reads back the invoice JSON embedded by `render_pdf`
+pub fn extract_invoice_json(pdf: &[u8]) -> Result<String, Error> {
+    let json = extract_attachment(pdf, SOURCE_JSON_FILE_NAME)?
+        .ok_or_else(|| Error::msg("The PDF has no embedded invoice JSON, it was not rendered by Invoicely"))?;
+    String::from_utf8(json).context("Embedded invoice JSON is not valid UTF-8")
+}
+
+/// Columns of blocks at the top of the first page, by default the logo and the customer's address
+/// next to the title and the invoice details
+fn first_page_header(invoice: &Invoice, rendering_context: &RenderingContext) -> Result<Grid, Error> {
+    let template = &rendering_context.template;
+    let mut column_widths = Vec::new();
+    let mut columns = Vec::new();
+    for header_region in template.header.iter() {
+        let width = header_region.width.unwrap_or(rendering_context.page.content_width());
+        column_widths.push(width);
+        columns.push(Box::new(region(invoice, rendering_context, header_region, width)?) as Box<dyn Component>);
+    }
+    Ok(Grid {
+        column_widths,
+        column_spacing: template.column_spacing,
+        row_spacing: 0.0,
+        rows: vec![columns]
+    })
+}
+
+/// Blocks of a template region below each other, leaving out the ones the invoice has no content for
+fn region(invoice: &Invoice, rendering_context: &RenderingContext, region: &Region, width: f32) -> Result<Stack, Error> {
+    let mut children = Vec::new();
+    for block in region.blocks.iter() {
+        children.extend(region_block(invoice, rendering_context, block, width)?);
+    }
+    Ok(Stack {
+        spacing: region.spacing,
+        children
+    })
+}
+
+fn region_block(invoice: &Invoice, rendering_context: &RenderingContext, block: &Block, width: f32) -> Result<Option<Box<dyn Component>>, Error> {
+    let hyphenation = Hyphenation::for_locale(&rendering_context.locale);
+    let theme = &rendering_context.theme;
+    let text = |value: &str| -> Box<dyn Component> {
+        Box::new(Label {
+            color: theme.text_color,
+            ..Label::new(value, block.font_size(), rendering_context.font(block.font())).with_hyphenation(hyphenation)
+        })
+    };
+    let component: Box<dyn Component> = match block.block {
+        BlockKind::Logo => match &invoice.billed_by.logo {
+            Some(logo_url) => {
+                let (max_width, max_height) = block.logo_box(width);
+                Box::new(Picture::fit(Rc::new(load_image(logo_url)?), max_width, max_height))
+            },
+            None => return Ok(None)
         },
-        if let Some(description) = invoice.invoice_description.as_ref() {
-            Label::new(description, 10.0, &rendering_context.regular_font_id).render_at(15.0, 130.0)
-        } else {
-            Vec::new()
+        BlockKind::Title => Box::new(Label {
+            color: theme.primary_color,
+            ..Label::new(rendering_context.translations.invoice.invoice, theme.title_font_size.unwrap_or(block.font_size()), rendering_context.font(block.font()))
+        }),
+        BlockKind::InvoiceInfo => Box::new(invoice_info(invoice, rendering_context, block, width)),
+        BlockKind::BilledTo => Box::new(party(&invoice.billed_to, rendering_context, block, width)),
+        BlockKind::BilledBy => Box::new(party(&invoice.billed_by, rendering_context, block, width)),
+        BlockKind::Summary => Box::new(summary(invoice, rendering_context, block, width)),
+        BlockKind::Note => match &invoice.note {
+            Some(note) => text(note),
+            None => return Ok(None)
         },
-        vec![
-            Op::SetOutlineThickness { pt: Pt(0.8) },
-            Op::DrawLine { 
-                line: Line {
-                    points: vec![
-                        LinePoint { p: Point::new(Mm(15.0), Mm(25.0)), bezier: false },
-                        LinePoint { p: Point::new(Mm(200.0), Mm(25.0)), bezier: false },
-                    ],
-                    is_closed: false
-                }
+        BlockKind::Description => match &invoice.invoice_description {
+            Some(description) => text(description),
+            None => return Ok(None)
+        }
+    };
+    Ok(Some(match block.align() {
+        Alignment::Left => component,
+        alignment => Box::new(Aligned { alignment, child: component })
+    }))
+}
+
+/// EPC QR code or bank barcode with its caption, placed by its top left corner on the last page
+struct PaymentCode {
+    component: Stack,
+    x: f32,
+    top: f32,
+    /// The code stands at the bottom of the page and the content is kept above it, a QR code at a
+    /// position given by the user does not
+    reserves_space: bool
+}
+
+impl PaymentCode {
+    /// Left, bottom, right and top edges in mm
+    fn bounds(&self) -> (f32, f32, f32, f32) {
+        let size = self.component.measure(None);
+        (self.x, self.top - size.height, self.x + size.width, self.top)
+    }
+}
+
+/// Places the requested payment codes above `bottom`. The bank barcode goes to the left margin and
+/// the EPC QR code above it when they would overlap side by side.
+fn payment_codes(invoice: &Invoice, rendering_context: &RenderingContext, options: &RenderOptions, bottom: f32) -> Result<Vec<PaymentCode>, Error> {
+    let page = &rendering_context.page;
+    let mut codes: Vec<PaymentCode> = Vec::new();
+    if options.bank_barcode_for(invoice) && options.payment_slip != Some(PaymentSlip::FinnishTransferSlip) {
+        let component = bank_barcode(invoice, rendering_context)?;
+        let top = bottom + PAYMENT_CODES_BOTTOM + component.measure(None).height;
+        codes.push(PaymentCode { component, x: page.margins.left, top, reserves_space: true });
+    }
+    if let Some(position) = options.epc_qr_code_for(invoice) {
+        let component = epc_qr_code(invoice, rendering_context)?;
+        let height = component.measure(None).height;
+        let top = bottom + PAYMENT_CODES_BOTTOM + height;
+        let mut code = match position {
+            QrCodePosition::BottomLeft => PaymentCode { component, x: page.margins.left, top, reserves_space: true },
+            QrCodePosition::BottomRight => PaymentCode { component, x: page.width - page.margins.right - EPC_QR_CODE_SIZE, top, reserves_space: true },
+            QrCodePosition::At { x, y } => PaymentCode { component, x, top: y, reserves_space: false }
+        };
+        if let Some(barcode) = codes.first().filter(|_| code.reserves_space) {
+            let (barcode_left, _, barcode_right, barcode_top) = barcode.bounds();
+            let (left, _, right, _) = code.bounds();
+            if left < barcode_right && barcode_left < right {
+                code.top = barcode_top + PAYMENT_CODE_SPACING + height;
             }
-        ],
-        billed_by(invoice, &rendering_context).render_at(25.0, 20.0)
-    ];
-    let mut page_contents = Vec::new();
-    for ops in invoice_parts {
-        page_contents.extend(ops);
+        }
+        codes.push(code);
     }
-    let page = PdfPage::new(Mm(210.0), Mm(297.0), page_contents);
-    doc.with_pages(vec![page]);
-    Ok(doc)
+    Ok(codes)
 }
 
-fn logo(doc: &mut PdfDocument, logo_image: &RawImage) -> Result<Vec<Op>, Error> {
-    let image_xobject_id = doc.add_image(&logo_image);
-    
-    let transform = XObjectTransform {
-        translate_x: Some(Pt(45.0)),
-        translate_y: Some(Pt(770.0)),
-        rotate: None,
-        scale_x: Some(0.5),
-        scale_y: Some(0.5),
-        dpi: Some(300.0),
-    };
-    
-    Ok(vec![Op::UseXobject { 
-        id: image_xobject_id.clone(), 
-        transform
-    }])
+fn epc_qr_code(invoice: &Invoice, rendering_context: &RenderingContext) -> Result<Stack, Error> {
+    let payment = EpcPayment::from_invoice(invoice)?;
+    Ok(Stack {
+        spacing: CAPTION_SPACING,
+        children: vec![
+            Box::new(QrCode::new(&payment.to_payload(), EPC_QR_CODE_SIZE)?.with_quiet_zone()),
+            Box::new(Label::new(rendering_context.translations.payment.epc_qr_code, 7.0, rendering_context.regular_font))
+        ]
+    })
+}
+
+fn bank_barcode(invoice: &Invoice, rendering_context: &RenderingContext) -> Result<Stack, Error> {
+    let barcode = bank_barcode_from_invoice(invoice)?;
+    Ok(Stack {
+        spacing: CAPTION_SPACING,
+        children: vec![
+            Box::new(Code128::new(&barcode, 0.3, 13.0)?),
+            Box::new(Label::new(&format!("{}: {}", rendering_context.translations.payment.virtual_barcode, barcode), 8.0, rendering_context.regular_font))
+        ]
+    })
 }
 
-fn invoice_info(invoice: &Invoice, rendering_context: &RenderingContext) -> Table {
+fn invoice_info(invoice: &Invoice, rendering_context: &RenderingContext, block: &Block, width: f32) -> Table {
     let translations = rendering_context.translations;
-    let regular_font_id = &rendering_context.regular_font_id;
     Table {
-        column_widths: vec![40.0, 30.0],
-        row_height: 5.0,
+        column_widths: block.column_widths(width).to_vec(),
+        column_alignments: Vec::new(),
+        style: rendering_context.layout_table_style(),
+        row_height: block.row_height(),
         header: None,
         rows: Label::new_rows(
             vec![
                 vec![&format!("{}:", translations.invoice.number), invoice.invoice_number.as_str()],
                 vec![&format!("{}:", translations.invoice.date), invoice.billed_at.as_str()],
                 vec![&format!("{}:", translations.invoice.due_date), invoice.due_date.as_str()],
-                vec![&format!("{}:", translations.invoice.reference_number), invoice.reference_id.as_ref().map(|s| s.as_str()).unwrap_or("")],
+                vec![&format!("{}:", translations.invoice.reference_number), invoice.reference_id.as_deref().unwrap_or("")],
                 vec![&format!("{}:", translations.account.number), invoice.bank_details.account_number.as_str()],
                 vec![&format!("{}:", translations.account.bic), invoice.bank_details.bic_code.as_str()]
             ],
-            11.0,
-            regular_font_id
+            block.font_size(),
+            rendering_context.font(block.font())
         )
     }
 }
 
-fn billed_to(invoice: &Invoice, rendering_context: &RenderingContext) -> Table {
+/// Name, address and identifiers of the customer or the seller
+fn party(party: &BillingInformation, rendering_context: &RenderingContext, block: &Block, width: f32) -> Table {
     let translations = rendering_context.translations;
-    let regular_font_id = &rendering_context.regular_font_id;
-    let mut billed_to_lines = vec![
-        vec![invoice.billed_to.name.as_str()],
-        vec![invoice.billed_to.address_line_1.as_str()]
+    let mut lines = vec![
+        vec![party.name.as_str()],
+        vec![party.address_line_1.as_str()]
     ];
-    if let Some(address_line_2) = invoice.billed_to.address_line_2.as_ref() {
-        billed_to_lines.push(vec![address_line_2.as_str()]);
+    if let Some(address_line_2) = party.address_line_2.as_ref() {
+        lines.push(vec![address_line_2.as_str()]);
     }
-    if let Some(address_line_3) = invoice.billed_to.address_line_3.as_ref() {
-        billed_to_lines.push(vec![address_line_3.as_str()]);
+    if let Some(address_line_3) = party.address_line_3.as_ref() {
+        lines.push(vec![address_line_3.as_str()]);
     }
-    let company_id_line = if let Some(company_id) = invoice.billed_to.company_id.as_ref() {
+    let company_id_line = if let Some(company_id) = party.company_id.as_ref() {
         format!("{}: {}", translations.company_id, company_id)
     } else {
         "".to_owned()
     };
     if !company_id_line.is_empty() {
-        billed_to_lines.push(vec![company_id_line.as_str()]);
+        lines.push(vec![company_id_line.as_str()]);
     }
-    let vat_id_line = if let Some(vat_id) = invoice.billed_to.vat_id.as_ref() {
+    let vat_id_line = if let Some(vat_id) = party.vat_id.as_ref() {
         format!("{}: {}", translations.vat_id, vat_id)
     } else {
         "".to_owned()
     };
     if !vat_id_line.is_empty() {
-        billed_to_lines.push(vec![vat_id_line.as_str()]);
+        lines.push(vec![vat_id_line.as_str()]);
     }
-    let billed_to = Table {
-        column_widths: vec![30.0],
-        row_height: 5.0,
+    Table {
+        column_widths: vec![width],
+        column_alignments: Vec::new(),
+        style: rendering_context.layout_table_style(),
+        row_height: block.row_height(),
         header: None,
-        rows: Label::new_rows(billed_to_lines, 11.0, &regular_font_id)
-    };
-    billed_to
+        rows: Label::new_rows(lines, block.font_size(), rendering_context.font(block.font()))
+    }
 }
 
-fn invoice_lines(invoice: &Invoice, rendering_context: &RenderingContext) -> Table {
+fn invoice_lines(invoice: &Invoice, rendering_context: &RenderingContext, lines: Range<usize>) -> Table {
     let translations = rendering_context.translations;
-    let regular_font_id = &rendering_context.regular_font_id;
-    let bold_font_id = &rendering_context.bold_font_id;
+    let regular_font = rendering_context.regular_font;
+    let bold_font = rendering_context.bold_font;
     let currency = &rendering_context.currency;
-    let mut invoice_lines: Vec<Vec<String>> = Vec::new();
-    for invoice_line in invoice.invoice_lines.iter() {
-        let price_without_vat = &invoice_line.price / BigDecimal::from_f32(1.0 + invoice.vat_percent / 100.0).unwrap();
-        invoice_lines.push(vec![
-            format!("{}", invoice_line.name),
-            format!("{}", invoice_line.count),
-            format_price(&invoice_line.price, currency, &rendering_context.locale),
-            format_price(&price_without_vat, currency, &rendering_context.locale),
-            format_vat(&invoice.vat_percent)
-        ]);
+    let hyphenation = Hyphenation::for_locale(&rendering_context.locale);
+    let font_size = rendering_context.template.lines.font_size;
+    let mut rows: Vec<Vec<Box<dyn Component>>> = Vec::new();
+    let line_prices_without_vat = invoice.totals().line_prices_without_vat;
+    for (invoice_line, price_without_vat) in invoice.invoice_lines[lines.clone()].iter().zip(line_prices_without_vat[lines.clone()].iter()) {
+        let mut row: Vec<Box<dyn Component>> = vec![Box::new(Label::new(&invoice_line.name, font_size, regular_font).with_hyphenation(hyphenation))];
+        row.extend(Label::new_row(vec![
+            &format!("{}", invoice_line.count),
+            &format_price(&invoice_line.price, currency, &rendering_context.locale),
+            &format_price(price_without_vat, currency, &rendering_context.locale),
+            &format_vat(&invoice.vat_percent)
+        ], font_size, regular_font));
+        rows.push(row);
+    }
+    if lines.start > 0 {
+        rows.insert(0, subtotal_row(invoice, rendering_context, translations.invoice.brought_forward, lines.start));
     }
     Table {
-        column_widths: vec![80.0, 20.0, 30.0, 40.0, 15.0],
-        row_height: 5.0,
+        column_widths: rendering_context.template.lines.column_widths.to_vec(),
+        column_alignments: INVOICE_LINE_COLUMN_ALIGNMENTS.to_vec(),
+        style: rendering_context.table_style(),
+        row_height: rendering_context.template.lines.row_height,
         header: Some(Label::new_row(vec![
             translations.invoice.line.item, translations.invoice.line.quantity, translations.invoice.line.price,
             translations.invoice.line.price_without_tax, &format!("{} %", translations.invoice.line.vat)
-        ], 10.0, &bold_font_id)),
-        rows: Label::new_rows(
-            invoice_lines.iter().map(|x| x.iter().map(|s| s.as_str()).collect()).collect(),
-            10.0,
-            &regular_font_id
-        )
+        ], font_size, bold_font)),
+        rows
     }
 }
 
-fn summary(invoice: &Invoice, rendering_context: &RenderingContext) -> Table {
+/// Border and the subtotal of the lines so far below the last line of a page continuing on the next page
+fn carried_forward(invoice: &Invoice, rendering_context: &RenderingContext, lines_end: usize, lines_bottom: f32) -> Vec<DrawOp> {
+    let width: f32 = rendering_context.template.lines.column_widths.iter().sum();
+    let margin_left = rendering_context.page.margins.left;
+    let border_y = lines_bottom - CARRIED_FORWARD_GAP;
+    let mut ops = Vec::new();
+    if let Some(border) = rendering_context.table_style().header_border {
+        ops.extend([
+            DrawOp::SetLineWidth(border.width),
+            DrawOp::SetStrokeColor(border.color),
+            stroke_line(&[(margin_left, border_y), (margin_left + width, border_y)]),
+            DrawOp::SetStrokeColor(Color::BLACK)
+        ]);
+    }
+    let table = subtotal_table(invoice, rendering_context, rendering_context.translations.invoice.carried_forward, lines_end);
+    ops.extend(render_top(&table, margin_left, border_y - CARRIED_FORWARD_GAP, None));
+    ops
+}
+
+/// Height the border and the carried forward row take below the lines
+fn carried_forward_height(invoice: &Invoice, rendering_context: &RenderingContext) -> f32 {
+    CARRIED_FORWARD_GAP * 2.0 + subtotal_table(invoice, rendering_context, "", 0).measure(None).height
+}
+
+/// Table with the subtotal row only, in the columns of the invoice lines
+fn subtotal_table(invoice: &Invoice, rendering_context: &RenderingContext, label: &str, lines_end: usize) -> Table {
+    Table {
+        column_widths: rendering_context.template.lines.column_widths.to_vec(),
+        column_alignments: INVOICE_LINE_COLUMN_ALIGNMENTS.to_vec(),
+        style: rendering_context.table_style(),
+        row_height: rendering_context.template.lines.row_height,
+        header: None,
+        rows: vec![subtotal_row(invoice, rendering_context, label, lines_end)]
+    }
+}
+
+/// Row with the sums of the prices with and without VAT of the first lines
+fn subtotal_row(invoice: &Invoice, rendering_context: &RenderingContext, label: &str, lines_end: usize) -> Vec<Box<dyn Component>> {
+    let currency = &rendering_context.currency;
+    let price: BigDecimal = invoice.invoice_lines[..lines_end].iter().map(|line| &line.price).sum();
+    let price_without_vat: BigDecimal = invoice.totals().line_prices_without_vat[..lines_end].iter().sum();
+    Label::new_row(vec![
+        label,
+        "",
+        &format_price(&price, currency, &rendering_context.locale),
+        &format_price(&price_without_vat, currency, &rendering_context.locale),
+        ""
+    ], rendering_context.template.lines.font_size, rendering_context.bold_font)
+}
+
+/// Totals with and without VAT, the total price is always bold
+fn summary(invoice: &Invoice, rendering_context: &RenderingContext, block: &Block, width: f32) -> Table {
     let translations = rendering_context.translations;
-    let regular_font_id = &rendering_context.regular_font_id;
-    let bold_font_id = &rendering_context.bold_font_id;
+    let font_size = block.font_size();
     let currency = &rendering_context.currency;
 
-    let total_price: BigDecimal = invoice.invoice_lines.iter().map(|line| &line.price).sum();
-    let total_vat: BigDecimal = BigDecimal::from_f32(invoice.vat_percent / 100.0).unwrap() * &total_price;
-    let total_price_without_vat = &total_price / BigDecimal::from_f32(1.0 + invoice.vat_percent / 100.0).unwrap();
+    let InvoiceTotals { total_price, total_vat, total_price_without_vat, .. } = invoice.totals();
 
     Table {
-        column_widths: vec![45.0, 30.0],
-        row_height: 5.0,
+        column_widths: block.column_widths(width).to_vec(),
+        column_alignments: vec![ColumnAlignment::Left, ColumnAlignment::Right],
+        style: rendering_context.table_style(),
+        row_height: block.row_height(),
         header: None,
         rows: vec![
             Label::new_row(
                 vec![&format!("{}:", translations.invoice.total_price_without_tax), &format_price(&total_price_without_vat, currency, &rendering_context.locale)],
-                10.0,
-                &regular_font_id
+                font_size,
+                rendering_context.font(block.font())
             ),
             Label::new_row(
                 vec![&format!("{} {} %:", translations.invoice.vat, &format_vat(&invoice.vat_percent)), &format_price(&total_vat, currency, &rendering_context.locale)],
-                10.0,
-                &regular_font_id
+                font_size,
+                rendering_context.font(block.font())
             ),
             Label::new_row(
                 vec![&format!("{}:", translations.invoice.total_price), &format_price(&total_price, currency, &rendering_context.locale)],
-                10.0,
-                &bold_font_id
+                font_size,
+                rendering_context.bold_font
             )
         ]
     }
 }
 
-fn billed_by(invoice: &Invoice, rendering_context: &RenderingContext) -> Table {
+/// Seller details in three columns at the bottom of the last page, leaving out the fields the seller does not have
+fn billed_by(invoice: &Invoice, rendering_context: &RenderingContext, footer: &Footer) -> Grid {
     let translations = rendering_context.translations;
-    let regular_font_id = &rendering_context.regular_font_id;
-    Table {
-        column_widths: vec![60.0, 60.0, 65.0],
-        row_height: 3.0,
-        header: None,
-        rows: Label::new_rows(
-            vec![
-                vec![invoice.billed_by.name.as_str(), invoice.billed_by.company_id.as_ref().map(|s| format!("{}: {}", translations.company_id, s)).unwrap_or_else(|| "".to_owned()).as_str(), invoice.billed_by.email.as_ref().map(|s| s.as_str()).unwrap_or("")],
-                vec![invoice.billed_by.address_line_1.as_str(), invoice.billed_by.vat_id.as_ref().map(|s| format!("{}: {}", translations.vat_id, s)).unwrap_or_else(|| "".to_owned()).as_str(), invoice.billed_by.phone_number.as_ref().map(|s| s.as_str()).unwrap_or("")],
-                vec![invoice.billed_by.address_line_2.as_ref().map(|s| s.as_str()).unwrap_or(""), invoice.bank_details.account_number.as_str(), ""],
-                vec![invoice.billed_by.address_line_3.as_ref().map(|s| s.as_str()).unwrap_or(""), invoice.bank_details.bic_code.as_str(), ""],
-                vec![invoice.billed_by.detail.as_ref().map(|s| s.as_str()).unwrap_or(""), "", ""]
-            ],
-            7.0,
-            &regular_font_id
-        )
+    let regular_font = rendering_context.regular_font;
+    let font_size = footer.font_size;
+    let billed_by = &invoice.billed_by;
+    let text = |value: &str| -> Box<dyn Component> {
+        Box::new(Label::new(value, font_size, regular_font))
+    };
+    let column = |width: f32, cells: Vec<Option<Box<dyn Component>>>| -> Box<dyn Component> {
+        Box::new(Table {
+            column_widths: vec![width],
+            column_alignments: Vec::new(),
+            style: TableStyle {
+                text_color: rendering_context.theme.primary_color,
+                ..TableStyle::default()
+            },
+            row_height: footer.row_height,
+            header: None,
+            rows: cells.into_iter().flatten().map(|cell| vec![cell]).collect()
+        })
+    };
+    let [address_width, identifiers_width, contact_width] = footer.column_widths;
+    let address = column(address_width, vec![
+        Some(text(&billed_by.name)),
+        Some(text(&billed_by.address_line_1)),
+        billed_by.address_line_2.as_deref().map(text),
+        billed_by.address_line_3.as_deref().map(text),
+        billed_by.detail.as_deref().map(text)
+    ]);
+    let identifiers = column(identifiers_width, vec![
+        billed_by.company_id.as_ref().map(|company_id| text(&format!("{}: {}", translations.company_id, company_id))),
+        billed_by.vat_id.as_ref().map(|vat_id| text(&format!("{}: {}", translations.vat_id, vat_id))),
+        Some(text(&invoice.bank_details.account_number)),
+        Some(text(&invoice.bank_details.bic_code))
+    ]);
+    let contact = column(contact_width, vec![
+        billed_by.email.as_deref().map(|email| Box::new(Link::mailto(email, font_size, regular_font)) as Box<dyn Component>),
+        billed_by.phone_number.as_deref().map(text),
+        billed_by.website.as_deref().map(|website| Box::new(Link::url(website, font_size, regular_font)) as Box<dyn Component>),
+        invoice.payment_url.as_deref().map(|payment_url| Box::new(Link::url(payment_url, font_size, regular_font)) as Box<dyn Component>)
+    ]);
+    Grid {
+        column_widths: footer.column_widths.to_vec(),
+        column_spacing: 0.0,
+        row_spacing: 0.0,
+        rows: vec![vec![address, identifiers, contact]]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::INVOICE;
+    use crate::invoice::{parse_invoice_json, InvoiceLine};
+    use crate::renderer::options::{Margins, PageSize};
+
+    fn rendering_context(invoice: &Invoice, options: &RenderOptions) -> RenderingContext {
+        init_rendering_context(invoice, get_translations(&invoice.locale).unwrap(), &invoice.locale, options).unwrap()
+    }
+
+    fn disjoint((left, bottom, right, top): (f32, f32, f32, f32), (other_left, other_bottom, other_right, other_top): (f32, f32, f32, f32)) -> bool {
+        right <= other_left || other_right <= left || top <= other_bottom || other_top <= bottom
+    }
+
+    #[test]
+    fn should_place_payment_codes_without_overlap() {
+        let invoice = parse_invoice_json(INVOICE).unwrap();
+        for page_size in [PageSize::A4, PageSize::A5, PageSize::Letter] {
+            for position in [QrCodePosition::BottomLeft, QrCodePosition::BottomRight] {
+                let options = RenderOptions { epc_qr_code: Some(position), bank_barcode: true, page_size, ..Default::default() };
+                let rendering_context = rendering_context(&invoice, &options);
+                let bottom = rendering_context.page.margins.bottom;
+                let codes = payment_codes(&invoice, &rendering_context, &options, bottom).unwrap();
+                assert_eq!(codes.len(), 2);
+                let (barcode, qr_code) = (codes[0].bounds(), codes[1].bounds());
+                assert!(disjoint(barcode, qr_code), "{:?} {:?}: {:?} overlaps {:?}", page_size, position, barcode, qr_code);
+                assert!(barcode.1 >= bottom && qr_code.1 >= bottom);
+            }
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn should_place_payment_codes_above_the_payment_slip() {
+        let invoice = parse_invoice_json(INVOICE).unwrap();
+        let slips = [(PaymentSlip::SwissQrBill, SWISS_QR_BILL_HEIGHT), (PaymentSlip::FinnishTransferSlip, FINNISH_TRANSFER_SLIP_HEIGHT)];
+        for ((payment_slip, slip_height), margins) in slips.into_iter().zip([Margins::default(), Margins { top: 10.0, right: 10.0, bottom: 10.0, left: 10.0 }]) {
+            let options = RenderOptions { epc_qr_code: Some(QrCodePosition::BottomLeft), bank_barcode: true, payment_slip: Some(payment_slip), margins, ..Default::default() };
+            let rendering_context = rendering_context(&invoice, &options);
+            let codes = payment_codes(&invoice, &rendering_context, &options, footer_y(&invoice, &rendering_context, &options)).unwrap();
+            assert!(!codes.is_empty());
+            for code in codes {
+                assert!(code.bounds().1 > slip_height, "{:?}: {:?} is on the slip", payment_slip, code.bounds());
+            }
+        }
+    }
+
+    #[test]
+    fn should_print_the_footer_above_the_payment_slip_and_centre_the_slip() {
+        let invoice = parse_invoice_json(INVOICE).unwrap();
+        let options = RenderOptions {
+            payment_slip: Some(PaymentSlip::FinnishTransferSlip),
+            page_size: PageSize::Letter,
+            margins: Margins { top: 10.0, right: 10.0, bottom: 10.0, left: 10.0 },
+            ..Default::default()
+        };
+        let pages = draw_pages(&invoice, &options).unwrap();
+        let ops = &pages.last().unwrap().ops;
+        let footer_address = ops.iter().find_map(|op| match op {
+            DrawOp::Text { y, text, .. } if text == "123 Business Street" => Some(*y),
+            _ => None
+        }).unwrap();
+        assert!(footer_address > FINNISH_TRANSFER_SLIP_HEIGHT);
+        let slip_x = (215.9 - PAYMENT_SLIP_WIDTH) / 2.0;
+        let slip_top = [(slip_x, FINNISH_TRANSFER_SLIP_HEIGHT), (slip_x + PAYMENT_SLIP_WIDTH, FINNISH_TRANSFER_SLIP_HEIGHT)];
+        assert!(ops.iter().any(|op| matches!(op, DrawOp::Line(points) if points[..] == slip_top)));
+    }
+
+    #[test]
+    fn should_extract_the_embedded_invoice_json() {
+        let invoice = parse_invoice_json(INVOICE).unwrap();
+        let options = RenderOptions { source_json: Some(INVOICE.as_bytes().to_vec()), ..Default::default() };
+        let json = extract_invoice_json(&render_pdf(&invoice, &options).unwrap()).unwrap();
+        assert_eq!(json, INVOICE);
+
+        let json = extract_invoice_json(&render_pdf(&invoice, &RenderOptions::default()).unwrap()).unwrap();
+        let extracted = parse_invoice_json(&json).unwrap();
+        assert_eq!(serde_json::to_value(&extracted).unwrap(), serde_json::to_value(&invoice).unwrap());
+    }
+
+    #[test]
+    fn should_continue_long_invoices_on_the_following_pages() {
+        let mut invoice = parse_invoice_json(INVOICE).unwrap();
+        invoice.invoice_lines = (1..=40)
+            .map(|number| InvoiceLine { name: format!("Item {}", number), count: 1, price: BigDecimal::from(10) })
+            .collect();
+        let pages = draw_pages(&invoice, &RenderOptions::default()).unwrap();
+        let page_count = pages.len();
+        assert!(page_count > 1);
+        let prices_without_vat = invoice.totals().line_prices_without_vat;
+        let subtotal = |lines_end: usize| [
+            format_price(&BigDecimal::from(10 * lines_end as i64), "€", &invoice.locale),
+            format_price(&prices_without_vat[..lines_end].iter().sum(), "€", &invoice.locale)
+        ];
+        let mut lines_end = 0;
+        for (index, page) in pages.iter().enumerate() {
+            let texts: Vec<&str> = page.ops.iter()
+                .filter_map(|op| match op {
+                    DrawOp::Text { text, .. } => Some(text.as_str()),
+                    _ => None
+                })
+                .collect();
+            // The prices of a subtotal row follow its label and the empty quantity
+            let following = |label: &str| texts.iter().position(|text| *text == label).map(|position| [texts[position + 2], texts[position + 3]]);
+            let last_page = index == page_count - 1;
+            assert!(texts.contains(&"Item"), "header row missing on page {}", index + 1);
+            assert!(texts.contains(&format!("Page {} of {}", index + 1, page_count).as_str()));
+            let brought_forward = following("Brought forward");
+            if index == 0 {
+                assert_eq!(brought_forward, None);
+            } else {
+                assert_eq!(brought_forward, Some(subtotal(lines_end).each_ref().map(String::as_str)));
+            }
+            lines_end += texts.iter().filter(|text| text.starts_with("Item ")).count();
+            let carried_forward = following("Carried forward");
+            if last_page {
+                assert_eq!(carried_forward, None);
+            } else {
+                assert_eq!(carried_forward, Some(subtotal(lines_end).each_ref().map(String::as_str)));
+            }
+            assert_eq!(texts.contains(&"Total price:"), last_page);
+            assert_eq!(texts.contains(&"123 Business Street"), last_page);
+        }
+        assert_eq!(lines_end, 40);
+    }
+}
@@ -0,0 +1,165 @@
+use anyhow::Error;
+use crate::drawing::DrawOp;
+use crate::components::{stroke_line, Component};
+use crate::components::code128::Code128;
+use crate::components::label::Label;
+use crate::date::Date;
+use crate::format::format_amount;
+use crate::invoice::Invoice;
+use crate::payment::bank_barcode::bank_barcode_from_invoice;
+use crate::payment::reference::{is_rf_reference, validate_finnish_reference, validate_rf_reference};
+use crate::renderer::rendering_context::RenderingContext;
+
+/// Height of the Finnish bank transfer slip (tilisiirtolomake) at the bottom of the page. The slip
+/// is drawn 210 mm wide from x = 0 and centred on wider pages by the renderer.
+pub(crate) const FINNISH_TRANSFER_SLIP_HEIGHT: f32 = 101.6;
+const LEFT: f32 = 5.0;
+const RIGHT: f32 = 205.0;
+const VALUE_X: f32 = 25.0;
+const MIDDLE_X: f32 = 110.0;
+const BIC_X: f32 = 85.0;
+const EURO_X: f32 = 158.0;
+const RECIPIENT_Y: f32 = 84.7;
+const PAYER_Y: f32 = 67.8;
+const SIGNATURE_Y: f32 = 42.4;
+const FROM_ACCOUNT_Y: f32 = 33.9;
+const BOTTOM_Y: f32 = 25.4;
+
+// Field headings are fixed by the standard and always printed in Finnish and Swedish
+const TITLE: &str = "TILISIIRTO GIRERING";
+const RECIPIENT_ACCOUNT: &str = "Saajan\ntilinumero\nMottagarens\nkontonummer";
+const BIC: &str = "BIC";
+const RECIPIENT: &str = "Saaja\nMottagare";
+const PAYER: &str = "Maksajan\nnimi ja\nosoite\nBetalarens\nnamn och\nadress";
+const SIGNATURE: &str = "Allekirjoitus\nUnderskrift";
+const FROM_ACCOUNT: &str = "Tililtä nro\nFrån konto nr";
+const REFERENCE: &str = "Viitenro\nRef.nr";
+const DUE_DATE: &str = "Eräpäivä\nFörfallodag";
+const EURO: &str = "Euro";
+
+pub(crate) fn finnish_transfer_slip(invoice: &Invoice, rendering_context: &RenderingContext, with_bank_barcode: bool) -> Result<Vec<DrawOp>, Error> {
+    if invoice.currency != "EUR" {
+        return Err(Error::msg(format!("Finnish bank transfer slip only supports EUR payments, got: {}", invoice.currency)));
+    }
+    let reference = match invoice.reference_id.as_deref() {
+        Some(reference) if is_rf_reference(reference) => validate_rf_reference(reference)?,
+        Some(reference) => validate_finnish_reference(reference)?,
+        None => String::new()
+    };
+    let due_date = Date::parse(&invoice.due_date)?;
+    let regular_font = rendering_context.regular_font;
+    let bold_font = rendering_context.bold_font;
+    let heading = |value: &str, x: f32, top: f32| Label::new(value, 6.0, regular_font).render_at(x, top - 2.5);
+    let value = |value: &str, x: f32, top: f32| Label::new(value, 10.0, regular_font).render_at(x, top - 5.0);
+
+    let mut ops = grid();
+    ops.extend(Label::new(TITLE, 8.0, bold_font).render_at(MIDDLE_X + 2.0, FINNISH_TRANSFER_SLIP_HEIGHT - 4.0));
+    ops.extend(Label::new(
+        &format!("{}: {}", rendering_context.translations.invoice.number, invoice.invoice_number),
+        10.0,
+        regular_font
+    ).render_at(MIDDLE_X + 2.0, FINNISH_TRANSFER_SLIP_HEIGHT - 12.0));
+
+    ops.extend(heading(RECIPIENT_ACCOUNT, LEFT, FINNISH_TRANSFER_SLIP_HEIGHT));
+    ops.extend(value(&invoice.bank_details.account_number, VALUE_X + 2.0, FINNISH_TRANSFER_SLIP_HEIGHT - 3.0));
+    ops.extend(heading(BIC, BIC_X + 2.0, FINNISH_TRANSFER_SLIP_HEIGHT));
+    ops.extend(value(&invoice.bank_details.bic_code, BIC_X + 2.0, FINNISH_TRANSFER_SLIP_HEIGHT - 3.0));
+
+    ops.extend(heading(RECIPIENT, LEFT, RECIPIENT_Y));
+    ops.extend(value(&invoice.billed_by.name, VALUE_X + 2.0, RECIPIENT_Y - 1.0));
+
+    let billed_to = &invoice.billed_to;
+    let payer_lines: Vec<&str> = [Some(&billed_to.name), Some(&billed_to.address_line_1), billed_to.address_line_2.as_ref(), billed_to.address_line_3.as_ref()]
+        .into_iter()
+        .flatten()
+        .map(|line| line.as_str())
+        .collect();
+    ops.extend(heading(PAYER, LEFT, PAYER_Y));
+    ops.extend(value(&payer_lines.join("\n"), VALUE_X + 2.0, PAYER_Y - 1.0));
+
+    ops.extend(heading(SIGNATURE, LEFT, SIGNATURE_Y));
+    ops.extend(heading(FROM_ACCOUNT, LEFT, FROM_ACCOUNT_Y));
+
+    ops.extend(heading(REFERENCE, MIDDLE_X + 2.0, SIGNATURE_Y));
+    ops.extend(value(&reference, MIDDLE_X + 22.0, SIGNATURE_Y - 1.0));
+    ops.extend(heading(DUE_DATE, MIDDLE_X + 2.0, FROM_ACCOUNT_Y));
+    ops.extend(value(&due_date.to_dd_mm_yyyy(), MIDDLE_X + 22.0, FROM_ACCOUNT_Y - 1.0));
+    ops.extend(heading(EURO, EURO_X + 2.0, FROM_ACCOUNT_Y));
+    ops.extend(value(&format_amount(&invoice.totals().total_price, "fi-FI"), EURO_X + 15.0, FROM_ACCOUNT_Y - 1.0));
+
+    if with_bank_barcode {
+        let barcode = bank_barcode_from_invoice(invoice)?;
+        ops.extend(Code128::new(&barcode, 0.3, 13.0)?.render_at(VALUE_X, BOTTOM_Y - 4.0));
+    }
+    Ok(ops)
+}
+
+fn grid() -> Vec<DrawOp> {
+    vec![
+        DrawOp::SetLineWidth(0.5),
+        DrawOp::SetLineDash(Some(3.0)),
+        stroke_line(&[(0.0, FINNISH_TRANSFER_SLIP_HEIGHT), (210.0, FINNISH_TRANSFER_SLIP_HEIGHT)]),
+        DrawOp::SetLineDash(None),
+        DrawOp::SetLineWidth(1.0),
+        stroke_line(&[(VALUE_X, FINNISH_TRANSFER_SLIP_HEIGHT), (VALUE_X, BOTTOM_Y)]),
+        stroke_line(&[(MIDDLE_X, FINNISH_TRANSFER_SLIP_HEIGHT), (MIDDLE_X, BOTTOM_Y)]),
+        stroke_line(&[(LEFT, SIGNATURE_Y), (RIGHT, SIGNATURE_Y)]),
+        stroke_line(&[(LEFT, BOTTOM_Y), (RIGHT, BOTTOM_Y)]),
+        DrawOp::SetLineWidth(0.5),
+        stroke_line(&[(LEFT, RECIPIENT_Y), (MIDDLE_X, RECIPIENT_Y)]),
+        stroke_line(&[(LEFT, PAYER_Y), (MIDDLE_X, PAYER_Y)]),
+        stroke_line(&[(LEFT, FROM_ACCOUNT_Y), (RIGHT, FROM_ACCOUNT_Y)]),
+        stroke_line(&[(BIC_X, FINNISH_TRANSFER_SLIP_HEIGHT), (BIC_X, RECIPIENT_Y)]),
+        stroke_line(&[(EURO_X, FROM_ACCOUNT_Y), (EURO_X, BOTTOM_Y)])
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::INVOICE;
+    use crate::invoice::parse_invoice_json;
+    use crate::locale::get_translations;
+    use crate::renderer::options::RenderOptions;
+    use crate::renderer::rendering_context::init_rendering_context;
+
+    fn slip(invoice: &Invoice) -> Result<Vec<DrawOp>, Error> {
+        let rendering_context = init_rendering_context(invoice, get_translations(&invoice.locale).unwrap(), &invoice.locale, &RenderOptions::default()).unwrap();
+        finnish_transfer_slip(invoice, &rendering_context, false)
+    }
+
+    fn texts(ops: &[DrawOp]) -> Vec<&str> {
+        ops.iter()
+            .filter_map(|op| match op {
+                DrawOp::Text { text, .. } => Some(text.as_str()),
+                _ => None
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_print_the_payment_details() {
+        let mut invoice = parse_invoice_json(INVOICE).unwrap();
+        invoice.due_date = "1.3.2025".to_owned();
+        let ops = slip(&invoice).unwrap();
+        let texts = texts(&ops);
+        for value in ["FI21 1234 5600 0007 85", "NDEAFIHH", "Example Company Ltd", "Client GmbH", "Hauptstrasse 1", "RF18539007547034", "01.03.2025", "251,00"] {
+            assert!(texts.contains(&value), "{} missing from {:?}", value, texts);
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_references_and_due_dates() {
+        let mut invoice = parse_invoice_json(INVOICE).unwrap();
+        invoice.reference_id = Some("20250001".to_owned());
+        assert_eq!(slip(&invoice).err().unwrap().to_string(), "Invalid Finnish reference number check digit: 20250001");
+
+        let mut invoice = parse_invoice_json(INVOICE).unwrap();
+        invoice.reference_id = Some("202500010".to_owned());
+        invoice.due_date = "31.02.2025".to_owned();
+        assert_eq!(slip(&invoice).err().unwrap().to_string(), "Invalid date: 31.02.2025");
+
+        invoice.currency = "SEK".to_owned();
+        assert!(slip(&invoice).is_err());
+    }
+}
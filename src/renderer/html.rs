@@ -0,0 +1,270 @@
+use anyhow::Error;
+use quick_xml::escape::escape;
+use std::fmt::Write;
+use crate::components::qr_code::QrCode;
+use crate::format::{format_price, format_vat};
+use crate::image::load_image;
+use crate::invoice::{BillingInformation, Invoice, InvoiceTotals};
+use crate::locale::get_translations;
+use crate::locale::translations::Translations;
+use crate::payment::bank_barcode::bank_barcode_from_invoice;
+use crate::payment::epc::EpcPayment;
+use crate::renderer::options::RenderOptions;
+use crate::renderer::rendering_context::currency_symbol;
+
+const EPC_QR_CODE_SIZE: f32 = 30.0;
+
+/// A4 page when printed, a centered page of the same width on screen. The layout only uses
+/// tables and plain blocks so that it also survives in email clients.
+const STYLE: &str = "
+@page { size: A4; margin: 15mm 15mm 30mm 15mm; }
+body { margin: 0; font-family: Helvetica, Arial, \"DejaVu Sans\", \"Liberation Sans\", sans-serif; font-size: 11pt; color: #000; background: #fff; }
+.page { max-width: 180mm; margin: 0 auto; padding: 15mm; }
+table { border-collapse: collapse; }
+td, th { padding: 0.5mm 2mm 0.5mm 0; text-align: left; vertical-align: top; }
+.header { width: 100%; margin-bottom: 8mm; }
+.header h1 { margin: 0; font-size: 22pt; }
+.logo { max-height: 25mm; max-width: 80mm; }
+.parties { width: 100%; margin-bottom: 15mm; }
+.parties > tbody > tr > td { width: 50%; padding: 0; }
+.invoice-lines { width: 100%; font-size: 10pt; margin-bottom: 8mm; }
+.invoice-lines th { font-weight: bold; }
+.summary { margin-left: auto; font-size: 10pt; margin-bottom: 8mm; }
+.summary .total { font-weight: bold; }
+.text { font-size: 10pt; white-space: pre-line; }
+.payment { font-size: 7pt; margin: 8mm 0; }
+.footer { width: 100%; border-top: 0.8pt solid #000; padding-top: 2mm; font-size: 7pt; }
+.footer td { width: 33%; padding: 0 2mm 0 0; }
+.footer a { color: inherit; }
+@media print {
+  .page { max-width: none; padding: 0; }
+  .footer { position: fixed; bottom: -22mm; left: 0; right: 0; }
+}
+";
+
+/// Renders the invoice as a standalone HTML page with the same content as `renderer::render`.
+/// Payment slips are only drawn into PDFs, the EPC QR code is drawn as inline SVG.
+pub fn render_html(invoice: &Invoice, options: &RenderOptions) -> Result<String, Error> {
+    let translations = get_translations(&invoice.locale)?;
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>")?;
+    writeln!(html, r#"<html lang="{}">"#, text(&invoice.locale))?;
+    writeln!(html, "<head>")?;
+    writeln!(html, r#"<meta charset="utf-8">"#)?;
+    writeln!(html, "<title>{} {}</title>", text(translations.invoice.invoice), text(&invoice.invoice_number))?;
+    writeln!(html, "<style>{}</style>", STYLE)?;
+    writeln!(html, "</head>")?;
+    writeln!(html, "<body>")?;
+    writeln!(html, r#"<div class="page">"#)?;
+
+    // The logo is inlined so that the page also shows it on other machines and in emails
+    let logo = match invoice.billed_by.logo.as_deref() {
+        Some(logo_url) => format!(r#"<img class="logo" src="{}" alt="{}">"#, load_image(logo_url)?.to_data_uri(), text(&invoice.billed_by.name)),
+        None => String::new()
+    };
+    writeln!(html, r#"<table class="header"><tr><td>{}</td><td><h1>{}</h1></td></tr></table>"#, logo, text(translations.invoice.invoice))?;
+
+    writeln!(html, r#"<table class="parties"><tr><td>{}</td><td>{}</td></tr></table>"#, billed_to(invoice, translations), invoice_info(invoice, translations))?;
+    write_invoice_lines(&mut html, invoice, translations)?;
+    write_summary(&mut html, invoice, translations)?;
+    for paragraph in [invoice.note.as_ref(), invoice.invoice_description.as_ref()].into_iter().flatten() {
+        writeln!(html, r#"<p class="text">{}</p>"#, text(paragraph))?;
+    }
+    write_payment_codes(&mut html, invoice, translations, options)?;
+    write_billed_by(&mut html, invoice, translations)?;
+
+    writeln!(html, "</div>")?;
+    writeln!(html, "</body>")?;
+    writeln!(html, "</html>")?;
+    Ok(html)
+}
+
+fn text(value: &str) -> String {
+    escape(value).into_owned()
+}
+
+/// Web address of a link, "https://" is assumed when the address has no scheme. Other schemes than
+/// http(s), such as `javascript:`, are not linked.
+fn web_uri(url: &str) -> Option<String> {
+    let url = url.trim();
+    match url.split_once(':').map(|(scheme, _)| scheme.to_ascii_lowercase()) {
+        None => Some(format!("https://{}", url)),
+        Some(scheme) if scheme == "http" || scheme == "https" => Some(url.to_owned()),
+        // A host name followed by a port
+        Some(scheme) if scheme.contains('.') => Some(format!("https://{}", url)),
+        Some(_) => None
+    }
+}
+
+fn billed_to(invoice: &Invoice, translations: &Translations) -> String {
+    let party = &invoice.billed_to;
+    let mut lines = vec![text(&party.name), text(&party.address_line_1)];
+    lines.extend([party.address_line_2.as_ref(), party.address_line_3.as_ref()].into_iter().flatten().map(|line| text(line)));
+    if let Some(company_id) = party.company_id.as_ref() {
+        lines.push(format!("{}: {}", text(translations.company_id), text(company_id)));
+    }
+    if let Some(vat_id) = party.vat_id.as_ref() {
+        lines.push(format!("{}: {}", text(translations.vat_id), text(vat_id)));
+    }
+    format!(r#"<div class="billed-to">{}</div>"#, lines.join("<br>"))
+}
+
+fn invoice_info(invoice: &Invoice, translations: &Translations) -> String {
+    let rows = [
+        (translations.invoice.number, invoice.invoice_number.as_str()),
+        (translations.invoice.date, invoice.billed_at.as_str()),
+        (translations.invoice.due_date, invoice.due_date.as_str()),
+        (translations.invoice.reference_number, invoice.reference_id.as_deref().unwrap_or("")),
+        (translations.account.number, invoice.bank_details.account_number.as_str()),
+        (translations.account.bic, invoice.bank_details.bic_code.as_str())
+    ];
+    let rows: String = rows.iter()
+        .map(|(label, value)| format!("<tr><td>{}:</td><td>{}</td></tr>", text(label), text(value)))
+        .collect();
+    format!(r#"<table class="invoice-info">{}</table>"#, rows)
+}
+
+fn write_invoice_lines(html: &mut String, invoice: &Invoice, translations: &Translations) -> Result<(), Error> {
+    let currency = currency_symbol(&invoice.currency);
+    let line = &translations.invoice.line;
+    writeln!(html, r#"<table class="invoice-lines">"#)?;
+    writeln!(html, "<thead><tr><th>{}</th><th>{}</th><th>{}</th><th>{}</th><th>{} %</th></tr></thead>",
+        text(line.item), text(line.quantity), text(line.price), text(line.price_without_tax), text(line.vat))?;
+    writeln!(html, "<tbody>")?;
+    let line_prices_without_vat = invoice.totals().line_prices_without_vat;
+    for (invoice_line, price_without_vat) in invoice.invoice_lines.iter().zip(line_prices_without_vat.iter()) {
+        writeln!(html, "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            text(&invoice_line.name),
+            invoice_line.count,
+            text(&format_price(&invoice_line.price, currency, &invoice.locale)),
+            text(&format_price(price_without_vat, currency, &invoice.locale)),
+            text(&format_vat(&invoice.vat_percent)))?;
+    }
+    writeln!(html, "</tbody>")?;
+    writeln!(html, "</table>")?;
+    Ok(())
+}
+
+fn write_summary(html: &mut String, invoice: &Invoice, translations: &Translations) -> Result<(), Error> {
+    let currency = currency_symbol(&invoice.currency);
+    let InvoiceTotals { total_price, total_vat, total_price_without_vat, .. } = invoice.totals();
+    let rows = [
+        ("", format!("{}:", translations.invoice.total_price_without_tax), format_price(&total_price_without_vat, currency, &invoice.locale)),
+        ("", format!("{} {} %:", translations.invoice.vat, format_vat(&invoice.vat_percent)), format_price(&total_vat, currency, &invoice.locale)),
+        (" class=\"total\"", format!("{}:", translations.invoice.total_price), format_price(&total_price, currency, &invoice.locale))
+    ];
+    writeln!(html, r#"<table class="summary">"#)?;
+    for (class, label, value) in rows {
+        writeln!(html, "<tr{}><td>{}</td><td>{}</td></tr>", class, text(&label), text(&value))?;
+    }
+    writeln!(html, "</table>")?;
+    Ok(())
+}
+
+fn write_payment_codes(html: &mut String, invoice: &Invoice, translations: &Translations, options: &RenderOptions) -> Result<(), Error> {
+    let mut codes = Vec::new();
+    if options.epc_qr_code_for(invoice).is_some() {
+        let payment = EpcPayment::from_invoice(invoice)?;
+        let qr_code = QrCode::new(&payment.to_payload(), EPC_QR_CODE_SIZE)?.with_quiet_zone();
+        codes.push(format!(r#"<div class="epc-qr-code">{}<br>{}</div>"#, qr_code.to_svg(), text(translations.payment.epc_qr_code)));
+    }
+    if options.bank_barcode_for(invoice) {
+        let barcode = bank_barcode_from_invoice(invoice)?;
+        codes.push(format!(r#"<div class="virtual-barcode">{}: {}</div>"#, text(translations.payment.virtual_barcode), text(&barcode)));
+    }
+    if !codes.is_empty() {
+        writeln!(html, r#"<div class="payment">{}</div>"#, codes.join(""))?;
+    }
+    Ok(())
+}
+
+fn write_billed_by(html: &mut String, invoice: &Invoice, translations: &Translations) -> Result<(), Error> {
+    let billed_by: &BillingInformation = &invoice.billed_by;
+    let optional = |value: Option<&String>| value.map(|value| text(value)).unwrap_or_default();
+    let link = |href: Option<String>, value: &str| match href {
+        Some(href) => format!(r#"<a href="{}">{}</a>"#, text(&href), text(value)),
+        None => text(value)
+    };
+    let labelled = |label: &str, value: Option<&String>| value.map(|value| format!("{}: {}", text(label), text(value))).unwrap_or_default();
+    let rows = [
+        [text(&billed_by.name), labelled(translations.company_id, billed_by.company_id.as_ref()),
+            billed_by.email.as_ref().map(|email| link(Some(format!("mailto:{}", email)), email)).unwrap_or_default()],
+        [text(&billed_by.address_line_1), labelled(translations.vat_id, billed_by.vat_id.as_ref()), optional(billed_by.phone_number.as_ref())],
+        [optional(billed_by.address_line_2.as_ref()), text(&invoice.bank_details.account_number),
+            billed_by.website.as_ref().map(|website| link(web_uri(website), website)).unwrap_or_default()],
+        [optional(billed_by.address_line_3.as_ref()), text(&invoice.bank_details.bic_code),
+            invoice.payment_url.as_ref().map(|payment_url| link(web_uri(payment_url), payment_url)).unwrap_or_default()],
+        [optional(billed_by.detail.as_ref()), String::new(), String::new()]
+    ];
+    writeln!(html, r#"<table class="footer">"#)?;
+    for row in rows.iter().filter(|row| row.iter().any(|cell| !cell.is_empty())) {
+        writeln!(html, "<tr><td>{}</td><td>{}</td><td>{}</td></tr>", row[0], row[1], row[2])?;
+    }
+    writeln!(html, "</table>")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::renderer::options::QrCodePosition;
+    use crate::test_fixtures::invoice_with;
+
+    #[test]
+    fn should_render_invoice_content() {
+        let invoice = invoice_with(json!({
+            "locale": "fi-FI",
+            "billed_by": { "name": "Example <Company> Oy" },
+            "note": "Thank you!\nSee you again",
+            "invoice_lines": [
+                { "name": "Computer services", "count": 1, "price": 89.99 },
+                { "name": "CD discs", "count": 5, "price": 35 }
+            ]
+        }));
+        let html = render_html(&invoice, &RenderOptions::default()).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>\n<html lang=\"fi-FI\">"));
+        assert!(html.contains("<title>Lasku 2025-0001</title>"));
+        assert!(html.contains("<h1>Lasku</h1>"));
+        assert!(html.contains("<td>Computer services</td><td>1</td><td>89,99 €</td><td>71,71 €</td><td>25,5</td>"));
+        assert!(html.contains("<tr class=\"total\"><td>Summa yhteensä:</td><td>124,99 €</td></tr>"));
+        assert!(html.contains("<p class=\"text\">Thank you!\nSee you again</p>"));
+        assert!(html.contains("<td>Example &lt;Company&gt; Oy</td>"));
+        assert!(html.contains(r#"<a href="mailto:contact@examplecompany.fi">contact@examplecompany.fi</a>"#));
+        assert!(!html.contains("<svg"));
+    }
+
+    #[test]
+    fn should_render_payment_codes() {
+        let options = RenderOptions {
+            epc_qr_code: Some(QrCodePosition::BottomLeft),
+            bank_barcode: true,
+            ..RenderOptions::default()
+        };
+        let html = render_html(&invoice_with(json!({ "locale": "fi-FI" })), &options).unwrap();
+        assert!(html.contains(r#"<div class="epc-qr-code"><svg"#));
+        assert!(html.contains("Virtuaaliviivakoodi: "));
+    }
+
+    #[test]
+    fn should_inline_the_logo() {
+        let invoice = invoice_with(json!({ "billed_by": { "logo": "examples/yritys_logo_small.png" } }));
+        let html = render_html(&invoice, &RenderOptions::default()).unwrap();
+        assert!(html.contains(r#"<img class="logo" src="data:image/png;base64,iVBORw0KGgo"#));
+    }
+
+    #[test]
+    fn should_only_link_web_addresses() {
+        assert_eq!(web_uri("www.example.fi").unwrap(), "https://www.example.fi");
+        assert_eq!(web_uri("http://example.fi/pay?id=1").unwrap(), "http://example.fi/pay?id=1");
+        assert_eq!(web_uri("example.fi:8080/pay").unwrap(), "https://example.fi:8080/pay");
+        assert_eq!(web_uri("javascript:alert(1)"), None);
+        assert_eq!(web_uri(" JavaScript:alert(1)"), None);
+
+        let invoice = invoice_with(json!({ "billed_by": { "website": "www.example.fi" }, "payment_url": "javascript:alert(1)" }));
+        let html = render_html(&invoice, &RenderOptions::default()).unwrap();
+        assert!(html.contains(r#"<a href="https://www.example.fi">www.example.fi</a>"#));
+        assert!(html.contains("<td>javascript:alert(1)</td>"));
+        assert!(!html.contains("href=\"javascript:"));
+    }
+}
@@ -0,0 +1,139 @@
+use crate::drawing::{A4, A5, LETTER};
+use crate::export::factur_x::FacturXProfile;
+use crate::invoice::Invoice;
+use crate::renderer::template::Template;
+use crate::theme::Theme;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QrCodePosition {
+    BottomLeft,
+    BottomRight,
+    /// Top left corner of the QR code in mm from the lower left corner of the page
+    At { x: f32, y: f32 }
+}
+
+/// Payment slip printed at the bottom of the page, the footer is moved above it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaymentSlip {
+    /// Swiss QR-bill payment part and receipt, for CHF and EUR invoices with a Swiss IBAN
+    SwissQrBill,
+    /// Finnish bank transfer slip (tilisiirtolomake), for EUR invoices
+    FinnishTransferSlip
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PageSize {
+    #[default]
+    A4,
+    A5,
+    Letter,
+    /// Width and height in mm in the portrait orientation
+    Custom { width: f32, height: f32 }
+}
+
+impl PageSize {
+    /// Width and height of the page in mm in the portrait orientation
+    pub fn dimensions(&self) -> (f32, f32) {
+        match self {
+            PageSize::A4 => A4,
+            PageSize::A5 => A5,
+            PageSize::Letter => LETTER,
+            PageSize::Custom { width, height } => (*width, *height)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Orientation {
+    #[default]
+    Portrait,
+    Landscape
+}
+
+/// Space between the edges of the page and the content in mm, the footer is printed inside the
+/// bottom margin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Margins {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32
+}
+
+impl Default for Margins {
+    fn default() -> Self {
+        Margins { top: 20.0, right: 10.0, bottom: 25.0, left: 15.0 }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    /// Where to place the EPC (SEPA credit transfer) QR code, `None` leaves it out.
+    /// The QR code is only rendered for invoices in EUR.
+    pub epc_qr_code: Option<QrCodePosition>,
+    /// Render the Finnish bank barcode and its virtual barcode digits above the footer,
+    /// or inside the Finnish bank transfer slip when it is printed. The barcode is only
+    /// rendered for invoices in EUR.
+    pub bank_barcode: bool,
+    pub payment_slip: Option<PaymentSlip>,
+    pub page_size: PageSize,
+    pub orientation: Orientation,
+    pub margins: Margins,
+    /// Blocks, regions, fonts and table style of the PDF layout
+    pub template: Template,
+    /// Brand colors and fonts, `None` uses the theme of the seller in the invoice if it has one
+    pub theme: Option<Theme>,
+    /// Produce a Factur-X / ZUGFeRD PDF/A-3 with the invoice embedded as factur-x.xml
+    pub factur_x: Option<FacturXProfile>,
+    /// The invoice JSON as read from the input, embedded into the PDF as it is. `None` embeds the
+    /// invoice serialized to JSON, e.g. for invoices imported from XML.
+    pub source_json: Option<Vec<u8>>
+}
+
+impl RenderOptions {
+    /// Position of the EPC QR code on the invoice, `None` when it is not requested or the invoice is not in EUR
+    pub fn epc_qr_code_for(&self, invoice: &Invoice) -> Option<QrCodePosition> {
+        self.epc_qr_code.filter(|_| invoice.currency == "EUR")
+    }
+
+    /// Whether the bank barcode is rendered on the invoice, only invoices in EUR get one
+    pub fn bank_barcode_for(&self, invoice: &Invoice) -> bool {
+        self.bank_barcode && invoice.currency == "EUR"
+    }
+
+    /// Messages about the requested payment codes left out of the invoice
+    pub fn skipped_payment_codes(&self, invoice: &Invoice) -> Vec<String> {
+        let mut skipped = Vec::new();
+        if self.epc_qr_code.is_some() && self.epc_qr_code_for(invoice).is_none() {
+            skipped.push(format!("The EPC QR code is left out, it only supports EUR payments and the invoice is in {}", invoice.currency));
+        }
+        if self.bank_barcode && !self.bank_barcode_for(invoice) {
+            skipped.push(format!("The bank barcode is left out, it only supports EUR payments and the invoice is in {}", invoice.currency));
+        }
+        skipped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::INVOICE;
+    use crate::invoice::parse_invoice_json;
+
+    #[test]
+    fn should_leave_out_payment_codes_of_invoices_not_in_eur() {
+        let options = RenderOptions { epc_qr_code: Some(QrCodePosition::BottomLeft), bank_barcode: true, ..Default::default() };
+        let mut invoice = parse_invoice_json(INVOICE).unwrap();
+        assert_eq!(options.epc_qr_code_for(&invoice), Some(QrCodePosition::BottomLeft));
+        assert!(options.bank_barcode_for(&invoice));
+        assert!(options.skipped_payment_codes(&invoice).is_empty());
+
+        invoice.currency = "SEK".to_owned();
+        assert_eq!(options.epc_qr_code_for(&invoice), None);
+        assert!(!options.bank_barcode_for(&invoice));
+        assert_eq!(options.skipped_payment_codes(&invoice), vec![
+            "The EPC QR code is left out, it only supports EUR payments and the invoice is in SEK",
+            "The bank barcode is left out, it only supports EUR payments and the invoice is in SEK"
+        ]);
+    }
+}
@@ -0,0 +1,126 @@
+use std::ops::Range;
+
+/// Vertical space for the invoice line table, the y coordinates are in mm from the bottom of the page
+pub(crate) struct PageSpace {
+    /// Top edge of the table on the first page and on the following pages, below the blocks above it
+    pub(crate) first_page_top: f32,
+    pub(crate) next_page_top: f32,
+    /// Height of the table header, and of the brought forward row below it on the following pages
+    pub(crate) header_height: f32,
+    pub(crate) brought_forward_height: f32,
+    /// Lowest bottom edge of the lines on a page continuing on the next page, leaving room for the
+    /// carried forward row
+    pub(crate) page_break_bottom: f32,
+    /// Height of the summary, note and description below the lines, including the space above them
+    pub(crate) tail_height: f32,
+    /// Lowest bottom edge of the summary, note and description above the footer and payment codes
+    pub(crate) content_bottom: f32
+}
+
+impl PageSpace {
+    /// Bottom edge of the table on a page with the given rows, the following pages also have the
+    /// brought forward row
+    pub(crate) fn lines_bottom(&self, first_page: bool, row_heights: &[f32]) -> f32 {
+        let top = if first_page { self.first_page_top } else { self.next_page_top - self.brought_forward_height };
+        top - self.header_height - row_heights.iter().sum::<f32>()
+    }
+
+    /// Number of rows whose bottom edge stays above the page break
+    fn capacity(&self, first_page: bool, row_heights: &[f32]) -> usize {
+        (0..row_heights.len())
+            .take_while(|&index| self.lines_bottom(first_page, &row_heights[..=index]) >= self.page_break_bottom)
+            .count()
+    }
+}
+
+/// Splits the invoice lines into pages by their row heights, filling each page down to the page
+/// break and moving at least one line to the last page when the summary does not fit below the lines
+pub(crate) fn paginate(row_heights: &[f32], space: &PageSpace) -> Vec<Range<usize>> {
+    let fits = |first_page: bool, rows: &[f32]| space.lines_bottom(first_page, rows) - space.tail_height >= space.content_bottom;
+    let mut pages = Vec::new();
+    let mut start = 0;
+    let mut first_page = true;
+    loop {
+        let remaining = &row_heights[start..];
+        if fits(first_page, remaining) {
+            pages.push(start..row_heights.len());
+            return pages;
+        }
+        let capacity = space.capacity(first_page, remaining);
+        let count = if remaining.len() <= capacity { remaining.len().saturating_sub(1) } else { capacity.max(1) };
+        if count == 0 && !first_page {
+            // The summary does not fit even on an empty page, let it run over the footer
+            pages.push(start..row_heights.len());
+            return pages;
+        }
+        pages.push(start..start + count);
+        start += count;
+        first_page = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROW_HEIGHT: f32 = 5.0;
+
+    const PAGE_SPACE: PageSpace = PageSpace {
+        first_page_top: 200.0,
+        next_page_top: 265.0,
+        header_height: 7.5,
+        brought_forward_height: ROW_HEIGHT,
+        page_break_bottom: 32.5,
+        tail_height: 40.0,
+        content_bottom: 27.0
+    };
+
+    #[test]
+    fn should_keep_short_invoices_on_one_page() {
+        assert_eq!(paginate(&[ROW_HEIGHT; 3], &PAGE_SPACE), vec![0..3]);
+        assert_eq!(PAGE_SPACE.lines_bottom(true, &[ROW_HEIGHT; 3]), 177.5);
+        assert_eq!(paginate(&[], &PAGE_SPACE), vec![0..0]);
+    }
+
+    #[test]
+    fn should_fill_first_page_when_summary_fits() {
+        // 200 - 7.5 - 5 * 25 - 40 = 27.5 still fits above the footer
+        assert_eq!(paginate(&[ROW_HEIGHT; 25], &PAGE_SPACE), vec![0..25]);
+        assert_eq!(paginate(&[ROW_HEIGHT; 26], &PAGE_SPACE), vec![0..25, 25..26]);
+    }
+
+    #[test]
+    fn should_split_long_invoices() {
+        let pages = paginate(&[ROW_HEIGHT; 40], &PAGE_SPACE);
+        // The first page fits 32 lines down to the page break
+        assert_eq!(pages, vec![0..32, 32..40]);
+        assert_eq!(PAGE_SPACE.lines_bottom(true, &[ROW_HEIGHT; 32]), 32.5);
+        assert_eq!(PAGE_SPACE.lines_bottom(false, &[]), 252.5);
+    }
+
+    #[test]
+    fn should_move_a_line_to_the_summary_page() {
+        // 30 lines fit on the first page but leave no room for the summary
+        assert_eq!(paginate(&[ROW_HEIGHT; 30], &PAGE_SPACE), vec![0..29, 29..30]);
+        let pages = paginate(&[ROW_HEIGHT; 200], &PAGE_SPACE);
+        assert_eq!(pages.first(), Some(&(0..32)));
+        assert_eq!(pages[1], 32..76);
+        assert_eq!(pages.last().map(|page| page.end), Some(200));
+        assert!(pages.windows(2).all(|pair| pair[0].end == pair[1].start));
+    }
+
+    #[test]
+    fn should_fit_fewer_wrapped_lines() {
+        // Every other line wraps to two lines, 21 rows take the height of 32 single lines
+        let row_heights: Vec<f32> = (0..40).map(|index| if index % 2 == 0 { ROW_HEIGHT * 2.0 } else { ROW_HEIGHT }).collect();
+        let pages = paginate(&row_heights, &PAGE_SPACE);
+        assert_eq!(pages, vec![0..21, 21..40]);
+        assert_eq!(PAGE_SPACE.lines_bottom(true, &row_heights[0..21]), 32.5);
+    }
+
+    #[test]
+    fn should_stop_when_summary_never_fits() {
+        let page_space = PageSpace { tail_height: 300.0, ..PAGE_SPACE };
+        assert_eq!(paginate(&[ROW_HEIGHT; 2], &page_space), vec![0..1, 1..2]);
+    }
+}
@@ -1,33 +1,156 @@
 use anyhow::Error;
-use printpdf::*;
+use crate::components::table::{Border, TableStyle};
+use crate::drawing::{Font, FontFamily, FontWeight};
 use crate::invoice::Invoice;
 use crate::locale::{translations::Translations};
-use crate::fonts::FontManager;
+use crate::renderer::options::{Margins, Orientation, RenderOptions};
+use crate::renderer::template::Template;
+use crate::theme::Theme;
 
 pub(crate) struct RenderingContext {
     pub(crate) translations: &'static Translations,
     pub(crate) currency: String,
     pub(crate) locale: String,
-    pub(crate) regular_font_id: FontId,
-    pub(crate) bold_font_id: FontId
+    /// Fonts of the family of the theme
+    pub(crate) regular_font: Font,
+    pub(crate) bold_font: Font,
+    pub(crate) page: PageLayout,
+    pub(crate) template: Template,
+    pub(crate) theme: Theme
 }
 
-pub(crate) fn init_rendering_context(doc: &mut PdfDocument, invoice: &Invoice, translations: &'static Translations, locale: &str) -> Result<RenderingContext, Error> {
-    let currency = if &invoice.currency == "EUR" {
+/// Size of the page in the orientation it is printed in and its margins, in mm
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PageLayout {
+    pub(crate) width: f32,
+    pub(crate) height: f32,
+    pub(crate) margins: Margins
+}
+
+impl PageLayout {
+    pub(crate) fn new(options: &RenderOptions) -> Result<PageLayout, Error> {
+        let (portrait_width, portrait_height) = options.page_size.dimensions();
+        let (width, height) = match options.orientation {
+            Orientation::Portrait => (portrait_width, portrait_height),
+            Orientation::Landscape => (portrait_height, portrait_width)
+        };
+        let page = PageLayout { width, height, margins: options.margins };
+        if page.content_width() <= 0.0 || page.content_top() <= page.margins.bottom {
+            return Err(Error::msg(format!("The margins leave no room for the content on a {} x {} mm page", width, height)));
+        }
+        Ok(page)
+    }
+
+    pub(crate) fn content_width(&self) -> f32 {
+        self.width - self.margins.left - self.margins.right
+    }
+
+    /// Top edge of the content below the top margin
+    pub(crate) fn content_top(&self) -> f32 {
+        self.height - self.margins.top
+    }
+}
+
+impl RenderingContext {
+    pub(crate) fn font(&self, weight: FontWeight) -> Font {
+        match weight {
+            FontWeight::Regular => self.regular_font,
+            FontWeight::Bold => self.bold_font
+        }
+    }
+
+    /// Style of the invoice line, subtotal and summary tables
+    pub(crate) fn table_style(&self) -> TableStyle {
+        themed_table_style(&self.theme, self.template.table_style)
+    }
+
+    /// Style of the tables laying out the addresses and invoice details
+    pub(crate) fn layout_table_style(&self) -> TableStyle {
+        TableStyle {
+            text_color: self.theme.text_color,
+            ..TableStyle::default()
+        }
+    }
+}
+
+/// The table style with the header, text and border colors of the theme
+fn themed_table_style(theme: &Theme, style: TableStyle) -> TableStyle {
+    let rule = |border: Option<Border>| border.map(|border| Border {
+        color: theme.rule_color.unwrap_or(border.color),
+        ..border
+    });
+    TableStyle {
+        outer_border: rule(style.outer_border),
+        row_border: rule(style.row_border),
+        column_border: rule(style.column_border),
+        header_border: rule(style.header_border),
+        header_fill: theme.accent_color.or(style.header_fill),
+        header_text_color: theme.primary_color.or(style.header_text_color),
+        text_color: theme.text_color.or(style.text_color),
+        ..style
+    }
+}
+
+/// Euro amounts are written with the € sign, other currencies with their code
+pub(crate) fn currency_symbol(currency: &str) -> &str {
+    if currency == "EUR" {
         "€"
     } else {
-        &invoice.currency
-    };
-
-    let font_manager = FontManager::initialize(doc)?;
-    let regular_font_id = font_manager.regular_font();
-    let bold_font_id = font_manager.bold_font();    
+        currency
+    }
+}
 
+/// The template is scaled to the content width of the page
+pub(crate) fn init_rendering_context(invoice: &Invoice, translations: &'static Translations, locale: &str, options: &RenderOptions) -> Result<RenderingContext, Error> {
+    let page = PageLayout::new(options)?;
+    let theme = options.theme.clone().or_else(|| invoice.billed_by.theme.clone()).unwrap_or_default();
+    let family = theme.font_family.as_deref().map(FontFamily::new);
     Ok(RenderingContext {
         translations,
-        currency: currency.to_string(),
+        currency: currency_symbol(&invoice.currency).to_string(),
         locale: locale.to_string(),
-        regular_font_id,
-        bold_font_id
+        regular_font: Font { family, ..Font::REGULAR },
+        bold_font: Font { family, ..Font::BOLD },
+        page,
+        template: options.template.scaled_to(page.content_width()),
+        theme
     })
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::options::PageSize;
+    use crate::theme::Color;
+
+    #[test]
+    fn should_lay_out_pages_in_the_orientation() {
+        let options = RenderOptions { page_size: PageSize::A5, orientation: Orientation::Landscape, ..Default::default() };
+        let page = PageLayout::new(&options).unwrap();
+        assert_eq!((page.width, page.height), (210.0, 148.0));
+        assert_eq!(page.content_width(), 185.0);
+        assert_eq!(page.content_top(), 128.0);
+    }
+
+    #[test]
+    fn should_apply_theme_colors_to_table_styles() {
+        let theme = Theme::from_json(r##"{ "primary_color": "#1f4e79", "rule_color": "#999999", "title_font_size": 18 }"##).unwrap();
+        let style = themed_table_style(&theme, TableStyle::default());
+        let primary = Color::from_hex("#1f4e79");
+        assert_eq!(style.header_text_color, primary);
+        assert_eq!(style.header_border.map(|border| border.color), Color::from_hex("#999999"));
+        assert_eq!(style.header_fill, None);
+        assert_eq!(style.text_color, None);
+        assert_eq!(themed_table_style(&Theme::default(), TableStyle::default()), TableStyle::default());
+    }
+
+    #[test]
+    fn should_reject_margins_without_room_for_content() {
+        let options = RenderOptions {
+            page_size: PageSize::Custom { width: 100.0, height: 100.0 },
+            margins: Margins { top: 50.0, right: 10.0, bottom: 50.0, left: 10.0 },
+            ..Default::default()
+        };
+        assert_eq!(PageLayout::new(&options).unwrap_err().to_string(), "The margins leave no room for the content on a 100 x 100 mm page");
+    }
+}
@@ -0,0 +1,203 @@
+use anyhow::Error;
+use crate::drawing::{DrawOp, Font};
+use crate::theme::Color;
+use crate::components::{fill_rectangles, stroke_line, Component};
+use crate::components::label::Label;
+use crate::components::qr_code::QrCode;
+use crate::invoice::Invoice;
+use crate::payment::swiss_qr_bill::{SwissAddress, SwissQrBill, SwissReference};
+use crate::renderer::rendering_context::RenderingContext;
+
+/// Height of the payment part and receipt at the bottom of the page. The bill is drawn 210 mm wide
+/// from x = 0 and centred on wider pages by the renderer.
+pub(crate) const SWISS_QR_BILL_HEIGHT: f32 = 105.0;
+const RECEIPT_WIDTH: f32 = 62.0;
+const RECEIPT_X: f32 = 5.0;
+const PAYMENT_PART_X: f32 = RECEIPT_WIDTH + 5.0;
+const INFORMATION_X: f32 = PAYMENT_PART_X + 51.0;
+const QR_CODE_SIZE: f32 = 46.0;
+const SWISS_CROSS_SIZE: f32 = 7.0;
+/// The style guide allows only Helvetica, Arial, Frutiger and Liberation Sans, so the default fonts
+/// are used whatever the font family of the theme
+const REGULAR_FONT: Font = Font::REGULAR;
+const BOLD_FONT: Font = Font::BOLD;
+
+pub(crate) fn swiss_qr_bill(invoice: &Invoice, rendering_context: &RenderingContext) -> Result<Vec<DrawOp>, Error> {
+    let bill = SwissQrBill::from_invoice(invoice)?;
+    let mut ops = perforation(rendering_context);
+    ops.extend(receipt(&bill, rendering_context));
+    ops.extend(payment_part(&bill, rendering_context)?);
+    Ok(ops)
+}
+
+fn perforation(rendering_context: &RenderingContext) -> Vec<DrawOp> {
+    let mut ops = vec![
+        DrawOp::SetLineWidth(0.5),
+        DrawOp::SetLineDash(Some(3.0)),
+        stroke_line(&[(0.0, SWISS_QR_BILL_HEIGHT), (210.0, SWISS_QR_BILL_HEIGHT)]),
+        stroke_line(&[(RECEIPT_WIDTH, 0.0), (RECEIPT_WIDTH, SWISS_QR_BILL_HEIGHT)]),
+        DrawOp::SetLineDash(None)
+    ];
+    ops.extend(
+        Label::new(rendering_context.translations.payment.swiss_qr_bill.separate_before_paying_in, 6.0, REGULAR_FONT)
+            .render_at(PAYMENT_PART_X + 20.0, SWISS_QR_BILL_HEIGHT + 1.5)
+    );
+    ops
+}
+
+fn receipt(bill: &SwissQrBill, rendering_context: &RenderingContext) -> Vec<DrawOp> {
+    let translations = &rendering_context.translations.payment.swiss_qr_bill;
+    let mut ops = Label::new(translations.receipt, 11.0, BOLD_FONT).render_at(RECEIPT_X, 97.0);
+    ops.extend(Label::new(translations.account_payable_to, 6.0, BOLD_FONT).render_at(RECEIPT_X, 91.0));
+    ops.extend(Label::new(&creditor_lines(bill), 8.0, REGULAR_FONT).render_at(RECEIPT_X, 88.0));
+    if let Some(reference) = formatted_reference(&bill.reference) {
+        ops.extend(Label::new(translations.reference, 6.0, BOLD_FONT).render_at(RECEIPT_X, 71.0));
+        ops.extend(Label::new(&reference, 8.0, REGULAR_FONT).render_at(RECEIPT_X, 68.0));
+    }
+    match &bill.debtor {
+        Some(debtor) => {
+            ops.extend(Label::new(translations.payable_by, 6.0, BOLD_FONT).render_at(RECEIPT_X, 63.0));
+            ops.extend(Label::new(&address_lines(debtor), 8.0, REGULAR_FONT).render_at(RECEIPT_X, 60.0));
+        },
+        None => {
+            ops.extend(Label::new(translations.payable_by_name_address, 6.0, BOLD_FONT).render_at(RECEIPT_X, 63.0));
+            ops.extend(corner_marks(RECEIPT_X, 61.0, 52.0, 20.0));
+        }
+    }
+    ops.extend(Label::new(translations.currency, 6.0, BOLD_FONT).render_at(RECEIPT_X, 35.0));
+    ops.extend(Label::new(translations.amount, 6.0, BOLD_FONT).render_at(RECEIPT_X + 13.0, 35.0));
+    ops.extend(Label::new(&bill.currency, 8.0, REGULAR_FONT).render_at(RECEIPT_X, 31.0));
+    ops.extend(Label::new(&formatted_amount(bill), 8.0, REGULAR_FONT).render_at(RECEIPT_X + 13.0, 31.0));
+    ops.extend(Label::new(translations.acceptance_point, 6.0, BOLD_FONT).render_at(RECEIPT_X + 33.0, 20.0));
+    ops
+}
+
+fn payment_part(bill: &SwissQrBill, rendering_context: &RenderingContext) -> Result<Vec<DrawOp>, Error> {
+    let translations = &rendering_context.translations.payment.swiss_qr_bill;
+    let mut ops = Label::new(translations.payment_part, 11.0, BOLD_FONT).render_at(PAYMENT_PART_X, 97.0);
+    ops.extend(QrCode::new(&bill.to_payload(), QR_CODE_SIZE)?.render_at(PAYMENT_PART_X, 88.0));
+    ops.extend(swiss_cross(PAYMENT_PART_X + QR_CODE_SIZE / 2.0, 88.0 - QR_CODE_SIZE / 2.0));
+    ops.extend(Label::new(translations.currency, 8.0, BOLD_FONT).render_at(PAYMENT_PART_X, 35.0));
+    ops.extend(Label::new(translations.amount, 8.0, BOLD_FONT).render_at(PAYMENT_PART_X + 13.0, 35.0));
+    ops.extend(Label::new(&bill.currency, 10.0, REGULAR_FONT).render_at(PAYMENT_PART_X, 30.0));
+    ops.extend(Label::new(&formatted_amount(bill), 10.0, REGULAR_FONT).render_at(PAYMENT_PART_X + 13.0, 30.0));
+
+    ops.extend(Label::new(translations.account_payable_to, 8.0, BOLD_FONT).render_at(INFORMATION_X, 97.0));
+    ops.extend(Label::new(&creditor_lines(bill), 10.0, REGULAR_FONT).render_at(INFORMATION_X, 93.0));
+    if let Some(reference) = formatted_reference(&bill.reference) {
+        ops.extend(Label::new(translations.reference, 8.0, BOLD_FONT).render_at(INFORMATION_X, 72.0));
+        ops.extend(Label::new(&reference, 10.0, REGULAR_FONT).render_at(INFORMATION_X, 68.0));
+    }
+    if !bill.message.is_empty() {
+        ops.extend(Label::new(translations.additional_information, 8.0, BOLD_FONT).render_at(INFORMATION_X, 62.0));
+        ops.extend(Label::new(&bill.message, 10.0, REGULAR_FONT).render_at(INFORMATION_X, 58.0));
+    }
+    match &bill.debtor {
+        Some(debtor) => {
+            ops.extend(Label::new(translations.payable_by, 8.0, BOLD_FONT).render_at(INFORMATION_X, 52.0));
+            ops.extend(Label::new(&address_lines(debtor), 10.0, REGULAR_FONT).render_at(INFORMATION_X, 48.0));
+        },
+        None => {
+            ops.extend(Label::new(translations.payable_by_name_address, 8.0, BOLD_FONT).render_at(INFORMATION_X, 52.0));
+            ops.extend(corner_marks(INFORMATION_X, 50.0, 65.0, 25.0));
+        }
+    }
+    Ok(ops)
+}
+
+/// Swiss cross centered at (x, y): white border, black square and a white cross
+fn swiss_cross(x: f32, y: f32) -> Vec<DrawOp> {
+    let half_size = SWISS_CROSS_SIZE / 2.0;
+    let square_size = SWISS_CROSS_SIZE - 1.0;
+    let arm_length = square_size * 20.0 / 32.0;
+    let arm_width = square_size * 6.0 / 32.0;
+    vec![
+        DrawOp::SetFillColor(Color::WHITE),
+        fill_rectangles(&[(x - half_size, y - half_size, SWISS_CROSS_SIZE, SWISS_CROSS_SIZE)]),
+        DrawOp::SetFillColor(Color::BLACK),
+        fill_rectangles(&[(x - square_size / 2.0, y - square_size / 2.0, square_size, square_size)]),
+        DrawOp::SetFillColor(Color::WHITE),
+        fill_rectangles(&[
+            (x - arm_width / 2.0, y - arm_length / 2.0, arm_width, arm_length),
+            (x - arm_length / 2.0, y - arm_width / 2.0, arm_length, arm_width)
+        ]),
+        DrawOp::SetFillColor(Color::BLACK)
+    ]
+}
+
+/// Corner marks of an empty field to be filled in by hand, (x, y) is the top left corner
+fn corner_marks(x: f32, y: f32, width: f32, height: f32) -> Vec<DrawOp> {
+    let mark = 3.0;
+    let (right, bottom) = (x + width, y - height);
+    vec![
+        DrawOp::SetLineWidth(0.75),
+        stroke_line(&[(x, y - mark), (x, y), (x + mark, y)]),
+        stroke_line(&[(right - mark, y), (right, y), (right, y - mark)]),
+        stroke_line(&[(x, bottom + mark), (x, bottom), (x + mark, bottom)]),
+        stroke_line(&[(right - mark, bottom), (right, bottom), (right, bottom + mark)])
+    ]
+}
+
+fn creditor_lines(bill: &SwissQrBill) -> String {
+    format!("{}\n{}", grouped(&bill.iban, 4), address_lines(&bill.creditor))
+}
+
+fn address_lines(address: &SwissAddress) -> String {
+    format!("{}\n{}\n{} {}", address.name, address.street, address.postal_code, address.town)
+}
+
+fn formatted_reference(reference: &SwissReference) -> Option<String> {
+    match reference {
+        SwissReference::Qrr(reference) => Some(format!("{} {}", &reference[..2], grouped(&reference[2..], 5))),
+        SwissReference::Scor(reference) => Some(grouped(reference, 4)),
+        SwissReference::None => None
+    }
+}
+
+/// Amount with a space as the thousands separator and a decimal point, e.g. "1 949.75"
+fn formatted_amount(bill: &SwissQrBill) -> String {
+    let amount = bill.amount.to_string();
+    let (units, decimals) = amount.split_once('.').unwrap_or((&amount, "00"));
+    let digits: Vec<char> = units.chars().collect();
+    let groups: Vec<String> = digits.rchunks(3).rev().map(|group| group.iter().collect()).collect();
+    format!("{}.{}", groups.join(" "), decimals)
+}
+
+fn grouped(value: &str, group_size: usize) -> String {
+    let characters: Vec<char> = value.chars().collect();
+    characters.chunks(group_size).map(|group| group.iter().collect::<String>()).collect::<Vec<String>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::INVOICE;
+    use crate::invoice::parse_invoice_json;
+    use crate::locale::get_translations;
+    use crate::renderer::options::RenderOptions;
+    use crate::renderer::rendering_context::init_rendering_context;
+    use crate::theme::Theme;
+
+    #[test]
+    fn should_group_characters() {
+        assert_eq!(grouped("CH4431999123000889012", 4), "CH44 3199 9123 0008 8901 2");
+        assert_eq!(formatted_reference(&SwissReference::Qrr("210000000003139471430009017".to_owned())).unwrap(), "21 00000 00003 13947 14300 09017");
+    }
+
+    #[test]
+    fn should_keep_the_default_fonts_with_a_theme_font() {
+        let mut invoice = parse_invoice_json(INVOICE).unwrap();
+        invoice.bank_details.account_number = "CH93 0076 2011 6238 5295 7".to_owned();
+        invoice.billed_by.country_code = Some("CH".to_owned());
+        let theme = Theme { font_family: Some("Liberation Serif".to_owned()), ..Default::default() };
+        let options = RenderOptions { theme: Some(theme), ..Default::default() };
+        let rendering_context = init_rendering_context(&invoice, get_translations(&invoice.locale).unwrap(), &invoice.locale, &options).unwrap();
+        assert!(rendering_context.regular_font.family.is_some());
+        let fonts: Vec<Font> = swiss_qr_bill(&invoice, &rendering_context).unwrap().into_iter().filter_map(|op| match op {
+            DrawOp::Text { font, .. } => Some(font),
+            _ => None
+        }).collect();
+        assert!(!fonts.is_empty());
+        assert!(fonts.iter().all(|font| font.family.is_none()));
+    }
+}
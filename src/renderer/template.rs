@@ -0,0 +1,228 @@
+//! Declarative description of the PDF layout: which blocks the first page header and the end of
+//! the last page show, in which regions, and the fonts and sizes of the blocks, lines and footer.
+//! The widths are scaled to the content width of the page, the font sizes and heights are kept.
+
+use anyhow::Error;
+use serde::Deserialize;
+use crate::components::layout::Alignment;
+use crate::components::table::TableStyle;
+use crate::drawing::FontWeight;
+
+/// Layout of the current invoice design
+pub const DEFAULT_TEMPLATE: &str = include_str!("default_template.json");
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Template {
+    /// Width of the page content in mm the widths of the template add up to
+    pub content_width: f32,
+    /// Columns of the first page header from left to right, every column needs a width
+    pub header: Vec<Region>,
+    /// Space between the header columns in mm
+    pub column_spacing: f32,
+    pub lines: Lines,
+    /// Blocks below the invoice lines on the last page, as wide as the page content
+    pub tail: Region,
+    /// Seller details at the bottom of the last page, left out when `null`
+    pub footer: Option<Footer>,
+    #[serde(default)]
+    pub table_style: TableStyle
+}
+
+/// Blocks stacked from the top down with the given space between them, sizes are in mm
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Region {
+    pub width: Option<f32>,
+    pub spacing: f32,
+    pub blocks: Vec<Block>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    Logo,
+    Title,
+    InvoiceInfo,
+    BilledTo,
+    BilledBy,
+    Summary,
+    Note,
+    Description
+}
+
+/// Block of a region, the options left out get the defaults of the block
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Block {
+    pub block: BlockKind,
+    /// Font size in points
+    pub font_size: Option<f32>,
+    pub font: Option<FontWeight>,
+    /// Height of the table rows in mm
+    pub row_height: Option<f32>,
+    pub align: Option<Alignment>,
+    /// Widths of the label and value columns of the invoice info and summary tables in mm
+    pub column_widths: Option<[f32; 2]>,
+    /// Box the logo is fitted into in mm, as wide as the region and 20 mm high by default
+    pub max_width: Option<f32>,
+    pub max_height: Option<f32>
+}
+
+impl Block {
+    pub fn font_size(&self) -> f32 {
+        self.font_size.unwrap_or(match self.block {
+            BlockKind::Title => 22.0,
+            BlockKind::InvoiceInfo | BlockKind::BilledTo | BlockKind::BilledBy => 11.0,
+            _ => 10.0
+        })
+    }
+
+    pub fn font(&self) -> FontWeight {
+        self.font.unwrap_or(if self.block == BlockKind::Title { FontWeight::Bold } else { FontWeight::Regular })
+    }
+
+    pub fn row_height(&self) -> f32 {
+        self.row_height.unwrap_or(5.0)
+    }
+
+    pub fn align(&self) -> Alignment {
+        self.align.unwrap_or(Alignment::Left)
+    }
+
+    /// Width and height of the box the logo is fitted into within the given width
+    pub fn logo_box(&self, width: f32) -> (f32, f32) {
+        (self.max_width.unwrap_or(width).min(width), self.max_height.unwrap_or(20.0))
+    }
+
+    /// Column widths of a two column table, by default the given width split evenly
+    pub fn column_widths(&self, width: f32) -> [f32; 2] {
+        self.column_widths.unwrap_or([width / 2.0, width / 2.0])
+    }
+}
+
+/// Invoice line table with the item, quantity, price, price without VAT and VAT columns
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Lines {
+    pub font_size: f32,
+    pub row_height: f32,
+    pub column_widths: [f32; 5]
+}
+
+/// Address, identifier and contact columns of the seller
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Footer {
+    pub font_size: f32,
+    pub row_height: f32,
+    pub column_widths: [f32; 3]
+}
+
+impl Template {
+    pub fn from_json(json: &str) -> Result<Template, Error> {
+        let template: Template = serde_json::from_str(json)
+            .map_err(|e| Error::msg(format!("Invalid template: {}", e)))?;
+        if template.content_width <= 0.0 {
+            return Err(Error::msg("Invalid template: the content width must be positive"));
+        }
+        if template.header.iter().any(|region| region.width.is_none()) {
+            return Err(Error::msg("Invalid template: every header column needs a width"));
+        }
+        Ok(template)
+    }
+
+    /// The template with its widths scaled from its own content width to the given one
+    pub fn scaled_to(&self, content_width: f32) -> Template {
+        let scale = content_width / self.content_width;
+        let scale_region = |region: &Region| Region {
+            width: region.width.map(|width| width * scale),
+            blocks: region.blocks.iter().map(|block| Block {
+                column_widths: block.column_widths.map(|widths| widths.map(|width| width * scale)),
+                max_width: block.max_width.map(|width| width * scale),
+                ..block.clone()
+            }).collect(),
+            ..region.clone()
+        };
+        Template {
+            content_width,
+            header: self.header.iter().map(scale_region).collect(),
+            column_spacing: self.column_spacing * scale,
+            lines: Lines {
+                column_widths: self.lines.column_widths.map(|width| width * scale),
+                ..self.lines.clone()
+            },
+            tail: scale_region(&self.tail),
+            footer: self.footer.as_ref().map(|footer| Footer {
+                column_widths: footer.column_widths.map(|width| width * scale),
+                ..footer.clone()
+            }),
+            table_style: self.table_style
+        }
+    }
+}
+
+impl Default for Template {
+    fn default() -> Self {
+        Template::from_json(DEFAULT_TEMPLATE).expect("The built-in template is valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_the_default_template() {
+        let template = Template::default();
+        assert_eq!(template.header.len(), 2);
+        assert_eq!(template.lines.column_widths, [80.0, 20.0, 30.0, 40.0, 15.0]);
+        assert_eq!(template.tail.blocks[0].block, BlockKind::Summary);
+        assert_eq!(template.tail.blocks[0].align(), Alignment::Right);
+        assert_eq!(template.table_style, TableStyle::default());
+    }
+
+    #[test]
+    fn should_default_block_options_by_kind() {
+        let template = Template::from_json(r##"{
+            "content_width": 185,
+            "header": [{ "width": 185, "spacing": 4, "blocks": [{ "block": "title" }, { "block": "billed_to", "font": "bold" }] }],
+            "column_spacing": 0,
+            "lines": { "font_size": 9, "row_height": 4.5, "column_widths": [85, 20, 30, 35, 15] },
+            "tail": { "spacing": 6, "blocks": [{ "block": "summary" }] },
+            "footer": null,
+            "table_style": { "header_fill": "#1f4e79", "stripe_fill": "#eeeeee" }
+        }"##).unwrap();
+        let blocks = &template.header[0].blocks;
+        assert_eq!((blocks[0].font_size(), blocks[0].font()), (22.0, FontWeight::Bold));
+        assert_eq!((blocks[1].font_size(), blocks[1].font()), (11.0, FontWeight::Bold));
+        assert_eq!(template.tail.blocks[0].column_widths(80.0), [40.0, 40.0]);
+        assert!(template.footer.is_none());
+        assert!(template.table_style.header_fill.is_some());
+        assert_eq!(template.table_style.cell_padding, TableStyle::default().cell_padding);
+    }
+
+    #[test]
+    fn should_scale_widths_to_the_content_width() {
+        // A5 with 15 mm margins on both sides
+        let template = Template::default().scaled_to(118.0);
+        let scale = 118.0 / 185.0;
+        assert_eq!(template.content_width, 118.0);
+        assert_eq!(template.header[0].width, Some(90.0 * scale));
+        assert_eq!(template.column_spacing, 5.0 * scale);
+        assert!((template.lines.column_widths.iter().sum::<f32>() - 118.0).abs() < 0.001);
+        assert_eq!(template.tail.blocks[0].column_widths, Some([45.0 * scale, 30.0 * scale]));
+        assert_eq!(template.lines.font_size, 10.0);
+        assert_eq!(Template::default().scaled_to(185.0), Template::default());
+    }
+
+    #[test]
+    fn should_reject_invalid_templates() {
+        let error = Template::from_json(r#"{ "header": [] }"#).unwrap_err();
+        assert!(error.to_string().starts_with("Invalid template: missing field"));
+        let unknown_block = DEFAULT_TEMPLATE.replace("\"note\"", "\"notes\"");
+        assert!(Template::from_json(&unknown_block).unwrap_err().to_string().contains("unknown variant `notes`"));
+        let no_width = DEFAULT_TEMPLATE.replacen("\"width\": 90,", "", 1);
+        assert_eq!(Template::from_json(&no_width).unwrap_err().to_string(), "Invalid template: every header column needs a width");
+    }
+}
@@ -0,0 +1,176 @@
+use anyhow::Error;
+use crate::format::{format_price, format_vat};
+use crate::invoice::{Invoice, InvoiceTotals};
+use crate::locale::get_translations;
+use crate::renderer::rendering_context::currency_symbol;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextFormat {
+    /// Plain text with space aligned columns
+    Plain,
+    Markdown
+}
+
+/// Renders a short summary of the invoice for email bodies and chat messages: invoice details,
+/// lines, totals and payment details, formatted the same way as the PDF
+pub fn render_text(invoice: &Invoice, format: TextFormat) -> Result<String, Error> {
+    let translations = get_translations(&invoice.locale)?;
+    let currency = currency_symbol(&invoice.currency);
+    let price = |value| format_price(value, currency, &invoice.locale);
+    let InvoiceTotals { total_price, total_vat, total_price_without_vat, line_prices_without_vat } = invoice.totals();
+    let line = &translations.invoice.line;
+
+    let mut details = vec![
+        (translations.invoice.number, invoice.invoice_number.clone()),
+        (translations.invoice.date, invoice.billed_at.clone()),
+        (translations.invoice.due_date, invoice.due_date.clone())
+    ];
+    let mut lines = vec![[
+        line.item.to_owned(),
+        line.quantity.to_owned(),
+        line.price.to_owned(),
+        line.price_without_tax.to_owned(),
+        format!("{} %", line.vat)
+    ]];
+    lines.extend(invoice.invoice_lines.iter().zip(line_prices_without_vat.iter()).map(|(invoice_line, price_without_vat)| [
+        invoice_line.name.clone(),
+        invoice_line.count.to_string(),
+        price(&invoice_line.price),
+        price(price_without_vat),
+        format_vat(&invoice.vat_percent)
+    ]));
+    let totals = [
+        (translations.invoice.total_price_without_tax.to_owned(), price(&total_price_without_vat)),
+        (format!("{} {} %", translations.invoice.vat, format_vat(&invoice.vat_percent)), price(&total_vat)),
+        (translations.invoice.total_price.to_owned(), price(&total_price))
+    ];
+    let mut payment = vec![
+        (translations.account.number, invoice.bank_details.account_number.clone()),
+        (translations.account.bic, invoice.bank_details.bic_code.clone())
+    ];
+    if let Some(reference_id) = invoice.reference_id.as_ref() {
+        payment.push((translations.invoice.reference_number, reference_id.clone()));
+    }
+    details.retain(|(_, value)| !value.is_empty());
+
+    let title = format!("{} {}", translations.invoice.invoice, invoice.invoice_number);
+    let mut text = String::new();
+    match format {
+        TextFormat::Plain => {
+            text.push_str(&format!("{}\n{}\n\n", title, "=".repeat(title.chars().count())));
+            text.push_str(&format!("{}\n\n", invoice.billed_by.name));
+            push_plain_fields(&mut text, &details);
+            text.push_str(&plain_table(&lines));
+            text.push('\n');
+            let totals: Vec<(&str, String)> = totals.iter().map(|(label, value)| (label.as_str(), value.clone())).collect();
+            push_plain_fields(&mut text, &totals);
+            push_plain_fields(&mut text, &payment);
+        }
+        TextFormat::Markdown => {
+            text.push_str(&format!("# {}\n\n", markdown(&title)));
+            text.push_str(&format!("{}\n\n", markdown(&invoice.billed_by.name)));
+            push_markdown_fields(&mut text, &details);
+            for (index, row) in lines.iter().enumerate() {
+                let cells: Vec<String> = row.iter().map(|cell| markdown(cell).replace('|', "\\|")).collect();
+                text.push_str(&format!("| {} |\n", cells.join(" | ")));
+                if index == 0 {
+                    text.push_str("| --- | ---: | ---: | ---: | ---: |\n");
+                }
+            }
+            text.push('\n');
+            for (index, (label, value)) in totals.iter().enumerate() {
+                let field = format!("{}: {}", markdown(label), markdown(value));
+                // The last row is the total price to pay
+                let field = if index == totals.len() - 1 { format!("**{}**", field) } else { field };
+                text.push_str(&format!("- {}\n", field));
+            }
+            text.push('\n');
+            push_markdown_fields(&mut text, &payment);
+        }
+    }
+    for paragraph in [invoice.note.as_ref(), invoice.invoice_description.as_ref()].into_iter().flatten() {
+        let paragraph = if format == TextFormat::Markdown { markdown(paragraph) } else { paragraph.clone() };
+        text.push_str(&format!("{}\n\n", paragraph.trim_end()));
+    }
+    Ok(format!("{}\n", text.trim_end()))
+}
+
+fn push_plain_fields(text: &mut String, fields: &[(&str, String)]) {
+    let width = fields.iter().map(|(label, _)| label.chars().count() + 1).max().unwrap_or(0);
+    for (label, value) in fields {
+        text.push_str(&format!("{:<width$} {}\n", format!("{}:", label), value, width = width));
+    }
+    text.push('\n');
+}
+
+fn push_markdown_fields(text: &mut String, fields: &[(&str, String)]) {
+    for (label, value) in fields {
+        text.push_str(&format!("- {}: {}\n", markdown(label), markdown(value)));
+    }
+    text.push('\n');
+}
+
+/// Aligns the columns with spaces, the first column to the left and the numbers to the right
+fn plain_table(rows: &[[String; 5]]) -> String {
+    let widths: Vec<usize> = (0..5)
+        .map(|column| rows.iter().map(|row| row[column].chars().count()).max().unwrap_or(0))
+        .collect();
+    rows.iter().map(|row| {
+        let cells: Vec<String> = row.iter().zip(widths.iter()).enumerate()
+            .map(|(column, (cell, width))| match column {
+                0 => format!("{:<width$}", cell, width = width),
+                _ => format!("{:>width$}", cell, width = width)
+            })
+            .collect();
+        format!("{}\n", cells.join("  ").trim_end())
+    }).collect()
+}
+
+/// Escapes the characters that would otherwise start Markdown formatting
+fn markdown(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for character in value.chars() {
+        if matches!(character, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(character);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::test_fixtures::invoice_with;
+
+    #[test]
+    fn should_render_plain_text() {
+        let invoice = invoice_with(json!({
+            "locale": "fi-FI",
+            "billed_by": { "name": "Example_Company Oy" },
+            "invoice_lines": [
+                { "name": "Computer services", "count": 1, "price": 89.99 },
+                { "name": "CD discs", "count": 5, "price": 35 }
+            ]
+        }));
+        let text = render_text(&invoice, TextFormat::Plain).unwrap();
+        assert!(text.starts_with("Lasku 2025-0001\n===============\n\nExample_Company Oy\n\n"));
+        assert!(text.contains("Eräpäivä:    28.02.2025\n"));
+        assert!(text.contains("Computer services      1          89,99 €        71,71 €   25,5\n"));
+        assert!(text.contains("CD discs               5          35,00 €        27,88 €   25,5\n"));
+        assert!(text.contains("Summa yhteensä:         124,99 €\n"));
+        assert!(text.ends_with("Viitenumero: RF18 5390 0754 7034\n"));
+    }
+
+    #[test]
+    fn should_render_markdown() {
+        let invoice = invoice_with(json!({ "locale": "fi-FI", "billed_by": { "name": "Example_Company Oy" } }));
+        let text = render_text(&invoice, TextFormat::Markdown).unwrap();
+        assert!(text.starts_with("# Lasku 2025-0001\n\nExample\\_Company Oy\n\n- Laskunumero: 2025-0001\n"));
+        assert!(text.contains("| Tuote | Määrä | Hinta(sis. ALV) | Veroton hinta | ALV % |\n| --- | ---: | ---: | ---: | ---: |\n"));
+        assert!(text.contains("| Consulting | 2 | 251,00 € | 200,00 € | 25,5 |\n"));
+        assert!(text.contains("- **Summa yhteensä: 251,00 €**\n"));
+        assert!(text.contains("- Tilinumero: FI21 1234 5600 0007 85\n"));
+    }
+}
@@ -0,0 +1,56 @@
+use serde_json::Value;
+use crate::invoice::{parse_invoice_json, Invoice};
+
+/// Invoice shared by the tests, complete enough for every export format
+pub(crate) const INVOICE: &str = r#"{
+    "billed_by": {
+        "name": "Example Company Ltd",
+        "address_line_1": "123 Business Street",
+        "postal_code": "00100",
+        "town": "Helsinki",
+        "country_code": "FI",
+        "company_id": "1234567-8",
+        "vat_id": "FI12345678",
+        "email": "contact@examplecompany.fi"
+    },
+    "billed_to": {
+        "name": "Client GmbH",
+        "address_line_1": "Hauptstrasse 1",
+        "postal_code": "10115",
+        "town": "Berlin",
+        "country_code": "DE",
+        "vat_id": "DE123456789",
+        "endpoint_id": "0204:991-12345-67"
+    },
+    "bank_details": { "account_number": "FI21 1234 5600 0007 85", "bic_code": "NDEAFIHH" },
+    "currency": "EUR",
+    "vat_percent": 25.5,
+    "billed_at": "04.02.2025",
+    "due_date": "28.02.2025",
+    "invoice_number": "2025-0001",
+    "buyer_reference": "991-12345-67",
+    "reference_id": "RF18 5390 0754 7034",
+    "invoice_lines": [
+        { "name": "Consulting", "count": 2, "price": 251 }
+    ],
+    "locale": "en-GB"
+}"#;
+
+/// The shared invoice with the fields of `overrides` replaced, objects such as `billed_by` are
+/// merged field by field and `null` clears an optional field
+pub(crate) fn invoice_with(overrides: Value) -> Invoice {
+    let mut invoice: Value = serde_json::from_str(INVOICE).unwrap();
+    merge(&mut invoice, overrides);
+    parse_invoice_json(&invoice.to_string()).unwrap()
+}
+
+fn merge(value: &mut Value, overrides: Value) {
+    match (value, overrides) {
+        (Value::Object(fields), Value::Object(overrides)) => {
+            for (name, value) in overrides {
+                merge(fields.entry(name).or_insert(Value::Null), value);
+            }
+        }
+        (value, overrides) => *value = overrides
+    }
+}
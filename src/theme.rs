@@ -0,0 +1,82 @@
+//! Brand colors and fonts of the seller, part of the invoice data and applied by the renderers
+
+use anyhow::Error;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32
+}
+
+impl Color {
+    pub const BLACK: Color = Color { r: 0.0, g: 0.0, b: 0.0 };
+    pub const WHITE: Color = Color { r: 1.0, g: 1.0, b: 1.0 };
+
+    /// Color from a "#rrggbb" hex string
+    pub fn from_hex(hex: &str) -> Option<Color> {
+        let digits = hex.strip_prefix('#')?;
+        if digits.len() != 6 || !digits.is_ascii() {
+            return None;
+        }
+        let channel = |index: usize| u8::from_str_radix(&digits[index..index + 2], 16).ok().map(|value| value as f32 / 255.0);
+        Some(Color { r: channel(0)?, g: channel(2)?, b: channel(4)? })
+    }
+
+    pub fn to_hex(&self) -> String {
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        format!("#{:02x}{:02x}{:02x}", channel(self.r), channel(self.g), channel(self.b))
+    }
+}
+
+/// Colors are written as "#rrggbb" in templates, themes and invoices
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        Color::from_hex(&hex).ok_or_else(|| de::Error::custom(format!("Invalid color \"{}\", expected #rrggbb", hex)))
+    }
+}
+
+/// Brand colors and fonts applied on top of the template, the fields left out keep the look of
+/// the template
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// Titles, table headers and the seller details in the footer
+    pub primary_color: Option<Color>,
+    /// Fill behind the table headers
+    pub accent_color: Option<Color>,
+    /// Body text, e.g. the addresses, invoice details, lines and totals
+    pub text_color: Option<Color>,
+    /// Table borders and the separator lines above the carried forward row and the footer
+    pub rule_color: Option<Color>,
+    /// Font size of the title in points
+    pub title_font_size: Option<f32>,
+    /// Installed font family used instead of the default fonts, e.g. "Liberation Serif"
+    pub font_family: Option<String>
+}
+
+impl Theme {
+    pub fn from_json(json: &str) -> Result<Theme, Error> {
+        serde_json::from_str(json).map_err(|e| Error::msg(format!("Invalid theme: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_reject_invalid_themes() {
+        let error = Theme::from_json(r#"{ "primary_color": "blue" }"#).unwrap_err();
+        assert!(error.to_string().starts_with("Invalid theme: Invalid color \"blue\", expected #rrggbb"));
+        assert!(Theme::from_json(r##"{ "secondary_color": "#000000" }"##).is_err());
+    }
+}